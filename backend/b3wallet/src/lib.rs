@@ -18,14 +18,29 @@ use b3_utils::{
 };
 use b3wallet_lib::{
     account::WalletAccount,
+    balance::{balance, invalidate_balance, refresh_balance, refresh_watched_balances},
+    btc_transfer::btc_swap_to_ckbtc,
+    bundle::{AccountBundle, ImportReport},
     error::WalletError,
     ledger::{
         btc::{network::BitcoinNetwork, types::UtxoStatus},
         chain::ChainTrait,
         ckbtc::{minter::Minter, types::RetrieveBtcStatus},
+        evm::{
+            address::EvmAddress,
+            network::EvmNetwork,
+            nft::{EvmNft, EvmNftApproval, EvmNftStandard},
+            types::EvmNonceStatus,
+            u256::U256,
+        },
         subaccount::SubaccountEcdsaTrait,
-        types::{AddressMap, Balance, BtcPending, ChainEnum, PendingEnum, SendResult},
+        types::{
+            AddressMap, Balance, BtcPending, CachedBalance, CachedBalanceMap, ChainEnum, ChainId,
+            PendingEnum, SendResult,
+        },
     },
+    portfolio::Portfolio,
+    send::send,
     setting::WalletSettings,
     store::{
         with_account, with_account_mut, with_chain, with_chain_mut, with_ledger, with_ledger_mut,
@@ -44,16 +59,28 @@ use ic_cdk::{
     },
     init, post_upgrade, pre_upgrade, query, update,
 };
+use ic_cdk_timers::{clear_timer, set_timer_interval, TimerId};
 use operations::{
     error::OperationError,
     operation::{
-        btc::transfer::BtcTransfer,
+        btc::{
+            batch::BtcBatchTransfer, bump::BtcBumpFee, cpfp::BtcChildPaysForParent,
+            transfer::BtcTransfer,
+        },
+        evm::{
+            EvmApproveNft, EvmContractCall, EvmDeployContract, EvmPersonalSign,
+            EvmRevokeNftApproval, EvmSignTypedData, EvmSignUserOperation, EvmTransferErc1155,
+            EvmTransferErc721,
+        },
         global::SendToken,
         icp::transfer::IcpTransfer,
         inner::account::{CreateAccount, RemoveAccount, RenameAccount},
         inner::user::AddUser,
         inner::{
-            setting::{UpdateCanisterSettings, UpgradeCanister},
+            setting::{
+                UpdateCanisterSettings, UpdateEvmCreate2Factory, UpdateEvmGasConfig,
+                UpdateEvmNetwork, UpgradeCanister,
+            },
             RemoveUser,
         },
         Operation, OperationState, OperationTrait,
@@ -70,6 +97,11 @@ use operations::{
     types::{PendingOperations, ProcessedOperations, RoleMap, UserMap, WalletSettingsAndSigners},
     user::{state::UserState, User},
 };
+use std::{cell::RefCell, time::Duration};
+
+thread_local! {
+    static BALANCE_TIMER: RefCell<Option<TimerId>> = RefCell::new(None);
+}
 
 #[init]
 fn init() {
@@ -143,6 +175,10 @@ fn post_upgrade() {
     with_users_mut(|users| *users = user_prev);
 
     with_roles_mut(|roles| *roles = role_prev);
+
+    if let Some(interval_secs) = with_setting(|s| s.balance_refresh_interval()) {
+        schedule_balance_refresh(interval_secs);
+    }
 }
 
 #[query(guard = "caller_is_signer")]
@@ -175,6 +211,35 @@ fn get_account_view(account_id: AccountId) -> WalletAccountView {
     with_account(&account_id, |account| account.view()).unwrap_or_else(panic_log)
 }
 
+#[query(guard = "caller_is_signer")]
+fn get_portfolio() -> Option<Portfolio> {
+    with_wallet(|s| s.portfolio().cloned())
+}
+
+#[query(guard = "caller_is_signer")]
+fn get_evm_networks() -> Vec<(ChainId, EvmNetwork)> {
+    let mut networks: Vec<(ChainId, EvmNetwork)> = with_setting(|s| {
+        s.evm_networks()
+            .iter()
+            .map(|(chain_id, network)| (*chain_id, network.clone()))
+            .collect()
+    });
+
+    networks.sort_by_key(|(chain_id, _)| *chain_id);
+
+    networks
+}
+
+#[query(guard = "caller_is_signer")]
+fn get_cached_balance(account_id: AccountId, chain: ChainEnum) -> Option<CachedBalance> {
+    with_chain(&account_id, &chain, |chain| chain.cached_balance()).unwrap_or_else(panic_log)
+}
+
+#[query(guard = "caller_is_signer")]
+fn get_cached_balances(account_id: AccountId) -> CachedBalanceMap {
+    with_ledger(&account_id, |ledger| ledger.cached_balances()).unwrap_or_else(panic_log)
+}
+
 #[query(guard = "caller_is_signer")]
 fn get_addresses(account_id: AccountId) -> AddressMap {
     with_ledger(&account_id, |ledger| ledger.address_map().clone()).unwrap_or_else(panic_log)
@@ -227,6 +292,33 @@ fn account_create(env: Option<Environment>, name: Option<String>) {
     with_wallet_mut(|s| s.insert_account(new_account, name));
 }
 
+#[update(guard = "caller_is_signer")]
+fn account_create_watch_only(env: Option<Environment>, name: Option<String>) {
+    log_cycle!("Create watch-only account: {:?} on env: {:?}", name, env);
+
+    let subaccount = with_wallet(|s| s.new_subaccount(env));
+
+    let new_account = WalletAccount::new_watch_only(subaccount, String::new());
+
+    with_wallet_mut(|s| s.insert_account(new_account, name));
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_add_watch_address(account_id: AccountId, chain: ChainEnum, address: String) {
+    log_cycle!(
+        "Add watch address: {} on chain: {:?} to account: {}",
+        address,
+        chain,
+        account_id
+    );
+
+    with_ledger_mut(&account_id, |ledger| {
+        ledger.add_watch_address(chain, address)
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log);
+}
+
 #[update(guard = "caller_is_signer")]
 fn account_rename(account_id: AccountId, name: String) {
     log_cycle!("Rename account: {} to {}", account_id, name);
@@ -242,9 +334,34 @@ fn account_hide(account_id: AccountId) {
 }
 
 #[update(guard = "caller_is_signer")]
-fn account_remove(account_id: AccountId) {
+fn account_unhide(account_id: AccountId) {
+    log_cycle!("Unhide account: {}", account_id);
+
+    with_account_mut(&account_id, |a| a.unhide()).unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_archive(account_id: AccountId) {
+    log_cycle!("Archive account: {}", account_id);
+
+    with_account_mut(&account_id, |a| a.archive()).unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_unarchive(account_id: AccountId) {
+    log_cycle!("Unarchive account: {}", account_id);
+
+    with_account_mut(&account_id, |a| a.unarchive()).unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+async fn account_remove(account_id: AccountId) {
     log_cycle!("Remove account: {}", account_id);
 
+    let account = with_account(&account_id, |a| a.clone()).unwrap_or_else(panic_log);
+
+    account.check_removable().await.unwrap_or_else(panic_log);
+
     with_wallet_mut(|s| s.remove_account(&account_id)).unwrap_or_else(panic_log);
 }
 
@@ -274,9 +391,7 @@ async fn account_balance(account_id: AccountId, chain: ChainEnum) -> Balance {
         chain
     );
 
-    let ledger = with_ledger(&account_id, |ledger| ledger.clone()).unwrap_or_else(panic_log);
-
-    let balance = ledger.balance(chain).await;
+    let balance = balance(&account_id, chain).await;
 
     match balance {
         Ok(balance) => balance,
@@ -284,6 +399,102 @@ async fn account_balance(account_id: AccountId, chain: ChainEnum) -> Balance {
     }
 }
 
+#[update(guard = "caller_is_signer")]
+async fn account_refresh_balance(account_id: AccountId, chain: ChainEnum) -> CachedBalance {
+    log_cycle!(
+        "Refresh balance for account: {} on chain: {:?}",
+        account_id,
+        chain
+    );
+
+    refresh_balance(&account_id, chain)
+        .await
+        .unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_watch(account_id: AccountId) {
+    log_cycle!("Watch account: {}", account_id);
+
+    with_account_mut(&account_id, |a| a.watch()).unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_unwatch(account_id: AccountId) {
+    log_cycle!("Unwatch account: {}", account_id);
+
+    with_account_mut(&account_id, |a| a.unwatch()).unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_admin")]
+fn set_balance_ttl(ttl: Option<u64>) {
+    log_cycle!("Set balance ttl: {:?}", ttl);
+
+    with_setting_mut(|s| s.set_balance_ttl(ttl));
+}
+
+#[update(guard = "caller_is_admin")]
+fn start_balance_refresh(interval_secs: u64) {
+    log_cycle!("Start balance refresh every {} seconds", interval_secs);
+
+    if interval_secs == 0 {
+        throw_log!("Interval must be greater than 0");
+    }
+
+    with_setting_mut(|s| s.set_balance_refresh_interval(Some(interval_secs)));
+
+    schedule_balance_refresh(interval_secs);
+}
+
+#[update(guard = "caller_is_admin")]
+fn stop_balance_refresh() {
+    log_cycle!("Stop balance refresh");
+
+    with_setting_mut(|s| s.set_balance_refresh_interval(None));
+
+    BALANCE_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            clear_timer(timer_id);
+        }
+    });
+}
+
+fn schedule_balance_refresh(interval_secs: u64) {
+    let timer_id = set_timer_interval(Duration::from_secs(interval_secs), || {
+        ic_cdk::spawn(async {
+            for (account_id, chain, result) in refresh_watched_balances().await {
+                if let Err(err) = result {
+                    log_cycle!(
+                        "Balance refresh failed for account: {} on chain: {:?}: {}",
+                        account_id,
+                        chain,
+                        err
+                    );
+                }
+            }
+        })
+    });
+
+    BALANCE_TIMER.with(|timer| {
+        if let Some(previous) = timer.borrow_mut().replace(timer_id) {
+            clear_timer(previous);
+        }
+    });
+}
+
+#[update(guard = "caller_is_signer")]
+async fn portfolio() -> Portfolio {
+    log_cycle!("Get portfolio");
+
+    let accounts = with_wallet(|s| s.accounts.clone());
+
+    let portfolio = Portfolio::fetch(&accounts).await;
+
+    with_wallet_mut(|s| s.set_portfolio(portfolio.clone()));
+
+    portfolio
+}
+
 #[update(guard = "caller_is_signer")]
 async fn account_send(
     account_id: AccountId,
@@ -299,12 +510,13 @@ async fn account_send(
         to
     );
 
-    let ledger = with_ledger(&account_id, |ledger| ledger.clone()).unwrap_or_else(panic_log);
-
-    ledger
-        .send(&chain, to, amount)
+    let result = send(&account_id, &chain, to, amount, None)
         .await
-        .unwrap_or_else(panic_log)
+        .unwrap_or_else(panic_log);
+
+    invalidate_balance(&account_id, chain).unwrap_or_else(panic_log);
+
+    result
 }
 
 #[update(guard = "caller_is_signer")]
@@ -324,7 +536,7 @@ async fn account_check_pending(account_id: AccountId, chain_enum: ChainEnum, pen
     match result {
         Ok(_) => {
             with_chain_mut(&account_id, chain_enum, |chain| {
-                chain.remove_pending(pending_index)
+                chain.confirm_pending(pending_index)
             })
             .unwrap_or_else(panic_log);
         }
@@ -360,6 +572,159 @@ async fn account_remove_pending(account_id: AccountId, chain: ChainEnum, pending
     .unwrap_or_else(panic_log);
 }
 
+#[update(guard = "caller_is_signer")]
+async fn account_evm_read_contract(
+    account_id: AccountId,
+    chain_id: ChainId,
+    contract_address: EvmAddress,
+    abi: String,
+    function: String,
+    args: String,
+) -> String {
+    log_cycle!(
+        "Read contract: {} function: {} on chain: {} for account: {}",
+        contract_address,
+        function,
+        chain_id,
+        account_id
+    );
+
+    let chain = with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())
+        .unwrap_or_else(panic_log)
+        .unwrap_or_else(panic_log);
+
+    chain
+        .read_contract(&contract_address, &abi, &function, &args)
+        .await
+        .unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+async fn account_evm_nonce_status(account_id: AccountId, chain_id: ChainId) -> EvmNonceStatus {
+    log_cycle!(
+        "Nonce status on chain: {} for account: {}",
+        chain_id,
+        account_id
+    );
+
+    let chain = with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())
+        .unwrap_or_else(panic_log)
+        .unwrap_or_else(panic_log);
+
+    chain.nonce_status().await.unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+async fn account_evm_reset_nonce(account_id: AccountId, chain_id: ChainId) -> EvmNonceStatus {
+    log_cycle!(
+        "Reset nonce on chain: {} for account: {}",
+        chain_id,
+        account_id
+    );
+
+    let chain = with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())
+        .unwrap_or_else(panic_log)
+        .unwrap_or_else(panic_log);
+
+    let status = chain.nonce_status().await.unwrap_or_else(panic_log);
+
+    with_chain_mut(&account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain.evm_mut().map(|evm| evm.reset_nonce(status.pending))
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log);
+
+    EvmNonceStatus::new(status.pending, status.latest, status.pending)
+}
+
+#[query(guard = "caller_is_signer")]
+fn account_evm_nfts(account_id: AccountId, chain_id: ChainId) -> Vec<EvmNft> {
+    with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| {
+        chain.evm().map(|evm| evm.nfts)
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log)
+}
+
+#[query(guard = "caller_is_signer")]
+fn account_evm_nft_approvals(account_id: AccountId, chain_id: ChainId) -> Vec<EvmNftApproval> {
+    with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| {
+        chain.evm().map(|evm| evm.nft_approvals)
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_signer")]
+async fn account_evm_add_nft(
+    account_id: AccountId,
+    chain_id: ChainId,
+    contract_address: EvmAddress,
+    token_id: U256,
+    standard: EvmNftStandard,
+) -> EvmNft {
+    log_cycle!(
+        "Add {} NFT: {} of {} on chain: {} for account: {}",
+        standard,
+        token_id,
+        contract_address,
+        chain_id,
+        account_id
+    );
+
+    let chain = with_chain(&account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())
+        .unwrap_or_else(panic_log)
+        .unwrap_or_else(panic_log);
+
+    // only what the chain says the account holds is added
+    let amount = chain
+        .nft_balance(&contract_address, token_id, standard)
+        .await
+        .unwrap_or_else(panic_log);
+
+    if amount.is_zero() {
+        throw_log!(
+            "NFT {} of {} is not held by this account",
+            token_id,
+            contract_address
+        );
+    }
+
+    let nft = EvmNft::new(contract_address, token_id, standard, amount);
+
+    with_chain_mut(&account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain.evm_mut().map(|evm| evm.add_nft(nft.clone()))
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log);
+
+    nft
+}
+
+#[update(guard = "caller_is_signer")]
+fn account_evm_remove_nft(
+    account_id: AccountId,
+    chain_id: ChainId,
+    contract_address: EvmAddress,
+    token_id: U256,
+) {
+    log_cycle!(
+        "Remove NFT: {} of {} on chain: {} for account: {}",
+        token_id,
+        contract_address,
+        chain_id,
+        account_id
+    );
+
+    with_chain_mut(&account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain
+            .evm_mut()
+            .map(|evm| evm.remove_nft(&contract_address, token_id))
+    })
+    .unwrap_or_else(panic_log)
+    .unwrap_or_else(panic_log);
+}
+
 #[update(guard = "caller_is_signer")]
 async fn account_swap_btc_to_ckbtc(
     account_id: AccountId,
@@ -373,23 +738,9 @@ async fn account_swap_btc_to_ckbtc(
         account_id
     );
 
-    let btc = with_chain(&account_id, &ChainEnum::BTC(network), |chain| chain.btc())
+    btc_swap_to_ckbtc(&account_id, network, amount)
+        .await
         .unwrap_or_else(panic_log)
-        .unwrap_or_else(panic_log);
-
-    let result = btc.swap_to_ckbtc(amount).await;
-
-    match result {
-        Ok(pending) => {
-            with_chain_mut(&account_id, ChainEnum::BTC(network), |chain| {
-                chain.add_pending(pending.clone().into())
-            })
-            .unwrap_or_else(panic_log);
-
-            pending
-        }
-        Err(err) => panic_log(err),
-    }
 }
 
 #[update(guard = "caller_is_signer")]
@@ -599,6 +950,31 @@ fn reset_accounts() {
     with_wallet_mut(|s| s.reset_accounts());
 }
 
+#[update(guard = "caller_is_admin")]
+async fn export_accounts() -> AccountBundle {
+    log_cycle!("Export accounts");
+
+    let payload = with_wallet(|s| s.export_accounts(ic_cdk::id(), ic_cdk::api::time()));
+
+    AccountBundle::sign(&payload)
+        .await
+        .unwrap_or_else(panic_log)
+}
+
+#[update(guard = "caller_is_admin")]
+async fn import_accounts(bundle: AccountBundle) -> ImportReport {
+    log_cycle!("Import accounts");
+
+    // the bundle must be signed by the canister it claims to come from
+    let exporter_public_key = bundle.exporter_public_key().await.unwrap_or_else(panic_log);
+
+    let payload = bundle
+        .verify(&exporter_public_key)
+        .unwrap_or_else(panic_log);
+
+    with_wallet_mut(|s| s.import_accounts(payload))
+}
+
 #[query(guard = "caller_is_signer")]
 fn setting_and_signer() -> WalletSettingsAndSigners {
     let settings = with_setting(|s| s.clone());
@@ -796,6 +1172,53 @@ fn request_update_settings(
     request_maker(request.into(), reason, deadline)
 }
 
+#[update(guard = "caller_is_admin")]
+fn request_update_evm_network(
+    request: UpdateEvmNetwork,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_update_evm_network: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_update_evm_gas_config(
+    request: UpdateEvmGasConfig,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_update_evm_gas_config: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_update_evm_create2_factory(
+    request: UpdateEvmCreate2Factory,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_update_evm_create2_factory: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request_maker(request.into(), reason, deadline)
+}
+
 #[update(guard = "caller_is_admin")]
 fn request_account_rename(
     request: RenameAccount,
@@ -871,6 +1294,206 @@ fn request_transfer_btc(
     request_maker(request.into(), reason, deadline)
 }
 
+#[update(guard = "caller_is_admin")]
+fn request_batch_transfer_btc(
+    request: BtcBatchTransfer,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_batch_transfer_btc: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_bump_fee_btc(
+    request: BtcBumpFee,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_bump_fee_btc: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_cpfp_btc(
+    request: BtcChildPaysForParent,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!("request_cpfp_btc: {:?} with reason: {}", request, reason);
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_contract_call(
+    request: EvmContractCall,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_contract_call: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_sign_typed_data(
+    request: EvmSignTypedData,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_sign_typed_data: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_personal_sign(
+    request: EvmPersonalSign,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_personal_sign: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_transfer_erc721(
+    request: EvmTransferErc721,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_transfer_erc721: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_transfer_erc1155(
+    request: EvmTransferErc1155,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_transfer_erc1155: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_approve_nft(
+    request: EvmApproveNft,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_approve_nft: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_revoke_nft_approval(
+    request: EvmRevokeNftApproval,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_revoke_nft_approval: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_deploy_contract(
+    request: EvmDeployContract,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_deploy_contract: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
+#[update(guard = "caller_is_admin")]
+fn request_evm_sign_user_operation(
+    request: EvmSignUserOperation,
+    reason: String,
+    deadline: Option<NanoTimeStamp>,
+) -> OperationId {
+    log_cycle!(
+        "request_evm_sign_user_operation: {:?} with reason: {}",
+        request,
+        reason
+    );
+
+    request.validate_request().unwrap_or_else(panic_log);
+
+    request_maker(request.into(), reason, deadline)
+}
+
 #[update(guard = "caller_is_signer")]
 fn request_send(
     request: SendToken,
@@ -1,4 +1,12 @@
-use crate::{ledger::ledger::Ledger, types::WalletAccountView};
+use crate::{
+    error::WalletError,
+    ledger::{
+        chain::{Chain, ChainTrait},
+        ledger::Ledger,
+        types::Balance,
+    },
+    types::WalletAccountView,
+};
 use b3_utils::{
     ledger::{Metadata, Value},
     Environment, Subaccount,
@@ -11,6 +19,9 @@ impl From<&WalletAccount> for WalletAccountView {
             id: account.id.clone(),
             name: account.name.clone(),
             hidden: account.hidden,
+            archived: account.archived,
+            watched: account.watched,
+            watch_only: account.ledger.is_watch_only(),
             metadata: account.metadata.clone(),
             environment: account.environment().clone(),
             pendings: account.ledger.pendings(),
@@ -24,6 +35,10 @@ pub struct WalletAccount {
     id: String,
     name: String,
     hidden: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    watched: bool,
     ledger: Ledger,
     metadata: Metadata,
 }
@@ -34,6 +49,8 @@ impl Default for WalletAccount {
             id: String::new(),
             name: String::new(),
             hidden: false,
+            archived: false,
+            watched: false,
             metadata: Metadata::default(),
             ledger: Ledger::default(),
         }
@@ -49,6 +66,8 @@ impl From<Subaccount> for WalletAccount {
             id,
             ledger,
             hidden: false,
+            archived: false,
+            watched: false,
             name: String::new(),
             metadata: Metadata::default(),
         }
@@ -65,6 +84,24 @@ impl WalletAccount {
             name,
             ledger,
             hidden: false,
+            archived: false,
+            watched: false,
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Create an account that only follows externally supplied addresses.
+    pub fn new_watch_only(subaccount: Subaccount, name: String) -> Self {
+        let id = subaccount.id();
+        let ledger = Ledger::new_watch_only(subaccount);
+
+        WalletAccount {
+            id,
+            name,
+            ledger,
+            hidden: false,
+            archived: false,
+            watched: false,
             metadata: Metadata::default(),
         }
     }
@@ -74,6 +111,9 @@ impl WalletAccount {
             id: self.id.clone(),
             name: self.name.clone(),
             hidden: self.hidden,
+            archived: self.archived,
+            watched: self.watched,
+            watch_only: self.ledger.is_watch_only(),
             metadata: self.metadata.clone(),
             addresses: self.ledger.address_map(),
             pendings: self.ledger.pendings(),
@@ -109,6 +149,18 @@ impl WalletAccount {
         self.hidden
     }
 
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    pub fn is_watched(&self) -> bool {
+        self.watched
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.ledger.is_watch_only()
+    }
+
     pub fn ledger_mut(&mut self) -> &mut Ledger {
         &mut self.ledger
     }
@@ -140,4 +192,43 @@ impl WalletAccount {
     pub fn unhide(&mut self) {
         self.hidden = false;
     }
+
+    pub fn archive(&mut self) {
+        self.archived = true;
+    }
+
+    pub fn unarchive(&mut self) {
+        self.archived = false;
+    }
+
+    /// Check that the account holds nothing before it is removed.
+    /// Every chain must have a zero balance and no open pendings.
+    /// Watched addresses are skipped, their funds are not held by the wallet.
+    pub async fn check_removable(&self) -> Result<(), WalletError> {
+        for (chain_type, chain) in self.ledger.chains.iter() {
+            if let Chain::WatchOnlyChain(_) = chain {
+                continue;
+            }
+
+            if !chain.pendings().is_empty() {
+                return Err(WalletError::AccountHasPendings(chain_type.clone()));
+            }
+
+            let balance = chain.balance().await?;
+
+            if balance != Balance::from(0u8) {
+                return Err(WalletError::AccountHasBalance(chain_type.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn watch(&mut self) {
+        self.watched = true;
+    }
+
+    pub fn unwatch(&mut self) {
+        self.watched = false;
+    }
 }
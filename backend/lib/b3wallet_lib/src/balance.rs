@@ -0,0 +1,105 @@
+use crate::{
+    error::WalletError,
+    ledger::{
+        chain::ChainTrait,
+        types::{Balance, CachedBalance, ChainEnum},
+    },
+    store::{with_chain, with_chain_mut, with_ledger, with_setting, with_wallet, with_wallet_mut},
+    types::AccountId,
+};
+use futures::future::join_all;
+
+#[cfg(test)]
+use b3_utils::mocks::time_mock as ic_timestamp;
+#[cfg(not(test))]
+use ic_cdk::api::time as ic_timestamp;
+
+/// Returns the balance of the chain.
+/// The cached balance is returned while it is fresh, otherwise the chain is queried.
+pub async fn balance(
+    account_id: &AccountId,
+    chain_type: ChainEnum,
+) -> Result<Balance, WalletError> {
+    let now = ic_timestamp();
+
+    let fresh = with_ledger(account_id, |ledger| ledger.fresh_balance(&chain_type, now))?;
+
+    match fresh {
+        Some(balance) => Ok(balance),
+        None => refresh_balance(account_id, chain_type)
+            .await
+            .map(|cached| cached.balance),
+    }
+}
+
+/// Query the chain for its balance and store the result in the cache.
+pub async fn refresh_balance(
+    account_id: &AccountId,
+    chain_type: ChainEnum,
+) -> Result<CachedBalance, WalletError> {
+    let chain = with_chain(account_id, &chain_type, |chain| chain.clone())?;
+
+    let balance = chain.balance().await?;
+
+    let ttl = with_setting(|setting| setting.balance_ttl());
+
+    let cached = CachedBalance::new(balance, ic_timestamp(), ttl);
+
+    with_chain_mut(account_id, chain_type, |chain| {
+        chain.set_cached_balance(cached.clone())
+    })?;
+
+    Ok(cached)
+}
+
+/// Drop the cached balance of the chain, so the next balance is queried again.
+/// Called after sending from the chain.
+pub fn invalidate_balance(
+    account_id: &AccountId,
+    chain_type: ChainEnum,
+) -> Result<(), WalletError> {
+    with_chain_mut(account_id, chain_type, |chain| chain.clear_cached_balance())
+}
+
+/// Drop the cached balances of every account.
+/// Called after an operation executes, as it may have moved the funds of any account.
+pub fn invalidate_balances() {
+    with_wallet_mut(|state| {
+        state
+            .accounts
+            .values_mut()
+            .for_each(|account| account.ledger_mut().clear_cached_balances())
+    });
+}
+
+/// Refresh the balance of every chain of every watched account.
+/// Failing chains keep their previous cached balance.
+pub async fn refresh_watched_balances() -> Vec<(AccountId, ChainEnum, Result<Balance, WalletError>)>
+{
+    let targets: Vec<(AccountId, ChainEnum)> = with_wallet(|state| {
+        state
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.is_watched() && !account.is_archived())
+            .flat_map(|(account_id, account)| {
+                account
+                    .ledger()
+                    .chains
+                    .keys()
+                    .map(move |chain_type| (account_id.clone(), chain_type.clone()))
+            })
+            .collect()
+    });
+
+    let calls = targets
+        .into_iter()
+        .map(|(account_id, chain_type)| async move {
+            let result = refresh_balance(&account_id, chain_type.clone())
+                .await
+                .map(|cached| cached.balance);
+
+            (account_id, chain_type, result)
+        });
+
+    join_all(calls).await
+}
@@ -0,0 +1,310 @@
+use b3_utils::ledger::ICRCAccount;
+
+use crate::{
+    error::WalletError,
+    ledger::{
+        btc::{
+            coin_selection::{CoinSelection, CoinSelectionStrategy},
+            error::BitcoinError,
+            network::BitcoinNetwork,
+            tx::{TxOut, UnsignedTransaction},
+            types::{BtcSentTransaction, BtcTxId, OutPoint, Satoshi},
+            utils::tx_vsize_estimate,
+            utxos::BitcoinUtxos,
+        },
+        ckbtc::minter::Minter,
+        types::{BtcPending, ChainEnum},
+    },
+    store::{with_chain, with_chain_mut},
+    types::AccountId,
+};
+
+#[cfg(test)]
+use b3_utils::mocks::time_mock as ic_timestamp;
+#[cfg(not(test))]
+use ic_cdk::api::time as ic_timestamp;
+
+/// Sends a transaction paying the outputs from the BTC chain of the account.
+/// The inputs are locked in the store as soon as they are picked, so transfers
+/// running at the same time skip them, and unlocked again if signing or sending fails.
+/// The sent transaction is kept as a pending of the chain, so its fee can be bumped.
+/// Returns the txid and the fee, in satoshi.
+pub async fn btc_transfer(
+    account_id: &AccountId,
+    network: BitcoinNetwork,
+    outputs: Vec<(String, Satoshi)>,
+    coin_selection: &impl CoinSelection,
+) -> Result<(BtcTxId, Satoshi), WalletError> {
+    let chain_type = ChainEnum::BTC(network);
+
+    let btc = with_chain(account_id, &chain_type, |chain| chain.btc())??;
+
+    let payments = outputs.clone();
+
+    let outputs = outputs
+        .iter()
+        .map(|(address, value)| btc.tx_out(address, *value))
+        .collect::<Result<Vec<TxOut>, _>>()?;
+
+    let utxos = BitcoinUtxos::try_from(btc.get_utxos(None).await?)?;
+    let available = utxos.utxos();
+
+    let fee_rate = btc.btc_network.fee_rate(49).await?;
+
+    let payment_count = outputs.len();
+
+    // nothing is awaited between picking the inputs and locking them
+    let built = with_chain_mut(account_id, chain_type.clone(), |chain| {
+        chain.btc_mut().map(|btc| {
+            btc.build_locked_transaction(utxos, coin_selection, outputs, fee_rate, ic_timestamp())
+        })
+    })??;
+
+    let (unsigned_transaction, fee) = built?;
+
+    let inputs = previous_outputs(&unsigned_transaction);
+
+    let transaction = BtcSentTransaction {
+        inputs: inputs
+            .iter()
+            .filter_map(|outpoint| {
+                available
+                    .iter()
+                    .find(|utxo| &utxo.outpoint == outpoint)
+                    .cloned()
+            })
+            .collect(),
+        outputs: payments,
+        change: match unsigned_transaction.outputs.get(payment_count) {
+            Some(change) => change.value,
+            None => 0,
+        },
+        fee,
+        vsize: tx_vsize_estimate(
+            unsigned_transaction.inputs.len() as u64,
+            unsigned_transaction.outputs.len() as u64,
+        ),
+    };
+
+    match btc.sign_and_send(unsigned_transaction, fee).await {
+        Ok(txid) => {
+            add_sent_pending(account_id, chain_type, &btc.address, &txid, transaction)?;
+
+            Ok((txid, fee))
+        }
+        Err(err) => {
+            with_chain_mut(account_id, chain_type, |chain| {
+                chain.btc_mut().map(|btc| btc.unlock_outpoints(&inputs))
+            })??;
+
+            Err(err.into())
+        }
+    }
+}
+
+/// Replaces the pending transfer `txid` by a transaction paying the same outputs
+/// at `fee_per_vbyte` millisatoshi per vbyte, or the median fee rate if not set.
+/// The coins added to pay for the higher fee are locked, and unlocked again if
+/// signing or sending fails.
+/// Returns the txid of the replacement and its fee, in satoshi.
+pub async fn btc_bump_fee(
+    account_id: &AccountId,
+    network: BitcoinNetwork,
+    txid: &str,
+    fee_per_vbyte: Option<u64>,
+) -> Result<(BtcTxId, Satoshi), WalletError> {
+    let chain_type = ChainEnum::BTC(network);
+
+    let btc = with_chain(account_id, &chain_type, |chain| chain.btc())??;
+
+    let sent = btc.sent_transaction(txid)?;
+
+    let utxos = BitcoinUtxos::try_from(btc.get_utxos(None).await?)?;
+
+    let fee_rate = match fee_per_vbyte {
+        Some(fee_rate) => fee_rate,
+        None => btc.btc_network.fee_rate(49).await?,
+    };
+
+    // nothing is awaited between picking the inputs and locking them
+    let built = with_chain_mut(account_id, chain_type.clone(), |chain| {
+        chain
+            .btc_mut()
+            .map(|btc| btc.build_replacement(txid, utxos, fee_rate, ic_timestamp()))
+    })??;
+
+    let (unsigned_transaction, replacement) = built?;
+
+    let fee = replacement.fee;
+
+    let added: Vec<OutPoint> = previous_outputs(&unsigned_transaction)
+        .into_iter()
+        .filter(|outpoint| !sent.inputs.iter().any(|input| &input.outpoint == outpoint))
+        .collect();
+
+    match btc.sign_and_send(unsigned_transaction, fee).await {
+        Ok(new_txid) => {
+            with_chain_mut(account_id, chain_type, |chain| {
+                chain
+                    .btc_mut()
+                    .map(|btc| btc.replace_pending(txid, new_txid.clone(), replacement))
+            })??;
+
+            Ok((new_txid, fee))
+        }
+        Err(err) => {
+            with_chain_mut(account_id, chain_type, |chain| {
+                chain.btc_mut().map(|btc| btc.unlock_outpoints(&added))
+            })??;
+
+            Err(err.into())
+        }
+    }
+}
+
+/// Speeds up the transaction `txid` stuck in the mempool by spending its output `vout`,
+/// which pays to the address of the account, in a child paying for both (CPFP).
+/// The parent is given as (vsize, fee in satoshi), or looked up in the pending transfers.
+/// The value of the output is needed for an incoming parent, as the Bitcoin canister
+/// does not return the outputs of transactions in the mempool.
+/// The package of both pays `fee_per_vbyte` millisatoshi per vbyte, or the median fee rate if not set.
+/// Returns the txid of the child and its fee, in satoshi.
+pub async fn btc_child_pays_for_parent(
+    account_id: &AccountId,
+    network: BitcoinNetwork,
+    txid: &str,
+    vout: u32,
+    parent: Option<(u64, Satoshi)>,
+    parent_value: Option<Satoshi>,
+    fee_per_vbyte: Option<u64>,
+) -> Result<(BtcTxId, Satoshi), WalletError> {
+    let chain_type = ChainEnum::BTC(network);
+
+    let btc = with_chain(account_id, &chain_type, |chain| chain.btc())??;
+
+    let (parent_vsize, parent_fee) = match parent {
+        Some(parent) => parent,
+        None => {
+            let sent = btc.sent_transaction(txid)?;
+
+            (sent.vsize, sent.fee)
+        }
+    };
+
+    let utxos = btc.get_unconfirmed_utxos().await?;
+
+    let fee_rate = match fee_per_vbyte {
+        Some(fee_rate) => fee_rate,
+        None => btc.btc_network.fee_rate(49).await?,
+    };
+
+    // nothing is awaited between picking the output and locking it
+    let built = with_chain_mut(account_id, chain_type.clone(), |chain| {
+        chain.btc_mut().map(|btc| {
+            btc.build_child(
+                txid,
+                vout,
+                &utxos,
+                parent_value,
+                parent_vsize,
+                parent_fee,
+                fee_rate,
+                ic_timestamp(),
+            )
+        })
+    })??;
+
+    let (unsigned_transaction, child) = built?;
+
+    let fee = child.fee;
+
+    let inputs = previous_outputs(&unsigned_transaction);
+
+    match btc.sign_and_send(unsigned_transaction, fee).await {
+        Ok(child_txid) => {
+            add_sent_pending(account_id, chain_type, &btc.address, &child_txid, child)?;
+
+            Ok((child_txid, fee))
+        }
+        Err(err) => {
+            with_chain_mut(account_id, chain_type, |chain| {
+                chain.btc_mut().map(|btc| btc.unlock_outpoints(&inputs))
+            })??;
+
+            Err(err.into())
+        }
+    }
+}
+
+/// Sends `amount` to the ckBTC minter address of the account, through [btc_transfer]
+/// so the inputs are locked while the transaction is in flight.
+/// The pending kept by the transfer is credited to the ckBTC account of the chain, so
+/// checking it asks the minter for the ckBTC once the transaction has confirmed.
+pub async fn btc_swap_to_ckbtc(
+    account_id: &AccountId,
+    network: BitcoinNetwork,
+    amount: Satoshi,
+) -> Result<BtcPending, WalletError> {
+    let btc = with_chain(account_id, &ChainEnum::BTC(network), |chain| chain.btc())??;
+
+    let account = ICRCAccount::from(btc.subaccount.clone());
+
+    let dst_address = Minter::new(network)
+        .get_btc_address(account.clone())
+        .await
+        .map_err(|err| BitcoinError::SwapToCkbtc(err.to_string()))?;
+
+    let (txid, _) = btc_transfer(
+        account_id,
+        network,
+        vec![(dst_address, amount)],
+        &CoinSelectionStrategy::default(),
+    )
+    .await?;
+
+    let pending = with_chain_mut(account_id, ChainEnum::BTC(network), |chain| {
+        chain.btc_mut().map(|btc| {
+            btc.pendings
+                .iter_mut()
+                .find(|pending| pending.txid == txid)
+                .map(|pending| {
+                    pending.account = account.to_string();
+
+                    pending.clone()
+                })
+        })
+    })??;
+
+    pending.ok_or(WalletError::BitcoinError(
+        BitcoinError::TransactionNotFound(txid),
+    ))
+}
+
+/// Keeps the sent transaction as a pending of the chain until it confirms.
+fn add_sent_pending(
+    account_id: &AccountId,
+    chain_type: ChainEnum,
+    address: &str,
+    txid: &str,
+    transaction: BtcSentTransaction,
+) -> Result<(), WalletError> {
+    with_chain_mut(account_id, chain_type, |chain| {
+        chain.btc_mut().map(|btc| {
+            btc.pendings.push(BtcPending {
+                txid: txid.to_string(),
+                account: address.to_string(),
+                transaction: Some(transaction),
+            })
+        })
+    })??;
+
+    Ok(())
+}
+
+fn previous_outputs(unsigned_transaction: &UnsignedTransaction) -> Vec<OutPoint> {
+    unsigned_transaction
+        .inputs
+        .iter()
+        .map(|input| input.previous_output.clone())
+        .collect()
+}
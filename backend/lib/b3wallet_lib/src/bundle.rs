@@ -0,0 +1,365 @@
+use crate::{
+    account::WalletAccount,
+    error::WalletError,
+    ledger::{
+        chain::Chain,
+        subaccount::SubaccountEcdsaTrait,
+        types::{AddressMap, ChainEnum},
+    },
+    types::{AccountId, WalletAccountMap},
+};
+use b3_utils::{
+    api::AppAccountsNonce, ledger::ICRCAccount, sha2::Sha256, types::CanisterId, Environment,
+    Subaccount,
+};
+use candid::CandidType;
+use ciborium::de::from_reader;
+use ciborium::ser::into_writer;
+use libsecp256k1::{verify, Message, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+#[cfg(test)]
+use b3_utils::mocks::id_mock as ic_cdk_id;
+#[cfg(not(test))]
+use ic_cdk::api::id as ic_cdk_id;
+
+/// The current version of the account bundle format.
+/// Bundles with a different version are rejected on import.
+pub const ACCOUNT_BUNDLE_VERSION: u16 = 1;
+
+/// The wallet accounts as they are written into an account bundle.
+/// Settings and controllers are left out, as they belong to the exporting canister.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountBundlePayload {
+    pub version: u16,
+    pub canister_id: CanisterId,
+    pub exported_at: u64,
+    pub nonces: AppAccountsNonce,
+    pub accounts: WalletAccountMap,
+    pub addresses: BTreeMap<AccountId, AddressMap>,
+}
+
+/// A CBOR encoded `AccountBundlePayload` signed by the exporting canister.
+/// The signature is made over the sha256 hash of the payload with the key of the default account.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AccountBundle {
+    pub version: u16,
+    pub payload: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// An address that is different, or not yet available, after the import.
+/// `new_address` is `None` when the chain has to be created again with `account_create_address`.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AddressChange {
+    pub account_id: AccountId,
+    pub chain: ChainEnum,
+    pub old_address: String,
+    pub new_address: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ImportReport {
+    pub source_canister_id: CanisterId,
+    pub exported_at: u64,
+    pub imported: Vec<AccountId>,
+    pub skipped: Vec<AccountId>,
+    pub address_changes: Vec<AddressChange>,
+}
+
+impl AccountBundlePayload {
+    pub fn new(
+        canister_id: CanisterId,
+        exported_at: u64,
+        nonces: AppAccountsNonce,
+        accounts: WalletAccountMap,
+    ) -> Self {
+        let addresses = accounts
+            .iter()
+            .map(|(id, account)| (id.clone(), account.ledger().address_map()))
+            .collect();
+
+        AccountBundlePayload {
+            version: ACCOUNT_BUNDLE_VERSION,
+            canister_id,
+            exported_at,
+            nonces,
+            accounts,
+            addresses,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        into_writer(&self, &mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WalletError> {
+        from_reader(&mut Cursor::new(bytes))
+            .map_err(|err| WalletError::InvalidAccountBundle(err.to_string()))
+    }
+
+    /// Rebuild the account for the current canister.
+    /// Chains that derive their address from the canister id are kept and pointed to this canister,
+    /// while ECDSA based chains are dropped, as their key depends on the canister id too.
+    pub fn rebuild_account(&self, account: &WalletAccount) -> WalletAccount {
+        let mut account = account.clone();
+
+        let canister_id = ic_cdk_id();
+
+        if canister_id == self.canister_id {
+            return account;
+        }
+
+        let subaccount = account.subaccount();
+
+        let ledger = account.ledger_mut();
+
+        ledger.public_key = None;
+
+        let chains = std::mem::take(&mut ledger.chains);
+
+        for (chain_type, chain) in chains {
+            if let Some(chain) = rebuild_chain(chain, &subaccount, canister_id) {
+                ledger.insert_chain(chain_type, chain);
+            }
+        }
+
+        account
+    }
+
+    /// Compare the exported addresses of the account with the addresses it has after the rebuild.
+    pub fn address_changes(&self, account: &WalletAccount) -> Vec<AddressChange> {
+        let old_addresses = match self.addresses.get(account.id()) {
+            Some(addresses) => addresses,
+            None => return vec![],
+        };
+
+        let new_addresses = account.ledger().address_map();
+
+        old_addresses
+            .iter()
+            .filter_map(|(chain, old_address)| {
+                let new_address = new_addresses.get(chain).cloned();
+
+                if new_address.as_ref() == Some(old_address) {
+                    return None;
+                }
+
+                Some(AddressChange {
+                    account_id: account.id().clone(),
+                    chain: chain.clone(),
+                    old_address: old_address.clone(),
+                    new_address,
+                })
+            })
+            .collect()
+    }
+}
+
+fn rebuild_chain(chain: Chain, subaccount: &Subaccount, canister_id: CanisterId) -> Option<Chain> {
+    match chain {
+        Chain::IcpChain(_) => Some(Chain::new_icp_chain(subaccount.clone())),
+        Chain::IcrcChain(mut icrc) => {
+            icrc.pendings.clear();
+            icrc.cached_balance = None;
+
+            Some(Chain::IcrcChain(icrc))
+        }
+        Chain::CkbtcChain(mut ckbtc) => {
+            ckbtc.account = ICRCAccount::new(canister_id, Some(subaccount.clone()));
+            ckbtc.pendings.clear();
+            ckbtc.cached_balance = None;
+
+            Some(Chain::CkbtcChain(ckbtc))
+        }
+        Chain::WatchOnlyChain(watch) => Some(Chain::WatchOnlyChain(watch)),
+        Chain::BtcChain(_) | Chain::EvmChain(_) => None,
+    }
+}
+
+impl AccountBundle {
+    /// Encode and sign the payload with the threshold ECDSA key of the default account.
+    pub async fn sign(payload: &AccountBundlePayload) -> Result<Self, WalletError> {
+        let subaccount = Subaccount::new(Environment::Production, 0);
+
+        let payload = payload.to_bytes();
+
+        let hash = Sha256::hash(&payload);
+
+        let public_key = subaccount.ecdsa_public_key().await?;
+
+        let signature = subaccount.sign_with_ecdsa(hash.to_vec()).await?;
+
+        Ok(AccountBundle {
+            version: ACCOUNT_BUNDLE_VERSION,
+            payload,
+            public_key,
+            signature,
+        })
+    }
+
+    /// The public key the canister the bundle claims to come from signs bundles with.
+    /// Only the canister id is read from the payload, before it is verified.
+    pub async fn exporter_public_key(&self) -> Result<Vec<u8>, WalletError> {
+        let payload = AccountBundlePayload::from_bytes(&self.payload)?;
+
+        let public_key = Subaccount::new(Environment::Production, 0)
+            .ecdsa_public_key_of(payload.canister_id)
+            .await?;
+
+        Ok(public_key)
+    }
+
+    /// Check the version of the bundle, that it was signed with `exporter_public_key`,
+    /// and decode its payload.
+    pub fn verify(&self, exporter_public_key: &[u8]) -> Result<AccountBundlePayload, WalletError> {
+        if self.version != ACCOUNT_BUNDLE_VERSION {
+            return Err(WalletError::AccountBundleVersionMismatch(self.version));
+        }
+
+        let public_key = PublicKey::parse_slice(&self.public_key, None)
+            .map_err(|err| WalletError::InvalidAccountBundle(err.to_string()))?;
+
+        let exporter_public_key = PublicKey::parse_slice(exporter_public_key, None)
+            .map_err(|err| WalletError::InvalidAccountBundle(err.to_string()))?;
+
+        if public_key != exporter_public_key {
+            return Err(WalletError::AccountBundlePublicKeyMismatch);
+        }
+
+        let signature = Signature::parse_standard_slice(&self.signature)
+            .map_err(|err| WalletError::InvalidAccountBundle(err.to_string()))?;
+
+        let message = Message::parse(&Sha256::hash(&self.payload));
+
+        if !verify(&message, &signature, &public_key) {
+            return Err(WalletError::InvalidAccountBundleSignature);
+        }
+
+        let payload = AccountBundlePayload::from_bytes(&self.payload)?;
+
+        if payload.version != self.version {
+            return Err(WalletError::AccountBundleVersionMismatch(payload.version));
+        }
+
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ledger::btc::network::BitcoinNetwork, state::WalletState};
+    use candid::Principal;
+    use libsecp256k1::{sign, SecretKey};
+
+    fn signed_bundle(payload: &AccountBundlePayload) -> AccountBundle {
+        let secret_key = SecretKey::parse(&[7; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secret_key);
+
+        let payload = payload.to_bytes();
+        let message = Message::parse(&Sha256::hash(&payload));
+
+        let (signature, _) = sign(&message, &secret_key);
+
+        AccountBundle {
+            version: ACCOUNT_BUNDLE_VERSION,
+            payload,
+            public_key: public_key.serialize_compressed().to_vec(),
+            signature: signature.serialize().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_verify() {
+        let state = WalletState::new();
+
+        let payload = state.export_accounts(Principal::from_slice(&[9; 10]), 1);
+
+        let bundle = signed_bundle(&payload);
+
+        let verified = bundle.verify(&bundle.public_key).unwrap();
+
+        assert_eq!(verified.canister_id, payload.canister_id);
+        assert_eq!(verified.accounts.len(), 1);
+
+        let mut tampered = bundle.clone();
+        tampered.payload.push(0);
+
+        assert_eq!(
+            tampered.verify(&bundle.public_key).err(),
+            Some(WalletError::InvalidAccountBundleSignature)
+        );
+
+        // signed by anyone else than the exporting canister
+        let other_key = PublicKey::from_secret_key(&SecretKey::parse(&[8; 32]).unwrap());
+
+        assert_eq!(
+            bundle.verify(&other_key.serialize_compressed()).err(),
+            Some(WalletError::AccountBundlePublicKeyMismatch)
+        );
+
+        // the key is compared whatever its encoding
+        let exporter_key = PublicKey::parse_slice(&bundle.public_key, None).unwrap();
+
+        assert!(bundle.verify(&exporter_key.serialize()).is_ok());
+
+        let mut outdated = bundle.clone();
+        outdated.version = 0;
+
+        assert_eq!(
+            outdated.verify(&bundle.public_key).err(),
+            Some(WalletError::AccountBundleVersionMismatch(0))
+        );
+    }
+
+    #[test]
+    fn test_import_accounts() {
+        let mut source = WalletState::new();
+
+        let subaccount = source.new_subaccount(None);
+        source.insert_account(WalletAccount::from(subaccount), None);
+
+        let mut payload = source.export_accounts(Principal::from_slice(&[9; 10]), 1);
+
+        let btc = ChainEnum::BTC(BitcoinNetwork::Mainnet);
+
+        // the exported addresses belong to the source canister
+        let addresses = payload.addresses.get_mut("account_1").unwrap();
+        addresses.insert(ChainEnum::ICP, "old-icp-address".to_owned());
+        addresses.insert(btc.clone(), "old-btc-address".to_owned());
+
+        let mut target = WalletState::new();
+
+        let report = target.import_accounts(payload);
+
+        assert_eq!(report.imported, vec!["account_1".to_owned()]);
+        assert_eq!(report.skipped, vec!["-default".to_owned()]);
+        assert_eq!(target.accounts_len(), 2);
+        assert_eq!(target.account_nonce(&Environment::Production).get(), 1);
+
+        assert_eq!(report.address_changes.len(), 2);
+
+        let icp_change = report
+            .address_changes
+            .iter()
+            .find(|change| change.chain == ChainEnum::ICP)
+            .unwrap();
+
+        assert_eq!(icp_change.old_address, "old-icp-address");
+        assert!(icp_change.new_address.is_some());
+
+        let btc_change = report
+            .address_changes
+            .iter()
+            .find(|change| change.chain == btc)
+            .unwrap();
+
+        assert_eq!(btc_change.new_address, None);
+    }
+}
@@ -4,7 +4,7 @@ use std::fmt;
 
 use crate::ledger::{
     btc::error::BitcoinError, ckbtc::error::CkbtcError, error::LedgerError, evm::error::EvmError,
-    icp::error::IcpError, icrc::error::IcrcError,
+    icp::error::IcpError, icrc::error::IcrcError, types::ChainEnum,
 };
 
 #[rustfmt::skip]
@@ -34,6 +34,12 @@ pub enum WalletError {
     ControllerAlreadyExists,
     CannotRemoveSelf,
     UpdateCanisterControllersError(String),
+    InvalidAccountBundle(String),
+    InvalidAccountBundleSignature,
+    AccountBundlePublicKeyMismatch,
+    AccountBundleVersionMismatch(u16),
+    AccountHasBalance(ChainEnum),
+    AccountHasPendings(ChainEnum),
 }
 
 #[rustfmt::skip]
@@ -64,6 +70,12 @@ impl fmt::Display for WalletError {
             WalletError::TooManyControllers => write!(f, "Too many controllers!"),
             WalletError::CannotRemoveSelf => write!(f, "Cannot remove self!"),
             WalletError::UpdateCanisterControllersError(ref msg) => write!(f, "Update canister controllers Error::{}", msg),
+            WalletError::InvalidAccountBundle(ref msg) => write!(f, "Invalid account bundle::{}", msg),
+            WalletError::InvalidAccountBundleSignature => write!(f, "Invalid account bundle signature!"),
+            WalletError::AccountBundlePublicKeyMismatch => write!(f, "Account bundle is not signed by the exporting canister!"),
+            WalletError::AccountBundleVersionMismatch(ref version) => write!(f, "Account bundle version {} is not supported!", version),
+            WalletError::AccountHasBalance(ref chain) => write!(f, "Account still has a balance on {}!", chain),
+            WalletError::AccountHasPendings(ref chain) => write!(f, "Account still has pendings on {}!", chain),
         }
     }
 }
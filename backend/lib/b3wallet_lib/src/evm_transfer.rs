@@ -0,0 +1,126 @@
+use crate::{
+    error::WalletError,
+    ledger::{
+        chain::ChainTrait,
+        evm::{address::EvmAddress, api::EVM_TRANSFER_GAS_LIMIT, gas::EvmGasConfig, u256::U256},
+        types::{ChainEnum, PendingEnum},
+    },
+    store::{with_chain, with_chain_mut, with_setting},
+    types::AccountId,
+};
+
+/// Sends `value` wei to `to` from the EVM chain of the account.
+/// The nonce is reserved in the store, so sends running at the same time do not take
+/// the same one, and given back if signing or broadcasting fails.
+/// The fees are checked against `gas_config`, the one of the chain when none is given.
+/// The sent transaction is kept as a pending of the chain until its receipt is seen.
+/// Returns the transaction hash.
+pub async fn evm_transfer(
+    account_id: &AccountId,
+    chain_id: u64,
+    to: String,
+    value: U256,
+    gas_config: Option<&EvmGasConfig>,
+) -> Result<String, WalletError> {
+    let evm = with_chain(account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())??;
+
+    let to = EvmAddress::parse(&to)?.to_string();
+
+    evm.check_eip1559()?;
+
+    let suggestion = evm
+        .suggest_fees(&to, value, "0x", Some(EVM_TRANSFER_GAS_LIMIT))
+        .await;
+
+    let fees = match gas_config {
+        Some(config) => config.clone(),
+        None => with_setting(|setting| setting.evm_gas_config(chain_id)),
+    }
+    .resolve(None, None, None, &suggestion)?;
+
+    let nonce = reserve_nonce(account_id, chain_id, None).await?;
+
+    let mut transaction = evm.transfer_transaction(to, value, nonce, fees);
+
+    let result = match evm.sign_transaction(&mut transaction).await {
+        Ok(signed) => broadcast_transaction(account_id, chain_id, &signed).await,
+        Err(err) => Err(err.into()),
+    };
+
+    if result.is_err() {
+        release_nonce(account_id, chain_id, nonce)?;
+    }
+
+    result
+}
+
+/// Reserve the nonce of a transaction executed now.
+/// Unless the request fixed it, the nonce is reconciled with the node at execution time,
+/// so requests waiting for approval do not hold on to a stale nonce.
+pub async fn reserve_nonce(
+    account_id: &AccountId,
+    chain_id: u64,
+    nonce: Option<u64>,
+) -> Result<u64, WalletError> {
+    let chain_type = ChainEnum::EVM(chain_id);
+
+    let chain = with_chain(account_id, &chain_type, |chain| chain.evm())??;
+
+    let pending = match nonce {
+        Some(nonce) => nonce,
+        None => {
+            chain
+                .rpc()
+                .get_transaction_count(&chain.address.to_string(), "pending")
+                .await?
+        }
+    };
+
+    let nonce = with_chain_mut(account_id, chain_type, |chain| {
+        chain.evm_mut().map(|evm| match nonce {
+            Some(nonce) => {
+                evm.use_nonce(nonce);
+                nonce
+            }
+            None => evm.reserve_nonce(pending),
+        })
+    })??;
+
+    Ok(nonce)
+}
+
+pub fn release_nonce(account_id: &AccountId, chain_id: u64, nonce: u64) -> Result<(), WalletError> {
+    with_chain_mut(account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain.evm_mut().map(|evm| evm.release_nonce(nonce))
+    })??;
+
+    Ok(())
+}
+
+pub fn use_nonce(account_id: &AccountId, chain_id: u64, nonce: u64) -> Result<(), WalletError> {
+    with_chain_mut(account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain.evm_mut().map(|evm| evm.use_nonce(nonce))
+    })??;
+
+    Ok(())
+}
+
+/// Broadcast a signed transaction and keep its hash as a pending of the chain,
+/// so the receipt can be checked later with `account_check_pending`.
+pub async fn broadcast_transaction(
+    account_id: &AccountId,
+    chain_id: u64,
+    signed_tx: &Vec<u8>,
+) -> Result<String, WalletError> {
+    let chain_type = ChainEnum::EVM(chain_id);
+
+    let chain = with_chain(account_id, &chain_type, |chain| chain.evm())??;
+
+    let tx_hash = chain.broadcast(signed_tx).await?;
+
+    with_chain_mut(account_id, chain_type, |chain| {
+        chain.add_pending(PendingEnum::new_evm(tx_hash.clone()))
+    })?;
+
+    Ok(tx_hash)
+}
@@ -2,15 +2,12 @@ use crate::ledger::{
     chain::ChainTrait,
     ckbtc::minter::Minter,
     error::LedgerError,
-    types::{Balance, BtcPending, PendingEnum, SendResult},
+    types::{Balance, BtcPending, CachedBalance, PendingEnum, SendResult},
 };
 use async_trait::async_trait;
-use b3_utils::{
-    ledger::{currency::TokenAmount, ICRCAccount},
-    vec_to_hex_string,
-};
+use b3_utils::ledger::{currency::TokenAmount, ICRCAccount};
 
-use super::btc::BtcChain;
+use super::{btc::BtcChain, error::BitcoinError, types::OutPoint};
 
 #[async_trait]
 impl ChainTrait for BtcChain {
@@ -27,25 +24,32 @@ impl ChainTrait for BtcChain {
             .map_err(LedgerError::BitcoinError)
     }
 
-    async fn send(&self, to: String, amount: TokenAmount) -> Result<SendResult, LedgerError> {
-        let amount = amount
-            .to_satoshi()
-            .map_err(|e| LedgerError::InvalidAmountError(e.to_string()))?;
-
-        let result = self.transfer(to, amount).await;
-
-        match result {
-            Ok((txid, _)) => Ok(SendResult::BTC(vec_to_hex_string(txid))),
-            Err(err) => Err(LedgerError::BitcoinError(err)),
-        }
+    /// A copy of the chain can not lock the inputs it spends,
+    /// Bitcoin is sent with [crate::btc_transfer::btc_transfer] instead.
+    async fn send(&self, _to: String, _amount: TokenAmount) -> Result<SendResult, LedgerError> {
+        Err(LedgerError::BitcoinError(BitcoinError::UnlockedTransfer))
     }
 
     async fn check_pending(&self, pending_index: usize) -> Result<(), LedgerError> {
-        let BtcPending { account, txid: _ } = self
+        let BtcPending {
+            account,
+            txid,
+            transaction,
+        } = self
             .pendings
             .get(pending_index)
             .ok_or(LedgerError::PendingIndexError(pending_index))?;
 
+        if let Some(transaction) = transaction {
+            self.check_confirmed(txid, transaction)
+                .await
+                .map_err(LedgerError::BitcoinError)?;
+
+            if account == &self.address {
+                return Ok(());
+            }
+        }
+
         let account =
             ICRCAccount::from_text(account).map_err(|e| LedgerError::ICRCAccountError(e))?;
 
@@ -74,7 +78,38 @@ impl ChainTrait for BtcChain {
         self.pendings.remove(pending_index);
     }
 
+    /// Also unlocks the inputs of the confirmed transaction.
+    fn confirm_pending(&mut self, pending_index: usize) {
+        if let Some(BtcPending {
+            transaction: Some(transaction),
+            ..
+        }) = self.pendings.get(pending_index)
+        {
+            let outpoints: Vec<OutPoint> = transaction
+                .inputs
+                .iter()
+                .map(|input| input.outpoint.clone())
+                .collect();
+
+            self.unlock_outpoints(&outpoints);
+        }
+
+        self.remove_pending(pending_index);
+    }
+
     fn clear_pending(&mut self) {
         self.pendings.clear();
     }
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
 }
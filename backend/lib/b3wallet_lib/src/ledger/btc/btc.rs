@@ -1,20 +1,26 @@
 use crate::ledger::btc::address::BitcoinAddress;
+use crate::ledger::btc::coin_selection::CoinSelection;
 use crate::ledger::btc::signature;
-use crate::ledger::btc::tx::{hash160, SignedInput, SignedTransaction, TxSigHasher};
-use crate::ledger::ckbtc::minter::Minter;
+use crate::ledger::btc::tx::{hash160, SignedInput, SignedTransaction, TxOut, TxSigHasher};
 use crate::ledger::subaccount::SubaccountEcdsaTrait;
-use crate::ledger::types::BtcPending;
+use crate::ledger::types::{BtcPending, CachedBalance};
 use b3_utils::vec_to_hex_string;
-use b3_utils::{ledger::ICRCAccount, Subaccount};
+use b3_utils::Subaccount;
 use ic_cdk::api::management_canister::bitcoin::Satoshi;
 use ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, UtxoFilter};
 use ic_cdk::println;
 use libsecp256k1::PublicKey;
 use serde_bytes::ByteBuf;
 
+use super::cpfp::child_pays_for_parent;
 use super::error::BitcoinError;
 use super::network::BitcoinNetwork;
-use super::tx::UnsignedTransaction;
+use super::rbf::replace_by_fee;
+use super::tx::{parse_txid, UnsignedTransaction};
+use super::types::{
+    BtcSentTransaction, BtcTxId, LockedOutpoint, OutPoint, Utxo, OUTPOINT_LOCK_TTL,
+};
+use super::utils::has_output;
 use super::utxos::BitcoinUtxos;
 
 use serde::{Deserialize, Serialize};
@@ -27,6 +33,11 @@ pub struct BtcChain {
     pub pendings: Vec<BtcPending>,
     pub ecdsa_public_key: PublicKey,
     pub min_confirmations: Option<u32>,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
+    /// The outpoints spent by transactions in flight, skipped by coin selection.
+    #[serde(default)]
+    pub locked_outpoints: Vec<LockedOutpoint>,
 }
 
 impl BtcChain {
@@ -56,28 +67,27 @@ impl BtcChain {
             .await
     }
 
-    /// Sends a transaction to the btc_network that transfers the given amount to the
-    /// given destination, where the source of the funds is the canister itself
-    /// at the given derivation path.
-    pub async fn transfer(
-        &self,
-        dst_address: String,
-        amount: Satoshi,
-    ) -> Result<([u8; 32], u64), BitcoinError> {
-        let dst_address = BitcoinAddress::parse(&dst_address, self.btc_network)
-            .map_err(|err| BitcoinError::InvalidAddress(err.to_string()))?;
-
-        let utxo_res = self.get_utxos(None).await?;
-
-        let utxo = BitcoinUtxos::try_from(utxo_res)?;
-
-        let fee_rate = self.btc_network.fee_rate(49).await?;
+    /// Get the UTXOs of the canister's bitcoin wallet, including those of
+    /// transactions that are not confirmed yet.
+    pub async fn get_unconfirmed_utxos(&self) -> Result<GetUtxosResponse, BitcoinError> {
+        self.get_utxos(Some(UtxoFilter::MinConfirmations(0))).await
+    }
 
-        let address = self.btc_address()?;
+    /// Parse the destination address on the network of the chain.
+    pub fn tx_out(&self, address: &str, value: Satoshi) -> Result<TxOut, BitcoinError> {
+        let address = BitcoinAddress::parse(address, self.btc_network)
+            .map_err(|err| BitcoinError::InvalidAddress(err.to_string()))?;
 
-        let (unsigned_transaction, fee) =
-            utxo.build_unsigned_transaction(&address, &dst_address, amount, fee_rate)?;
+        Ok(TxOut { address, value })
+    }
 
+    /// Signs the transaction and sends it to the btc_network.
+    /// Returns the txid as shown by block explorers.
+    pub async fn sign_and_send(
+        &self,
+        unsigned_transaction: UnsignedTransaction,
+        fee: u64,
+    ) -> Result<BtcTxId, BitcoinError> {
         let signed_transaction = self.sign_transaction(unsigned_transaction).await?;
 
         println!(
@@ -90,9 +100,258 @@ impl BtcChain {
             .send_transaction(&signed_transaction)
             .await?;
 
-        let txid = signed_transaction.wtxid();
+        Ok(signed_transaction.display_txid())
+    }
+
+    /// Builds a transaction from the UTXOs that are not locked and locks its inputs,
+    /// so a transfer running at the same time can not pick them.
+    /// The expired locks are released first.
+    pub fn build_locked_transaction(
+        &mut self,
+        utxos: BitcoinUtxos,
+        coin_selection: &impl CoinSelection,
+        outputs: Vec<TxOut>,
+        fee_per_vbyte: u64,
+        now: u64,
+    ) -> Result<(UnsignedTransaction, u64), BitcoinError> {
+        self.release_locks(now);
+
+        let address = self.btc_address()?;
+
+        let (unsigned_transaction, fee) = self.spendable_utxos(utxos, now).build_transaction(
+            coin_selection,
+            &address,
+            outputs,
+            fee_per_vbyte,
+        )?;
+
+        let outpoints = unsigned_transaction
+            .inputs
+            .iter()
+            .map(|input| input.previous_output.clone())
+            .collect();
+
+        self.lock_outpoints(outpoints, now);
+
+        Ok((unsigned_transaction, fee))
+    }
+
+    /// The transaction of the pending transfer with the given txid.
+    pub fn sent_transaction(&self, txid: &str) -> Result<BtcSentTransaction, BitcoinError> {
+        self.pendings
+            .iter()
+            .find(|pending| pending.txid == txid)
+            .and_then(|pending| pending.transaction.clone())
+            .ok_or(BitcoinError::TransactionNotFound(txid.to_string()))
+    }
+
+    /// Rebuilds the pending transfer with the given txid at a higher fee rate (BIP-125),
+    /// adding unlocked coins when its change can not pay for it, and locks the inputs.
+    pub fn build_replacement(
+        &mut self,
+        txid: &str,
+        utxos: BitcoinUtxos,
+        fee_per_vbyte: u64,
+        now: u64,
+    ) -> Result<(UnsignedTransaction, BtcSentTransaction), BitcoinError> {
+        let sent = self.sent_transaction(txid)?;
+
+        // the inputs of a confirmed transaction are not unspent anymore
+        let unspent = |input: &Utxo| utxos.iter().any(|utxo| utxo.outpoint == input.outpoint);
+
+        if !sent.inputs.iter().all(unspent) {
+            return Err(BitcoinError::TransactionAlreadyConfirmed(txid.to_string()));
+        }
+
+        self.release_locks(now);
+
+        let payments = sent
+            .outputs
+            .iter()
+            .map(|(address, value)| self.tx_out(address, *value))
+            .collect::<Result<Vec<TxOut>, _>>()?;
+
+        let address = self.btc_address()?;
+
+        let (unsigned_transaction, replacement) = replace_by_fee(
+            &sent,
+            payments,
+            &address,
+            &self.spendable_utxos(utxos, now),
+            fee_per_vbyte,
+        )?;
+
+        let outpoints = replacement
+            .inputs
+            .iter()
+            .map(|input| input.outpoint.clone())
+            .collect();
+
+        self.lock_outpoints(outpoints, now);
+
+        Ok((unsigned_transaction, replacement))
+    }
+
+    /// Points the pending transfer to the transaction that replaced it.
+    pub fn replace_pending(
+        &mut self,
+        txid: &str,
+        new_txid: BtcTxId,
+        transaction: BtcSentTransaction,
+    ) {
+        if let Some(pending) = self
+            .pendings
+            .iter_mut()
+            .find(|pending| pending.txid == txid)
+        {
+            pending.txid = new_txid;
+            pending.transaction = Some(transaction);
+        }
+    }
+
+    /// A sent transaction has confirmed once one of its outputs is listed in the UTXOs of
+    /// its address, as the Bitcoin canister only returns the outputs of transactions in a block.
+    /// Inputs missing from the UTXOs prove nothing, a transaction in the mempool may spend them.
+    /// A transaction whose outputs have all been spent again is not seen as confirmed.
+    pub async fn check_confirmed(
+        &self,
+        txid: &str,
+        transaction: &BtcSentTransaction,
+    ) -> Result<(), BitcoinError> {
+        let txid_bytes = parse_txid(txid)?;
+
+        for (address, vout) in transaction.outputs_to(&self.address) {
+            let utxos = self.btc_network.get_utxos(address, None).await?;
+
+            if has_output(&utxos, &txid_bytes, vout) {
+                return Ok(());
+            }
+        }
+
+        Err(BitcoinError::TransactionNotConfirmed(txid.to_string()))
+    }
+
+    /// The output `vout` of the transaction `txid` paying to the address of the chain.
+    /// The change of a pending transfer is known even while `utxos` do not show it yet.
+    /// The Bitcoin canister does not return the outputs of transactions in the mempool,
+    /// so the output of an incoming transaction is taken from `value` as given by the caller.
+    /// A wrong value only makes the child invalid, as its signature commits to the amount spent.
+    pub fn parent_output(
+        &self,
+        txid: &str,
+        vout: u32,
+        utxos: &GetUtxosResponse,
+        value: Option<Satoshi>,
+    ) -> Result<Utxo, BitcoinError> {
+        let outpoint = OutPoint::new(parse_txid(txid)?.to_vec(), vout);
+
+        let unspent = utxos
+            .utxos
+            .iter()
+            .find(|utxo| utxo.outpoint.txid == outpoint.txid && utxo.outpoint.vout == vout);
+
+        if let Some(utxo) = unspent {
+            return Ok(Utxo {
+                outpoint,
+                value: utxo.value,
+                height: utxo.height,
+            });
+        }
+
+        match (self.sent_transaction(txid), value) {
+            (Ok(sent), _) if sent.change > 0 && vout as usize == sent.outputs.len() => Ok(Utxo {
+                outpoint,
+                value: sent.change,
+                height: 0,
+            }),
+            (Err(_), Some(value)) => Ok(Utxo {
+                outpoint,
+                value,
+                height: 0,
+            }),
+            _ => Err(BitcoinError::OutputNotFound(txid.to_string(), vout)),
+        }
+    }
 
-        Ok((txid, fee))
+    /// Builds a child spending the output `vout` of the stuck transaction `txid` (CPFP)
+    /// and locks it, see [child_pays_for_parent] and [BtcChain::parent_output].
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_child(
+        &mut self,
+        txid: &str,
+        vout: u32,
+        utxos: &GetUtxosResponse,
+        parent_value: Option<Satoshi>,
+        parent_vsize: u64,
+        parent_fee: Satoshi,
+        fee_per_vbyte: u64,
+        now: u64,
+    ) -> Result<(UnsignedTransaction, BtcSentTransaction), BitcoinError> {
+        let parent_output = self.parent_output(txid, vout, utxos, parent_value)?;
+
+        if self.is_locked(&parent_output.outpoint, now) {
+            return Err(BitcoinError::OutputLocked(txid.to_string(), vout));
+        }
+
+        let address = self.btc_address()?;
+
+        let outpoint = parent_output.outpoint.clone();
+
+        let built = child_pays_for_parent(
+            parent_output,
+            parent_vsize,
+            parent_fee,
+            &address,
+            fee_per_vbyte,
+        )?;
+
+        self.lock_outpoints(vec![outpoint], now);
+
+        Ok(built)
+    }
+
+    /// Whether a transaction in flight spends the outpoint.
+    pub fn is_locked(&self, outpoint: &OutPoint, now: u64) -> bool {
+        self.locked_outpoints
+            .iter()
+            .any(|locked| &locked.outpoint == outpoint && locked.expires_at > now)
+    }
+
+    /// The UTXOs that no transaction in flight spends.
+    pub fn spendable_utxos(&self, utxos: BitcoinUtxos, now: u64) -> BitcoinUtxos {
+        BitcoinUtxos::from(
+            utxos
+                .iter()
+                .filter(|utxo| !self.is_locked(&utxo.outpoint, now))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Locks the outpoints for [OUTPOINT_LOCK_TTL] from `now`.
+    pub fn lock_outpoints(&mut self, outpoints: Vec<OutPoint>, now: u64) {
+        self.unlock_outpoints(&outpoints);
+
+        let expires_at = now + OUTPOINT_LOCK_TTL;
+
+        self.locked_outpoints
+            .extend(outpoints.into_iter().map(|outpoint| LockedOutpoint {
+                outpoint,
+                expires_at,
+            }));
+    }
+
+    /// Gives the outpoints back, e.g. when the transaction spending them failed to send.
+    pub fn unlock_outpoints(&mut self, outpoints: &[OutPoint]) {
+        self.locked_outpoints
+            .retain(|locked| !outpoints.contains(&locked.outpoint));
+    }
+
+    /// Drops the expired locks. An outpoint missing from the UTXOs stays locked, it may be
+    /// spent by a transaction in the mempool, the lock goes once that transaction confirms.
+    pub fn release_locks(&mut self, now: u64) {
+        self.locked_outpoints
+            .retain(|locked| locked.expires_at > now);
     }
 
     /// Gathers ECDSA signatures for all the inputs in the specified unsigned
@@ -137,29 +396,4 @@ impl BtcChain {
             lock_time: unsigned_tx.lock_time,
         })
     }
-
-    pub async fn swap_to_ckbtc(&self, amount: Satoshi) -> Result<BtcPending, BitcoinError> {
-        let minter = Minter::new(self.btc_network.clone());
-
-        let account = ICRCAccount::from(self.subaccount.clone());
-
-        let dst_address = minter
-            .get_btc_address(account.clone())
-            .await
-            .map_err(|err| BitcoinError::SwapToCkbtc(err.to_string()))?;
-
-        let (txid_bytes, _) = self
-            .transfer(dst_address, amount)
-            .await
-            .map_err(|err| BitcoinError::SwapToCkbtc(err.to_string()))?;
-
-        let txid = vec_to_hex_string(txid_bytes);
-
-        let pending = BtcPending {
-            txid,
-            account: account.to_string(),
-        };
-
-        Ok(pending)
-    }
 }
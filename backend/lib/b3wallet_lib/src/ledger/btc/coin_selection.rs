@@ -0,0 +1,325 @@
+//! Coin selection picks which UTXOs fund a transaction.
+//! Every strategy settles fee and change through the same [FeeModel], so the
+//! fee estimated before building a transaction is the fee it pays.
+
+use super::{
+    error::BitcoinError,
+    types::{Satoshi, Utxo},
+    utils::tx_vsize_estimate,
+};
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::fmt;
+
+/// Change below this value is not worth an output, it is left to the fee instead.
+pub const DUST_THRESHOLD: Satoshi = 1_000;
+
+/// The number of steps after which Branch and Bound gives up on finding a changeless match.
+const BNB_MAX_TRIES: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeModel {
+    /// The fee rate in millisatoshi per vbyte.
+    pub fee_per_vbyte: u64,
+    /// The number of outputs paid by the transaction, not counting the change.
+    pub output_count: u64,
+}
+
+/// The UTXOs picked by a strategy, with the fee and change of spending them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub utxos: Vec<Utxo>,
+    pub fee: Satoshi,
+    /// Zero when the transaction has no change output.
+    pub change: Satoshi,
+}
+
+impl FeeModel {
+    pub fn new(fee_per_vbyte: u64, output_count: u64) -> Self {
+        Self {
+            fee_per_vbyte,
+            output_count,
+        }
+    }
+
+    /// The fee of a transaction spending `input_count` inputs, with or without a change output.
+    pub fn fee(&self, input_count: u64, with_change: bool) -> Satoshi {
+        let output_count = self.output_count + with_change as u64;
+
+        tx_vsize_estimate(input_count, output_count) * self.fee_per_vbyte / 1000
+    }
+
+    /// The fee that spending one more input adds.
+    pub fn input_fee(&self) -> Satoshi {
+        self.fee(1, true) - self.fee(0, true)
+    }
+
+    /// Whether the UTXO is worth more than the fee of spending it.
+    pub fn is_economical(&self, utxo: &Utxo) -> bool {
+        utxo.value > self.input_fee()
+    }
+
+    /// Settles the fee and change of paying `target` with `utxos`.
+    /// Change that would be dust is left to the fee.
+    /// Returns None if the UTXOs do not cover the target and the fee.
+    pub fn settle(&self, utxos: &[Utxo], target: Satoshi) -> Option<Selection> {
+        let input_count = utxos.len() as u64;
+        let total: Satoshi = utxos.iter().map(|utxo| utxo.value).sum();
+
+        let fee = self.fee(input_count, true);
+
+        if total >= target.saturating_add(fee).saturating_add(DUST_THRESHOLD) {
+            return Some(Selection {
+                utxos: utxos.to_vec(),
+                fee,
+                change: total - target - fee,
+            });
+        }
+
+        if total >= target.saturating_add(self.fee(input_count, false)) {
+            return Some(Selection {
+                utxos: utxos.to_vec(),
+                fee: total - target,
+                change: 0,
+            });
+        }
+
+        None
+    }
+
+    /// Adds the UTXOs in order until they pay for the target.
+    fn accumulate<'a>(
+        &self,
+        utxos: impl Iterator<Item = &'a Utxo>,
+        target: Satoshi,
+    ) -> Option<Selection> {
+        let mut selected = Vec::new();
+
+        for utxo in utxos.filter(|utxo| self.is_economical(utxo)) {
+            selected.push(utxo.clone());
+
+            if let Some(selection) = self.settle(&selected, target) {
+                return Some(selection);
+            }
+        }
+
+        None
+    }
+}
+
+pub trait CoinSelection {
+    /// Picks the UTXOs that pay `target` plus the fee of the transaction.
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError>;
+}
+
+fn insufficient_balance(utxos: &[Utxo], target: Satoshi) -> BitcoinError {
+    let total = utxos.iter().map(|utxo| utxo.value).sum();
+
+    BitcoinError::InsufficientBalance(total, target)
+}
+
+/// Spends the biggest UTXOs first, which keeps the number of inputs low.
+pub struct LargestFirst;
+
+impl CoinSelection for LargestFirst {
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError> {
+        let mut candidates: Vec<&Utxo> = utxos.iter().collect();
+        candidates.sort_by(|a, b| b.value.cmp(&a.value));
+
+        fee_model
+            .accumulate(candidates.into_iter(), target)
+            .ok_or_else(|| insufficient_balance(utxos, target))
+    }
+}
+
+/// Spends the UTXOs with the lowest block height first, which consolidates old coins.
+pub struct OldestFirst;
+
+impl CoinSelection for OldestFirst {
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError> {
+        let mut candidates: Vec<&Utxo> = utxos.iter().collect();
+        candidates.sort_by(|a, b| a.height.cmp(&b.height).then(b.value.cmp(&a.value)));
+
+        fee_model
+            .accumulate(candidates.into_iter(), target)
+            .ok_or_else(|| insufficient_balance(utxos, target))
+    }
+}
+
+/// Searches for UTXOs that pay the target without a change output, wasting at most
+/// what the change output would have cost. Falls back to [LargestFirst] if there is none.
+pub struct BranchAndBound;
+
+struct BnbSearch<'a> {
+    candidates: Vec<&'a Utxo>,
+    target: Satoshi,
+    fee_model: &'a FeeModel,
+    tries: usize,
+    best: Option<(Satoshi, Vec<usize>)>,
+}
+
+impl BnbSearch<'_> {
+    fn search(&mut self, index: usize, selected: &mut Vec<usize>, total: Satoshi, rest: Satoshi) {
+        self.tries += 1;
+
+        if self.tries > BNB_MAX_TRIES || matches!(self.best, Some((0, _))) {
+            return;
+        }
+
+        let input_count = selected.len() as u64;
+        let lower = self
+            .target
+            .saturating_add(self.fee_model.fee(input_count, false));
+        let upper = self
+            .target
+            .saturating_add(self.fee_model.fee(input_count, true))
+            .saturating_add(DUST_THRESHOLD);
+
+        if total >= upper {
+            return;
+        }
+
+        if !selected.is_empty() && total >= lower {
+            let waste = total - lower;
+
+            if self.best.as_ref().map_or(true, |(best, _)| waste < *best) {
+                self.best = Some((waste, selected.clone()));
+            }
+
+            // every economical input adds more value than fee, so the waste would only grow
+            return;
+        }
+
+        if index == self.candidates.len() || total.saturating_add(rest) < lower {
+            return;
+        }
+
+        let value = self.candidates[index].value;
+
+        selected.push(index);
+        self.search(index + 1, selected, total + value, rest - value);
+        selected.pop();
+
+        self.search(index + 1, selected, total, rest - value);
+    }
+}
+
+impl CoinSelection for BranchAndBound {
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError> {
+        let mut candidates: Vec<&Utxo> = utxos
+            .iter()
+            .filter(|utxo| fee_model.is_economical(utxo))
+            .collect();
+        candidates.sort_by(|a, b| b.value.cmp(&a.value));
+
+        let rest = candidates.iter().map(|utxo| utxo.value).sum();
+
+        let mut search = BnbSearch {
+            candidates,
+            target,
+            fee_model,
+            tries: 0,
+            best: None,
+        };
+
+        search.search(0, &mut Vec::new(), 0, rest);
+
+        if let Some((_, indexes)) = search.best {
+            let selected: Vec<Utxo> = indexes
+                .into_iter()
+                .map(|index| search.candidates[index].clone())
+                .collect();
+
+            if let Some(selection) = fee_model.settle(&selected, target) {
+                return Ok(selection);
+            }
+        }
+
+        LargestFirst.select(utxos, target, fee_model)
+    }
+}
+
+/// Spends a single UTXO when one covers the payment, the smallest that does,
+/// otherwise as few as possible, so that fewer of the wallet's coins are linked together.
+pub struct Privacy;
+
+impl CoinSelection for Privacy {
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError> {
+        let mut candidates: Vec<&Utxo> = utxos.iter().collect();
+        candidates.sort_by(|a, b| a.value.cmp(&b.value));
+
+        let single = candidates
+            .into_iter()
+            .filter(|utxo| fee_model.is_economical(utxo))
+            .find_map(|utxo| fee_model.settle(&[utxo.clone()], target));
+
+        match single {
+            Some(selection) => Ok(selection),
+            None => LargestFirst.select(utxos, target, fee_model),
+        }
+    }
+}
+
+/// The coin selection strategy of a Bitcoin transfer.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoinSelectionStrategy {
+    BranchAndBound,
+    #[default]
+    LargestFirst,
+    OldestFirst,
+    Privacy,
+}
+
+impl CoinSelection for CoinSelectionStrategy {
+    fn select(
+        &self,
+        utxos: &[Utxo],
+        target: Satoshi,
+        fee_model: &FeeModel,
+    ) -> Result<Selection, BitcoinError> {
+        match self {
+            CoinSelectionStrategy::BranchAndBound => {
+                BranchAndBound.select(utxos, target, fee_model)
+            }
+            CoinSelectionStrategy::LargestFirst => LargestFirst.select(utxos, target, fee_model),
+            CoinSelectionStrategy::OldestFirst => OldestFirst.select(utxos, target, fee_model),
+            CoinSelectionStrategy::Privacy => Privacy.select(utxos, target, fee_model),
+        }
+    }
+}
+
+impl fmt::Display for CoinSelectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoinSelectionStrategy::BranchAndBound => write!(f, "branch and bound"),
+            CoinSelectionStrategy::LargestFirst => write!(f, "largest first"),
+            CoinSelectionStrategy::OldestFirst => write!(f, "oldest first"),
+            CoinSelectionStrategy::Privacy => write!(f, "privacy"),
+        }
+    }
+}
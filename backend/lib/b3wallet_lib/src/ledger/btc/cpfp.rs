@@ -0,0 +1,121 @@
+//! Child-pays-for-parent speeds up a transaction stuck in the mempool that can not be
+//! replaced, e.g. one paying into the wallet, by spending one of its outputs in a child
+//! paying enough fee for miners to take both.
+
+use super::{
+    address::BitcoinAddress,
+    coin_selection::DUST_THRESHOLD,
+    error::BitcoinError,
+    rbf::INCREMENTAL_RELAY_FEE,
+    tx::{TxOut, UnsignedInput, UnsignedTransaction, SEQUENCE_RBF_ENABLED},
+    types::{BtcSentTransaction, Satoshi, Utxo},
+    utils::tx_vsize_estimate,
+};
+
+/// Builds a child spending `parent_output` to `own_address`, so that the parent of
+/// `parent_vsize` vbytes paying `parent_fee` and the child together pay `fee_per_vbyte`
+/// millisatoshi per vbyte.
+///
+/// The child pays at least the minimum relay fee for its own size, and itself signals
+/// replaceability so its fee can be bumped in turn.
+pub fn child_pays_for_parent(
+    parent_output: Utxo,
+    parent_vsize: u64,
+    parent_fee: Satoshi,
+    own_address: &BitcoinAddress,
+    fee_per_vbyte: u64,
+) -> Result<(UnsignedTransaction, BtcSentTransaction), BitcoinError> {
+    let parent_fee_per_vbyte = parent_fee * 1000 / parent_vsize.max(1);
+
+    if fee_per_vbyte <= parent_fee_per_vbyte {
+        return Err(BitcoinError::FeeRateTooLow(
+            fee_per_vbyte,
+            parent_fee_per_vbyte,
+        ));
+    }
+
+    let vsize = tx_vsize_estimate(1, 1);
+
+    let package_fee = (parent_vsize + vsize) * fee_per_vbyte / 1000;
+
+    let fee = package_fee
+        .saturating_sub(parent_fee)
+        .max(vsize * INCREMENTAL_RELAY_FEE / 1000);
+
+    if parent_output.value < fee + DUST_THRESHOLD {
+        return Err(BitcoinError::InsufficientBalance(
+            parent_output.value,
+            fee + DUST_THRESHOLD,
+        ));
+    }
+
+    let change = parent_output.value - fee;
+
+    let unsigned_transaction = UnsignedTransaction {
+        inputs: vec![UnsignedInput {
+            previous_output: parent_output.outpoint.clone(),
+            sequence: SEQUENCE_RBF_ENABLED,
+            value: parent_output.value,
+        }],
+        outputs: vec![TxOut {
+            address: own_address.clone(),
+            value: change,
+        }],
+        lock_time: 0,
+    };
+
+    let child = BtcSentTransaction {
+        inputs: vec![parent_output],
+        outputs: vec![],
+        change,
+        fee,
+        vsize,
+    };
+
+    Ok((unsigned_transaction, child))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::btc::types::OutPoint;
+
+    fn parent_output(value: u64) -> Utxo {
+        Utxo {
+            outpoint: OutPoint::new(vec![1; 32], 1),
+            value,
+            height: 0,
+        }
+    }
+
+    #[test]
+    fn test_child_pays_for_parent() {
+        let own_address = BitcoinAddress::P2wpkhV0([0; 20]);
+
+        // a parent of 200 vbytes paying 1 sat/vbyte, to 20 sat/vbyte
+        let (tx, child) =
+            child_pays_for_parent(parent_output(50_000), 200, 200, &own_address, 20_000).unwrap();
+
+        let vsize = tx_vsize_estimate(1, 1);
+
+        assert_eq!(child.fee, (200 + vsize) * 20 - 200);
+        assert_eq!((child.fee + 200) * 1000 / (200 + vsize), 20_000);
+        assert_eq!(child.change, 50_000 - child.fee);
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(tx.inputs[0].previous_output, parent_output(50_000).outpoint);
+        assert_eq!(tx.inputs[0].sequence, SEQUENCE_RBF_ENABLED);
+        assert_eq!(tx.outputs.len(), 1);
+        assert_eq!(tx.outputs[0].address, own_address);
+        assert_eq!(tx.outputs[0].value, child.change);
+
+        assert_eq!(
+            child_pays_for_parent(parent_output(50_000), 200, 4_000, &own_address, 20_000),
+            Err(BitcoinError::FeeRateTooLow(20_000, 20_000))
+        );
+
+        assert!(matches!(
+            child_pays_for_parent(parent_output(5_000), 200, 200, &own_address, 20_000),
+            Err(BitcoinError::InsufficientBalance(5_000, _))
+        ));
+    }
+}
@@ -23,6 +23,14 @@ pub enum BitcoinError {
     InvalidNetworkAddress(String),
     InvalidChain(String),
     DustOutput { address: String, amount: u64 },
+    TransactionNotFound(String),
+    TransactionNotConfirmed(String),
+    TransactionAlreadyConfirmed(String),
+    FeeRateTooLow(u64, u64),
+    InvalidTxid(String),
+    OutputNotFound(String, u32),
+    OutputLocked(String, u32),
+    UnlockedTransfer,
 }
 
 #[rustfmt::skip]
@@ -48,6 +56,14 @@ impl fmt::Display for BitcoinError {
             BitcoinError::InvalidFeePercentile(msg) => write!(f, "Invalid fee percentile: {}", msg),
             BitcoinError::InvalidNetworkAddress(msg) => write!(f, "Invalid network address: {}", msg),
             BitcoinError::DustOutput { address, amount } => write!(f, "Dust output: {} < {}", address, amount),
+            BitcoinError::TransactionNotFound(txid) => write!(f, "Transaction not found: {}", txid),
+            BitcoinError::TransactionNotConfirmed(txid) => write!(f, "Transaction not confirmed: {}", txid),
+            BitcoinError::TransactionAlreadyConfirmed(txid) => write!(f, "Transaction already confirmed: {}", txid),
+            BitcoinError::FeeRateTooLow(rate, minimum) => write!(f, "Fee rate too low: {} <= {}", rate, minimum),
+            BitcoinError::InvalidTxid(txid) => write!(f, "Invalid txid: {}", txid),
+            BitcoinError::OutputNotFound(txid, vout) => write!(f, "Output not found: {}:{}", txid, vout),
+            BitcoinError::OutputLocked(txid, vout) => write!(f, "Output already spent by a transaction in flight: {}:{}", txid, vout),
+            BitcoinError::UnlockedTransfer => write!(f, "Bitcoin is only sent through the wallet store, which locks the inputs"),
         }
     }
 }
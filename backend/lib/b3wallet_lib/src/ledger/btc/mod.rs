@@ -1,8 +1,11 @@
 pub mod address;
 pub mod api;
 pub mod btc;
+pub mod coin_selection;
+pub mod cpfp;
 pub mod error;
 pub mod network;
+pub mod rbf;
 pub mod signature;
 pub mod tx;
 pub mod types;
@@ -0,0 +1,208 @@
+//! Replace-by-fee (BIP-125) rebuilds a sent transaction that is stuck in the mempool
+//! with a higher fee, spending the same inputs and paying the same outputs.
+
+use super::{
+    address::BitcoinAddress,
+    coin_selection::{FeeModel, DUST_THRESHOLD},
+    error::BitcoinError,
+    tx::{TxOut, UnsignedInput, UnsignedTransaction, SEQUENCE_RBF_ENABLED},
+    types::{BtcSentTransaction, Satoshi, Utxo},
+    utils::tx_vsize_estimate,
+    utxos::BitcoinUtxos,
+};
+
+/// The fee a replacement pays on top of the original for each of its vbytes,
+/// in millisatoshi (the default incremental relay fee of Bitcoin Core).
+pub const INCREMENTAL_RELAY_FEE: u64 = 1000;
+
+/// Rebuilds `sent` at `fee_per_vbyte` millisatoshi per vbyte, paying `payments` again.
+/// The extra fee comes out of the change, and when the change is not enough
+/// the coins of `utxos` are added, largest first.
+///
+/// As BIP-125 asks, the replacement pays a higher fee rate and at least the fee of
+/// the original plus the incremental relay fee for its own size.
+pub fn replace_by_fee(
+    sent: &BtcSentTransaction,
+    payments: Vec<TxOut>,
+    own_address: &BitcoinAddress,
+    utxos: &BitcoinUtxos,
+    fee_per_vbyte: u64,
+) -> Result<(UnsignedTransaction, BtcSentTransaction), BitcoinError> {
+    if fee_per_vbyte <= sent.fee_per_vbyte() {
+        return Err(BitcoinError::FeeRateTooLow(
+            fee_per_vbyte,
+            sent.fee_per_vbyte(),
+        ));
+    }
+
+    let payment_count = payments.len() as u64;
+    let amount: Satoshi = payments.iter().map(|payment| payment.value).sum();
+
+    let fee_model = FeeModel::new(fee_per_vbyte, payment_count);
+
+    let required_fee = |input_count: u64, with_change: bool| {
+        let vsize = tx_vsize_estimate(input_count, payment_count + with_change as u64);
+
+        fee_model
+            .fee(input_count, with_change)
+            .max(sent.fee + vsize * INCREMENTAL_RELAY_FEE / 1000)
+    };
+
+    let mut extra: Vec<&Utxo> = utxos
+        .iter()
+        .filter(|utxo| fee_model.is_economical(utxo))
+        .filter(|utxo| {
+            !sent
+                .inputs
+                .iter()
+                .any(|input| input.outpoint == utxo.outpoint)
+        })
+        .collect();
+    extra.sort_by(|a, b| b.value.cmp(&a.value));
+
+    let mut extra = extra.into_iter();
+    let mut inputs = sent.inputs.clone();
+
+    let (fee, change) = loop {
+        let input_count = inputs.len() as u64;
+        let total: Satoshi = inputs.iter().map(|input| input.value).sum();
+
+        let fee = required_fee(input_count, true);
+        if total >= amount + fee + DUST_THRESHOLD {
+            break (fee, total - amount - fee);
+        }
+
+        let fee = required_fee(input_count, false);
+        if total >= amount + fee {
+            break (total - amount, 0);
+        }
+
+        match extra.next() {
+            Some(utxo) => inputs.push(utxo.clone()),
+            None => return Err(BitcoinError::InsufficientBalance(total, amount + fee)),
+        }
+    };
+
+    let mut outputs = payments;
+
+    if change > 0 {
+        outputs.push(TxOut {
+            address: own_address.clone(),
+            value: change,
+        });
+    }
+
+    let replacement = BtcSentTransaction {
+        vsize: tx_vsize_estimate(inputs.len() as u64, outputs.len() as u64),
+        inputs,
+        outputs: sent.outputs.clone(),
+        change,
+        fee,
+    };
+
+    let unsigned_transaction = UnsignedTransaction {
+        inputs: replacement
+            .inputs
+            .iter()
+            .map(|utxo| UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                sequence: SEQUENCE_RBF_ENABLED,
+                value: utxo.value,
+            })
+            .collect(),
+        outputs,
+        lock_time: 0,
+    };
+
+    Ok((unsigned_transaction, replacement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::btc::types::OutPoint;
+
+    fn utxo(txid: u8, value: u64) -> Utxo {
+        Utxo {
+            outpoint: OutPoint::new(vec![txid; 32], 0),
+            value,
+            height: 10,
+        }
+    }
+
+    fn payment() -> TxOut {
+        TxOut {
+            address: BitcoinAddress::P2wpkhV0([1; 20]),
+            value: 50_000,
+        }
+    }
+
+    fn sent(inputs: Vec<Utxo>, change: u64, fee: u64, vsize: u64) -> BtcSentTransaction {
+        BtcSentTransaction {
+            inputs,
+            outputs: vec![("bc1q".to_string(), 50_000)],
+            change,
+            fee,
+            vsize,
+        }
+    }
+
+    #[test]
+    fn test_replace_by_fee_lowers_change() {
+        let own_address = BitcoinAddress::P2wpkhV0([0; 20]);
+
+        // one input and two outputs at 1 sat/vbyte
+        let original = sent(vec![utxo(1, 100_000)], 100_000 - 50_000 - 141, 141, 141);
+
+        let utxos = BitcoinUtxos::from(vec![utxo(1, 100_000), utxo(2, 30_000)]);
+
+        let (tx, replacement) =
+            replace_by_fee(&original, vec![payment()], &own_address, &utxos, 5000).unwrap();
+
+        assert_eq!(replacement.inputs, original.inputs);
+        assert_eq!(replacement.fee, 141 * 5);
+        assert_eq!(replacement.change, 100_000 - 50_000 - 141 * 5);
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(tx.inputs[0].sequence, SEQUENCE_RBF_ENABLED);
+        assert_eq!(tx.outputs[0], payment());
+        assert_eq!(tx.outputs[1].address, own_address);
+        assert_eq!(tx.outputs[1].value, replacement.change);
+
+        // a barely higher rate still pays the incremental relay fee
+        let (_, replacement) =
+            replace_by_fee(&original, vec![payment()], &own_address, &utxos, 1001).unwrap();
+
+        assert_eq!(replacement.fee, 141 + 141);
+
+        assert_eq!(
+            replace_by_fee(&original, vec![payment()], &own_address, &utxos, 1000),
+            Err(BitcoinError::FeeRateTooLow(1000, 1000))
+        );
+    }
+
+    #[test]
+    fn test_replace_by_fee_adds_inputs() {
+        let own_address = BitcoinAddress::P2wpkhV0([0; 20]);
+
+        // no change output, 500 satoshi of fee for 110 vbytes
+        let original = sent(vec![utxo(1, 50_500)], 0, 500, 110);
+
+        let utxos = BitcoinUtxos::from(vec![utxo(1, 50_500), utxo(2, 20_000), utxo(3, 10)]);
+
+        let (tx, replacement) =
+            replace_by_fee(&original, vec![payment()], &own_address, &utxos, 10_000).unwrap();
+
+        // the original input stays first, the uneconomical coin is left out
+        assert_eq!(replacement.inputs, vec![utxo(1, 50_500), utxo(2, 20_000)]);
+        assert_eq!(replacement.fee, tx_vsize_estimate(2, 2) * 10);
+        assert_eq!(replacement.change, 70_500 - 50_000 - replacement.fee);
+        assert_eq!(tx.outputs.len(), 2);
+
+        let utxos = BitcoinUtxos::from(vec![utxo(1, 50_500)]);
+
+        assert!(matches!(
+            replace_by_fee(&original, vec![payment()], &own_address, &utxos, 10_000),
+            Err(BitcoinError::InsufficientBalance(50_500, _))
+        ));
+    }
+}
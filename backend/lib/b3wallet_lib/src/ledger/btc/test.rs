@@ -3,6 +3,9 @@ use std::str::FromStr;
 
 use crate::ledger::btc::error::BitcoinError;
 
+use super::coin_selection::{
+    CoinSelection, CoinSelectionStrategy, FeeModel, LargestFirst, DUST_THRESHOLD,
+};
 use super::network::BitcoinNetwork;
 use super::types::{OutPoint, Satoshi, Utxo};
 use super::utxos::BitcoinUtxos;
@@ -22,6 +25,7 @@ use proptest::{
     array::uniform32,
     collection::vec as pvec,
     prelude::{any, Strategy},
+    strategy::Just,
 };
 use proptest::{prop_assert, prop_assert_eq, prop_assume, prop_oneof};
 use serde_bytes::ByteBuf;
@@ -230,6 +234,195 @@ fn test_no_dust_outputs() {
     assert_eq!(available_utxos.len(), 1);
 }
 
+#[test]
+fn test_signed_tx_txid() {
+    // Mainnet transaction f5864806e3565c34d1b41e716f72609d00b55ea5eac5b924c9719a842ef42206,
+    // spending a P2WPKH output to a P2SH address.
+    let tx_bytes = hex::decode(
+        "02000000000101595895ea20179de87052b4046dfe6fd515860505d6511a9004cf12a1f93cac7c01000000\
+         00ffffffff01deb807000000000017a9140f3444e271620c736808aa7b33e370bd87cb5a078702483045022\
+         100fb60dad8df4af2841adc0346638c16d0b8035f5e3f3753b88db122e70c79f9370220756e6633b17fd271\
+         0e626347d28d60b0a2d6cbb41de51740644b9fb3ba7751040121028fa937ca8cba2197a37c007176ed89410\
+         55d3bcb8627d085e94553e62f057dcc00000000",
+    )
+    .unwrap();
+
+    let previous_txid =
+        tx::parse_txid("7cac3cf9a112cf04901a51d605058615d56ffe6d04b45270e89d1720ea955859").unwrap();
+
+    let script_hash: [u8; 20] = hex::decode("0f3444e271620c736808aa7b33e370bd87cb5a07")
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let signature = EncodedSignature::try_from_slice(
+        &hex::decode(
+            "3045022100fb60dad8df4af2841adc0346638c16d0b8035f5e3f3753b88db122e70c79f9370220756e66\
+             33b17fd2710e626347d28d60b0a2d6cbb41de51740644b9fb3ba77510401",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let pubkey = ByteBuf::from(
+        hex::decode("028fa937ca8cba2197a37c007176ed8941055d3bcb8627d085e94553e62f057dcc").unwrap(),
+    );
+
+    let outputs = vec![tx::TxOut {
+        value: 506_078,
+        address: BitcoinAddress::P2sh(script_hash),
+    }];
+
+    let signed_tx = tx::SignedTransaction {
+        inputs: vec![tx::SignedInput {
+            previous_output: OutPoint::new(previous_txid.to_vec(), 1),
+            sequence: 0xffffffff,
+            signature,
+            pubkey,
+        }],
+        outputs: outputs.clone(),
+        lock_time: 0,
+    };
+
+    assert_eq!(signed_tx.serialize(), tx_bytes);
+    assert_eq!(
+        signed_tx.display_txid(),
+        "f5864806e3565c34d1b41e716f72609d00b55ea5eac5b924c9719a842ef42206"
+    );
+    assert_eq!(
+        tx::DisplayTxid(&signed_tx.wtxid()).to_string(),
+        "80b7d8a82d5d5bf92905b06f2014dd699e03837ca172e3a59d51426ebbe3e7f5"
+    );
+    assert_eq!(signed_tx.vsize(), 111);
+
+    // The txid does not depend on the signatures, so it is known before signing.
+    let unsigned_tx = tx::UnsignedTransaction {
+        inputs: vec![tx::UnsignedInput {
+            previous_output: OutPoint::new(previous_txid.to_vec(), 1),
+            value: 0,
+            sequence: 0xffffffff,
+        }],
+        outputs,
+        lock_time: 0,
+    };
+
+    assert_eq!(unsigned_tx.txid(), signed_tx.txid());
+
+    let decoded_tx: Transaction = deserialize(&tx_bytes).unwrap();
+    assert_eq!(decoded_tx.txid().to_string(), signed_tx.display_txid());
+    assert_eq!(decoded_tx.wtxid().to_byte_array(), signed_tx.wtxid());
+
+    assert_eq!(
+        tx::parse_txid(&signed_tx.display_txid()),
+        Ok(signed_tx.txid())
+    );
+    assert_eq!(
+        tx::parse_txid("f58648"),
+        Err(BitcoinError::InvalidTxid("f58648".to_string()))
+    );
+}
+
+#[test]
+fn test_branch_and_bound_avoids_change() {
+    let utxo = |value: u64, vout: u32| Utxo {
+        outpoint: OutPoint::new(vec![vout as u8; 32], vout),
+        value,
+        height: 10,
+    };
+
+    let utxos = vec![utxo(150_000, 0), utxo(60_000, 1), utxo(40_000, 2)];
+    let fee_model = FeeModel::new(1000, 1);
+
+    // two inputs and one output weigh 178 vbytes
+    let target = 100_000 - 178;
+
+    let selection = CoinSelectionStrategy::BranchAndBound
+        .select(&utxos, target, &fee_model)
+        .unwrap();
+
+    assert_eq!(selection.utxos, vec![utxo(60_000, 1), utxo(40_000, 2)]);
+    assert_eq!(selection.fee, 178);
+    assert_eq!(selection.change, 0);
+
+    let selection = LargestFirst.select(&utxos, target, &fee_model).unwrap();
+
+    assert_eq!(selection.utxos, vec![utxo(150_000, 0)]);
+    assert_eq!(selection.change, 150_000 - target - fee_model.fee(1, true));
+
+    assert_eq!(
+        CoinSelectionStrategy::Privacy.select(&utxos, 1_000_000, &fee_model),
+        Err(BitcoinError::InsufficientBalance(250_000, 1_000_000))
+    );
+}
+
+#[test]
+fn test_batch_transfer_is_cheaper() {
+    let utxos = BitcoinUtxos::from(
+        (0..10u8)
+            .map(|i| Utxo {
+                outpoint: OutPoint::new(vec![i; 32], 0),
+                value: 1_000_000,
+                height: 10,
+            })
+            .collect(),
+    );
+
+    let own_address = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 5000;
+
+    let outputs: Vec<tx::TxOut> = (1..=5u8)
+        .map(|i| tx::TxOut {
+            value: 100_000 * i as u64,
+            address: BitcoinAddress::P2wpkhV0([i; 20]),
+        })
+        .collect();
+
+    let (batch_tx, batch_fee) = utxos
+        .build_transaction(
+            &CoinSelectionStrategy::LargestFirst,
+            &own_address,
+            outputs.clone(),
+            fee_per_vbyte,
+        )
+        .unwrap();
+
+    // every payment plus a single change output
+    assert_eq!(batch_tx.outputs.len(), outputs.len() + 1);
+    assert_eq!(&batch_tx.outputs[..outputs.len()], &outputs[..]);
+    assert_eq!(batch_tx.outputs.last().unwrap().address, own_address);
+
+    let separate_fees: u64 = outputs
+        .iter()
+        .map(|output| {
+            utxos
+                .build_unsigned_transaction(
+                    &own_address,
+                    &output.address,
+                    output.value,
+                    fee_per_vbyte,
+                )
+                .unwrap()
+                .1
+        })
+        .sum();
+
+    assert_eq!(
+        batch_fee,
+        tx_vsize_estimate(2, 6) * fee_per_vbyte / 1000,
+        "two inputs cover the payments"
+    );
+    assert!(batch_fee < separate_fees);
+}
+
+fn arb_coin_selection() -> impl Strategy<Value = CoinSelectionStrategy> {
+    prop_oneof![
+        Just(CoinSelectionStrategy::BranchAndBound),
+        Just(CoinSelectionStrategy::LargestFirst),
+        Just(CoinSelectionStrategy::OldestFirst),
+        Just(CoinSelectionStrategy::Privacy),
+    ]
+}
+
 fn arb_amount() -> impl Strategy<Value = Satoshi> {
     1..10_000_000_000u64
 }
@@ -370,6 +563,8 @@ proptest! {
         prop_assert_eq!(serialize(&btc_tx), tx_bytes);
         prop_assert_eq!(&decoded_btc_tx, &btc_tx);
         prop_assert_eq!(&arb_tx.wtxid(), &btc_tx.wtxid().to_byte_array());
+        prop_assert_eq!(&arb_tx.txid(), &btc_tx.txid().to_byte_array());
+        prop_assert_eq!(arb_tx.display_txid(), btc_tx.txid().to_string());
         prop_assert_eq!(arb_tx.vsize(), btc_tx.vsize());
     }
 
@@ -653,4 +848,58 @@ proptest! {
             lower_bound
         );
     }
+
+    #[test]
+    fn coin_selection_covers_outputs_and_fee(
+        dummy_utxos in pvec(arb_utxo(5_000u64..1_000_000_000), 1..20),
+        strategy in arb_coin_selection(),
+        dst_pkhashes in pvec(uniform20(any::<u8>()), 1..5),
+        main_pkhash in uniform20(any::<u8>()),
+        fee_per_vbyte in 1000..10000u64,
+    ) {
+        let utxos = BitcoinUtxos::from(dummy_utxos);
+
+        let output_value = utxos.total_value() / 2 / dst_pkhashes.len() as u64;
+
+        let outputs: Vec<tx::TxOut> = dst_pkhashes
+            .into_iter()
+            .map(|pkhash| tx::TxOut {
+                value: output_value,
+                address: BitcoinAddress::P2wpkhV0(pkhash),
+            })
+            .collect();
+
+        let amount = outputs.iter().map(|output| output.value).sum::<u64>();
+        let fee_model = FeeModel::new(fee_per_vbyte, outputs.len() as u64);
+
+        let fee_estimate = utxos.estimate_fee_with(&strategy, amount, &fee_model);
+
+        let (unsigned_tx, fee) = utxos.build_transaction(
+            &strategy,
+            &BitcoinAddress::P2wpkhV0(main_pkhash),
+            outputs.clone(),
+            fee_per_vbyte
+        )
+        .expect("failed to build transaction");
+
+        let inputs_value = unsigned_tx.inputs.iter().map(|input| input.value).sum::<u64>();
+        let outputs_value = unsigned_tx.outputs.iter().map(|output| output.value).sum::<u64>();
+
+        prop_assert_eq!(fee, fee_estimate, "the estimate and the selection disagree");
+        prop_assert_eq!(inputs_value, outputs_value + fee);
+        prop_assert!(inputs_value >= amount + fee);
+        prop_assert!(fee >= unsigned_tx.fake_sign().vsize() as u64 * fee_per_vbyte / 1000);
+        prop_assert_eq!(&unsigned_tx.outputs[..outputs.len()], &outputs[..]);
+
+        if unsigned_tx.outputs.len() > outputs.len() {
+            let change = unsigned_tx.outputs.last().unwrap();
+
+            prop_assert_eq!(&change.address, &BitcoinAddress::P2wpkhV0(main_pkhash));
+            prop_assert!(change.value >= DUST_THRESHOLD);
+        }
+
+        for input in unsigned_tx.inputs.iter() {
+            prop_assert!(utxos.iter().any(|utxo| utxo.outpoint == input.previous_output));
+        }
+    }
 }
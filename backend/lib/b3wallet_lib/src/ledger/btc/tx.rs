@@ -3,6 +3,7 @@
 
 use crate::ledger::btc::types::Satoshi;
 
+use super::error::BitcoinError;
 use super::signature::EncodedSignature;
 use super::types::OutPoint;
 use super::{address::BitcoinAddress, types::Txid};
@@ -21,6 +22,9 @@ pub const PUBKEY_LEN: usize = 32;
 const MARKER: u8 = 0;
 // The flags for the segregated witness encoding.
 const FLAGS: u8 = 1;
+/// Signals that the transaction can be replaced with a higher fee (BIP-125).
+pub const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
+
 // The signature applies to all inputs and outputs.
 pub const SIGHASH_ALL: u32 = 1;
 
@@ -44,6 +48,31 @@ impl fmt::Display for DisplayOutpoint<'_> {
     }
 }
 
+/// Displays a transaction id the way block explorers and the ckBTC minter do,
+/// as hex in reversed byte order.
+pub struct DisplayTxid<'a>(pub &'a Txid);
+
+impl fmt::Display for DisplayTxid<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter().rev() {
+            write!(fmt, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a transaction id as shown by block explorers, the reverse of [DisplayTxid].
+pub fn parse_txid(txid: &str) -> Result<Txid, BitcoinError> {
+    let mut bytes = hex::decode(txid)
+        .ok()
+        .and_then(|bytes| Txid::try_from(bytes).ok())
+        .ok_or(BitcoinError::InvalidTxid(txid.to_string()))?;
+
+    bytes.reverse();
+
+    Ok(bytes)
+}
+
 /// Displays an amount in satoshis as decimal fraction of BTC.
 pub struct DisplayAmount(pub u64);
 
@@ -379,6 +408,20 @@ impl SignedTransaction {
         encode_into(&BaseTxView(self), CountBytes::default())
     }
 
+    /// Returns the transaction id, the double SHA-256 of the transaction
+    /// serialized without the witness data, in internal byte order.
+    /// This is what the outpoints spending this transaction refer to.
+    pub fn txid(&self) -> Txid {
+        Sha256::hash(&encode_into(&BaseTxView(self), Sha256::new()))
+    }
+
+    /// Returns the transaction id as shown by block explorers.
+    pub fn display_txid(&self) -> String {
+        DisplayTxid(&self.txid()).to_string()
+    }
+
+    /// Returns the witness transaction id, which commits to the signatures
+    /// and differs from the txid for segwit transactions.
     pub fn wtxid(&self) -> [u8; 32] {
         Sha256::hash(&encode_into(self, Sha256::new()))
     }
@@ -1,4 +1,5 @@
 use candid::{CandidType, Deserialize};
+use serde::Serialize;
 
 pub type BtcTxId = String;
 
@@ -8,8 +9,14 @@ pub type Txid = [u8; 32];
 
 pub type Satoshi = u64;
 
+/// How long an outpoint stays locked after a transaction spending it was built,
+/// in nanoseconds (24 hours). Spends that never confirm give their coins back after it.
+pub const OUTPOINT_LOCK_TTL: u64 = 24 * 60 * 60 * 1_000_000_000;
+
 /// A reference to a transaction output.
-#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(
+    CandidType, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
 pub struct OutPoint {
     /// A cryptographic hash of the transaction.
     /// A transaction can output multiple UTXOs.
@@ -25,8 +32,54 @@ impl OutPoint {
     }
 }
 
+/// A transaction sent by the wallet, kept until it confirms so it can be replaced.
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BtcSentTransaction {
+    /// The coins spent, in the order of the transaction inputs.
+    pub inputs: Vec<Utxo>,
+    /// The payments as (address, amount), in the order of the transaction outputs.
+    pub outputs: Vec<(String, Satoshi)>,
+    /// The value of the change output, the last one, zero if there is none.
+    pub change: Satoshi,
+    pub fee: Satoshi,
+    /// The estimated virtual size, in vbytes.
+    pub vsize: u64,
+}
+
+impl BtcSentTransaction {
+    /// The fee rate in millisatoshi per vbyte.
+    pub fn fee_per_vbyte(&self) -> u64 {
+        self.fee * 1000 / self.vsize.max(1)
+    }
+
+    /// The outputs as (address, vout), the change to `address` first if there is one.
+    pub fn outputs_to(&self, address: &str) -> Vec<(String, u32)> {
+        let change = (self.change > 0).then(|| (address.to_string(), self.outputs.len() as u32));
+
+        change
+            .into_iter()
+            .chain(
+                self.outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(vout, (address, _))| (address.clone(), vout as u32)),
+            )
+            .collect()
+    }
+}
+
+/// An outpoint spent by a transaction that is built but not confirmed yet.
+/// `expires_at` is in nanoseconds.
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockedOutpoint {
+    pub outpoint: OutPoint,
+    pub expires_at: u64,
+}
+
 /// An unspent transaction output.
-#[derive(CandidType, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[derive(
+    CandidType, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone,
+)]
 pub struct Utxo {
     pub outpoint: OutPoint,
     pub value: Satoshi,
@@ -1,3 +1,5 @@
+use ic_cdk::api::management_canister::bitcoin::GetUtxosResponse;
+
 /// Computes an estimate for the size of transaction (in vbytes) with the given number of inputs and outputs.
 pub fn tx_vsize_estimate(input_count: u64, output_count: u64) -> u64 {
     // See
@@ -11,3 +13,12 @@ pub fn tx_vsize_estimate(input_count: u64, output_count: u64) -> u64 {
 
     input_count * INPUT_SIZE_VBYTES + output_count * OUTPUT_SIZE_VBYTES + TX_OVERHEAD_VBYTES
 }
+
+/// Whether the output `vout` of the transaction `txid`, in the byte order of the outpoints,
+/// is listed in the UTXOs.
+pub fn has_output(utxos: &GetUtxosResponse, txid: &[u8], vout: u32) -> bool {
+    utxos
+        .utxos
+        .iter()
+        .any(|utxo| utxo.outpoint.txid == txid && utxo.outpoint.vout == vout)
+}
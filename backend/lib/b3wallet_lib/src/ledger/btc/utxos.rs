@@ -2,11 +2,12 @@ use std::collections::BTreeSet;
 
 use super::{
     address::BitcoinAddress,
+    coin_selection::{CoinSelection, CoinSelectionStrategy, FeeModel, DUST_THRESHOLD},
     error::BitcoinError,
-    tx::{TxOut, UnsignedTransaction},
+    tx::{TxOut, UnsignedTransaction, SEQUENCE_RBF_ENABLED},
     types::{OutPoint, Utxo},
 };
-use crate::ledger::btc::{tx::UnsignedInput, utils::tx_vsize_estimate};
+use crate::ledger::btc::tx::UnsignedInput;
 use ic_cdk::api::management_canister::bitcoin::GetUtxosResponse;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -52,7 +53,7 @@ impl BitcoinUtxos {
     }
 
     /// Returns the list of UTXOs.
-    /// The list is sorted by value, descending.
+    /// The list is sorted by outpoint.
     pub fn utxos(&self) -> Vec<Utxo> {
         self.0.iter().cloned().collect()
     }
@@ -61,32 +62,30 @@ impl BitcoinUtxos {
     ///
     /// Arguments:
     /// * `amount` - The amount to send, in satoshi.
-    /// * `fee_per_vbyte` - The fee per vbyte, in satoshi.
+    /// * `fee_per_vbyte` - The fee per vbyte, in millisatoshi.
     ///
     /// Returns:
     /// * The estimated fee, in satoshi.
     pub fn estimate_fee(&self, amount: u64, fee_per_vbyte: u64) -> u64 {
-        // One output for the caller and one for the change.
-        const DEFAULT_OUTPUT_COUNT: u64 = 2;
-        let mut selected_utxos = Vec::new();
-        let mut total_value = 0u64;
-        let mut total_fee = 0u64;
-
-        for utxo in self.0.iter().rev() {
-            selected_utxos.push(utxo.clone());
-            total_value += utxo.value;
-
-            let estimated_vsize =
-                tx_vsize_estimate(selected_utxos.len() as u64, DEFAULT_OUTPUT_COUNT);
-
-            total_fee = estimated_vsize * fee_per_vbyte / 1000;
+        self.estimate_fee_with(
+            &CoinSelectionStrategy::default(),
+            amount,
+            &FeeModel::new(fee_per_vbyte, 1),
+        )
+    }
 
-            if total_value >= amount + total_fee {
-                break;
-            }
+    /// Computes the fee of the transaction that the same strategy and fee model build.
+    /// If the UTXOs do not cover the amount, the fee of spending all of them.
+    pub fn estimate_fee_with(
+        &self,
+        strategy: &impl CoinSelection,
+        amount: u64,
+        fee_model: &FeeModel,
+    ) -> u64 {
+        match strategy.select(&self.utxos(), amount, fee_model) {
+            Ok(selection) => selection.fee,
+            Err(_) => fee_model.fee(self.len() as u64, true),
         }
-
-        total_fee
     }
 
     pub fn build_unsigned_transaction(
@@ -96,48 +95,54 @@ impl BitcoinUtxos {
         amount: u64,
         fee_per_vbyte: u64,
     ) -> Result<(UnsignedTransaction, u64), BitcoinError> {
-        assert!(!self.is_empty());
-
-        const DUST_THRESHOLD: u64 = 1_000;
-        const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
-
-        let mut selected_utxos = Vec::new();
-        let mut total_value = 0u64;
-        let mut total_fee = 0u64;
-
-        println!("total value: {}", self.iter().map(|u| u.value).sum::<u64>());
-
-        for utxo in self.iter() {
-            selected_utxos.push(utxo.clone());
-            total_value += utxo.value;
+        let outputs = vec![TxOut {
+            address: dst_address.clone(),
+            value: amount,
+        }];
 
-            let estimated_vsize = tx_vsize_estimate(selected_utxos.len() as u64, 2);
+        self.build_transaction(
+            &CoinSelectionStrategy::default(),
+            own_address,
+            outputs,
+            fee_per_vbyte,
+        )
+    }
 
-            total_fee = estimated_vsize * fee_per_vbyte / 1000;
+    /// Builds a transaction paying the outputs, with the inputs picked by the strategy.
+    /// The change, if any, goes to `own_address` as the last output.
+    ///
+    /// Returns:
+    /// * The unsigned transaction and its fee, in satoshi.
+    pub fn build_transaction(
+        &self,
+        strategy: &impl CoinSelection,
+        own_address: &BitcoinAddress,
+        outputs: Vec<TxOut>,
+        fee_per_vbyte: u64,
+    ) -> Result<(UnsignedTransaction, u64), BitcoinError> {
+        let amount = outputs
+            .iter()
+            .try_fold(0u64, |sum, output| sum.checked_add(output.value))
+            .ok_or(BitcoinError::InsufficientBalance(
+                self.total_value(),
+                u64::MAX,
+            ))?;
 
-            if total_fee > amount {
-                return Err(BitcoinError::FeeTooHigh(total_fee, amount));
-            }
+        let fee_model = FeeModel::new(fee_per_vbyte, outputs.len() as u64);
 
-            if total_value >= amount + total_fee {
-                break;
-            }
-        }
+        let selection = strategy.select(&self.utxos(), amount, &fee_model)?;
 
-        if total_value < amount + total_fee {
-            return Err(BitcoinError::InsufficientBalance(
-                total_value,
-                amount + total_fee,
-            ));
+        if selection.fee > amount {
+            return Err(BitcoinError::FeeTooHigh(selection.fee, amount));
         }
 
         let mut unsigned_transaction = UnsignedTransaction {
             inputs: Vec::new(),
-            outputs: Vec::new(),
+            outputs,
             lock_time: 0,
         };
 
-        for utxo in selected_utxos {
+        for utxo in selection.utxos {
             let tx_in = UnsignedInput {
                 previous_output: OutPoint::new(utxo.outpoint.txid.clone(), utxo.outpoint.vout),
                 sequence: SEQUENCE_RBF_ENABLED,
@@ -147,20 +152,14 @@ impl BitcoinUtxos {
             unsigned_transaction.inputs.push(tx_in);
         }
 
-        unsigned_transaction.outputs.push(TxOut {
-            address: dst_address.clone(),
-            value: amount,
-        });
-
-        let remaining_amount = total_value - amount - total_fee;
-        if remaining_amount >= DUST_THRESHOLD {
+        if selection.change > 0 {
             unsigned_transaction.outputs.push(TxOut {
                 address: own_address.clone(),
-                value: remaining_amount,
+                value: selection.change,
             });
         }
 
-        Ok((unsigned_transaction, total_fee))
+        Ok((unsigned_transaction, selection.fee))
     }
 
     pub fn build_unsigned_transaction_with_fee(
@@ -170,9 +169,6 @@ impl BitcoinUtxos {
         amount: u64,
         fee: u64,
     ) -> Result<UnsignedTransaction, BitcoinError> {
-        const DUST_THRESHOLD: u64 = 1_000;
-        const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
-
         let mut unsigned_transaction = UnsignedTransaction {
             inputs: Vec::new(),
             outputs: Vec::new(),
@@ -263,11 +259,12 @@ impl TryFrom<Vec<Utxo>> for BitcoinUtxos {
 mod test {
 
     use crate::ledger::{
-        btc::types::OutPoint,
+        btc::coin_selection::CoinSelectionStrategy,
+        btc::types::{BtcSentTransaction, OutPoint, OUTPOINT_LOCK_TTL},
         btc::{network::BitcoinNetwork, utxos::BitcoinUtxos},
         chain::{Chain, ChainTrait},
         ledger::Ledger,
-        types::{ChainEnum, ChainMap},
+        types::{BtcPending, ChainEnum, ChainMap},
     };
 
     use super::*;
@@ -295,6 +292,7 @@ mod test {
             public_key: None,
             subaccount: subaccount.clone(),
             chains,
+            watch_only: false,
         };
 
         let ecdsa = [
@@ -436,4 +434,100 @@ mod test {
 
         assert_eq!(tx.inputs[0].sequence, 0xfffffffd);
     }
+
+    #[test]
+    fn test_locked_outpoints() {
+        let subaccount = Subaccount([
+            8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0,
+        ]);
+
+        let ecdsa = [
+            3, 94, 114, 171, 76, 217, 209, 126, 120, 169, 209, 205, 226, 55, 21, 238, 204, 199,
+            153, 192, 65, 30, 59, 177, 153, 39, 80, 76, 185, 200, 51, 255, 218,
+        ];
+
+        let chain = Chain::new_btc_chain(
+            BitcoinNetwork::Regtest,
+            subaccount,
+            PublicKey::parse_compressed(&ecdsa).unwrap(),
+        )
+        .unwrap();
+
+        let mut btc = chain.btc().unwrap();
+
+        let utxo = |txid: u8, value: u64| Utxo {
+            outpoint: OutPoint::new(vec![txid; 32], 0),
+            value,
+            height: 10,
+        };
+
+        let utxos = BitcoinUtxos::from(vec![utxo(1, 50_000), utxo(2, 40_000), utxo(3, 30_000)]);
+
+        let recipient = BitcoinAddress::parse(
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            BitcoinNetwork::Mainnet,
+        )
+        .unwrap();
+
+        let outputs = || {
+            vec![TxOut {
+                address: recipient.clone(),
+                value: 20_000,
+            }]
+        };
+
+        let now = 1_000;
+        let strategy = CoinSelectionStrategy::LargestFirst;
+
+        let (first, _) = btc
+            .build_locked_transaction(utxos.clone(), &strategy, outputs(), 2000, now)
+            .unwrap();
+        let (second, _) = btc
+            .build_locked_transaction(utxos.clone(), &strategy, outputs(), 2000, now)
+            .unwrap();
+
+        // the second transfer skips the coin locked by the first one
+        assert_eq!(first.inputs[0].previous_output, utxo(1, 0).outpoint);
+        let first_input = utxo(1, first.inputs[0].value);
+        assert_eq!(second.inputs[0].previous_output, utxo(2, 0).outpoint);
+        assert!(btc.is_locked(&utxo(1, 0).outpoint, now));
+        assert!(!btc.is_locked(&utxo(3, 0).outpoint, now));
+
+        // a failed broadcast gives the coin back
+        btc.unlock_outpoints(&[utxo(2, 0).outpoint]);
+        assert!(!btc.is_locked(&utxo(2, 0).outpoint, now));
+
+        // the first spend is in the mempool, its coin is not listed anymore but stays locked
+        let utxos = BitcoinUtxos::from(vec![utxo(2, 40_000), utxo(3, 30_000)]);
+        btc.release_locks(now);
+        assert!(btc.is_locked(&utxo(1, 0).outpoint, now));
+
+        // it confirms, which unlocks its coin
+        btc.pendings.push(BtcPending {
+            txid: "ab".repeat(32),
+            account: btc.address.clone(),
+            transaction: Some(BtcSentTransaction {
+                inputs: vec![first_input],
+                outputs: vec![],
+                change: 0,
+                fee: 0,
+                vsize: 0,
+            }),
+        });
+        btc.confirm_pending(0);
+        assert!(btc.pendings.is_empty());
+        assert!(btc.locked_outpoints.is_empty());
+
+        btc.lock_outpoints(vec![utxo(2, 0).outpoint], now);
+        assert_eq!(btc.spendable_utxos(utxos.clone(), now).len(), 1);
+
+        // locks expire when the spend never confirms
+        let later = now + OUTPOINT_LOCK_TTL;
+        assert!(!btc.is_locked(&utxo(2, 0).outpoint, later));
+        assert_eq!(btc.spendable_utxos(utxos.clone(), later).len(), 2);
+
+        btc.release_locks(later);
+        assert!(btc.locked_outpoints.is_empty());
+    }
 }
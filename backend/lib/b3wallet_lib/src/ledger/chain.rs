@@ -3,17 +3,27 @@ use super::{
     ckbtc::ckbtc::CkbtcChain,
     ecdsa::ChainAddress,
     error::LedgerError,
-    evm::api::EvmChain,
+    evm::{address::EvmAddress, api::EvmChain},
     icp::icp::IcpChain,
     icrc::icrc::IcrcChain,
-    types::{Balance, ChainId, PendingEnum, SendResult},
+    types::{Balance, CachedBalance, ChainEnum, ChainId, PendingEnum, SendResult},
+    watch::watch::WatchOnlyChain,
 };
+use crate::store::with_setting;
 use async_trait::async_trait;
 use b3_utils::{ledger::currency::TokenAmount, types::CanisterId, Environment, Subaccount};
 use enum_dispatch::enum_dispatch;
 use libsecp256k1::PublicKey;
 use serde::{Deserialize, Serialize};
 
+/// The symbol and decimals of the native token of the EVM chain, from the network registry.
+/// A chain whose network was removed since keeps showing ETH with 18 decimals.
+fn evm_native_token(chain_id: ChainId) -> (String, u8) {
+    with_setting(|setting| setting.evm_network(chain_id))
+        .map(|network| (network.symbol, network.decimals))
+        .unwrap_or_else(|_| ("ETH".to_string(), 18))
+}
+
 #[async_trait]
 #[enum_dispatch]
 pub trait ChainTrait {
@@ -24,7 +34,14 @@ pub trait ChainTrait {
     async fn check_pending(&self, pending_index: usize) -> Result<(), LedgerError>;
     fn add_pending(&mut self, pending: PendingEnum);
     fn remove_pending(&mut self, pending_index: usize);
+    /// Removes the pending once [ChainTrait::check_pending] has seen it through.
+    fn confirm_pending(&mut self, pending_index: usize) {
+        self.remove_pending(pending_index)
+    }
     fn clear_pending(&mut self);
+    fn cached_balance(&self) -> Option<CachedBalance>;
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance);
+    fn clear_cached_balance(&mut self);
 }
 
 #[enum_dispatch(ChainTrait)]
@@ -35,6 +52,7 @@ pub enum Chain {
     BtcChain,
     EvmChain,
     IcpChain,
+    WatchOnlyChain,
 }
 
 impl Default for Chain {
@@ -77,6 +95,8 @@ impl Chain {
 
         let chain = Chain::BtcChain(BtcChain {
             min_confirmations: None,
+            cached_balance: None,
+            locked_outpoints: Vec::new(),
             pendings: Vec::new(),
             ecdsa_public_key,
             btc_network,
@@ -87,21 +107,80 @@ impl Chain {
         Ok(chain)
     }
 
-    pub fn new_evm_chain(chain_id: ChainId, address: String) -> Self {
-        Chain::EvmChain(EvmChain {
+    pub fn new_evm_chain(
+        chain_id: ChainId,
+        subaccount: Subaccount,
+        ecdsa_public_key: PublicKey,
+    ) -> Result<Self, LedgerError> {
+        with_setting(|setting| setting.evm_network(chain_id)).map_err(LedgerError::EvmError)?;
+
+        let address = EvmAddress::from_public_key(&ecdsa_public_key);
+
+        let chain = Chain::EvmChain(EvmChain {
+            cached_balance: None,
+            nonce: 0,
+            nfts: Vec::new(),
+            nft_approvals: Vec::new(),
+            pendings: Vec::new(),
+            ecdsa_public_key: Some(ecdsa_public_key),
+            subaccount: Some(subaccount),
             chain_id,
             address,
-            pendings: Vec::new(),
-        })
+        });
+
+        Ok(chain)
     }
 
     pub fn new_icp_chain(subaccount: Subaccount) -> Self {
         Chain::IcpChain(IcpChain::new(subaccount))
     }
 
+    pub fn new_watch_only_chain(chain: ChainEnum, address: String) -> Result<Self, LedgerError> {
+        let watch = WatchOnlyChain::new(chain, address)?;
+
+        Ok(Chain::WatchOnlyChain(watch))
+    }
+
+    /// Returns the symbol of the token held on this chain.
+    /// ICRC chains read it from the ledger metadata fetched on creation.
+    pub fn symbol(&self) -> String {
+        match self {
+            Chain::CkbtcChain(_) => "ckBTC".to_string(),
+            Chain::IcrcChain(icrc) => icrc
+                .symbol()
+                .unwrap_or_else(|| icrc.canister_id.to_string()),
+            Chain::BtcChain(_) => "BTC".to_string(),
+            Chain::EvmChain(evm) => evm_native_token(evm.chain_id).0,
+            Chain::IcpChain(_) => "ICP".to_string(),
+            Chain::WatchOnlyChain(watch) => match watch.chain {
+                ChainEnum::CKBTC(_) => "ckBTC".to_string(),
+                ChainEnum::ICRC(canister_id) => canister_id.to_string(),
+                ChainEnum::BTC(_) => "BTC".to_string(),
+                ChainEnum::EVM(chain_id) => evm_native_token(chain_id).0,
+                ChainEnum::ICP => "ICP".to_string(),
+            },
+        }
+    }
+
+    /// Returns the number of decimals of the token held on this chain.
+    pub fn decimals(&self) -> u8 {
+        match self {
+            Chain::CkbtcChain(_) => 8,
+            Chain::IcrcChain(icrc) => icrc.decimals().unwrap_or(8),
+            Chain::BtcChain(_) => 8,
+            Chain::EvmChain(evm) => evm_native_token(evm.chain_id).1,
+            Chain::IcpChain(_) => 8,
+            Chain::WatchOnlyChain(watch) => match watch.chain {
+                ChainEnum::EVM(chain_id) => evm_native_token(chain_id).1,
+                _ => 8,
+            },
+        }
+    }
+
     pub fn icrc(&self) -> Result<IcrcChain, LedgerError> {
         match self {
             Chain::IcrcChain(icrc) => Ok(icrc.clone()),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -109,6 +188,7 @@ impl Chain {
     pub fn icrc_mut(&mut self) -> Result<&mut IcrcChain, LedgerError> {
         match self {
             Chain::IcrcChain(icrc) => Ok(icrc),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -116,6 +196,7 @@ impl Chain {
     pub fn ckbtc(&self) -> Result<CkbtcChain, LedgerError> {
         match self {
             Chain::CkbtcChain(ckbtc) => Ok(ckbtc.clone()),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -123,6 +204,7 @@ impl Chain {
     pub fn ckbtc_mut(&mut self) -> Result<&mut CkbtcChain, LedgerError> {
         match self {
             Chain::CkbtcChain(ckbtc) => Ok(ckbtc),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -130,6 +212,7 @@ impl Chain {
     pub fn btc(&self) -> Result<BtcChain, LedgerError> {
         match self {
             Chain::BtcChain(btc) => Ok(btc.clone()),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -137,6 +220,7 @@ impl Chain {
     pub fn btc_mut(&mut self) -> Result<&mut BtcChain, LedgerError> {
         match self {
             Chain::BtcChain(btc) => Ok(btc),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -144,6 +228,7 @@ impl Chain {
     pub fn evm(&self) -> Result<EvmChain, LedgerError> {
         match self {
             Chain::EvmChain(evm) => Ok(evm.clone()),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -151,6 +236,7 @@ impl Chain {
     pub fn evm_mut(&mut self) -> Result<&mut EvmChain, LedgerError> {
         match self {
             Chain::EvmChain(evm) => Ok(evm),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -158,6 +244,7 @@ impl Chain {
     pub fn icp(&self) -> Result<IcpChain, LedgerError> {
         match self {
             Chain::IcpChain(icp) => Ok(icp.clone()),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
@@ -165,6 +252,7 @@ impl Chain {
     pub fn icp_mut(&mut self) -> Result<&mut IcpChain, LedgerError> {
         match self {
             Chain::IcpChain(icp) => Ok(icp),
+            Chain::WatchOnlyChain(_) => Err(LedgerError::WatchOnlyAccount),
             _ => Err(LedgerError::InvalidChain),
         }
     }
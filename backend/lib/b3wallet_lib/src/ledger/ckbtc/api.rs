@@ -1,6 +1,6 @@
 use super::{ckbtc::CkbtcChain, error::CkbtcError, types::RetrieveBtcStatus};
 use crate::{
-    ledger::types::{Balance, SendResult},
+    ledger::types::{Balance, CachedBalance, SendResult},
     ledger::{
         chain::ChainTrait,
         error::LedgerError,
@@ -96,4 +96,16 @@ impl ChainTrait for CkbtcChain {
     fn clear_pending(&mut self) {
         self.pendings.clear();
     }
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
 }
@@ -2,7 +2,7 @@ use super::error::CkbtcError;
 use super::minter::Minter;
 use super::types::{RetrieveBtcOk, RetrieveBtcResult, UpdateBalanceResult};
 use crate::ledger::btc::types::Satoshi;
-use crate::ledger::types::CkbtcPending;
+use crate::ledger::types::{CachedBalance, CkbtcPending};
 use crate::ledger::{
     btc::network::BitcoinNetwork,
     icrc::{
@@ -32,6 +32,8 @@ pub struct CkbtcChain {
     pub memo: Option<ICRCMemo>,
     pub pendings: Vec<CkbtcPending>,
     pub created_at_time: Option<ICRCTimestamp>,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
 }
 
 impl CkbtcChain {
@@ -60,6 +62,7 @@ impl CkbtcChain {
             fee: Some(fee),
             created_at_time: None,
             pendings: Vec::new(),
+            cached_balance: None,
         })
     }
 
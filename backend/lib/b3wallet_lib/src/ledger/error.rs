@@ -31,6 +31,8 @@ pub enum LedgerError {
     InvalidEcdsaPublicKey,
     EcdsaPublicKeyAlreadySet,
     InvalidMessageLength,
+    WatchOnlyAccount,
+    InvalidWatchAddress(String),
 }
 
 #[rustfmt::skip]
@@ -38,6 +40,8 @@ impl fmt::Display for LedgerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             LedgerError::InvalidMessageLength => write!(f, "Invalid message length"),
+            LedgerError::WatchOnlyAccount => write!(f, "Watch-only account cannot sign"),
+            LedgerError::InvalidWatchAddress(ref msg) => write!(f, "Invalid watch address: {}", msg),
             LedgerError::MissingAddress => write!(f, "Missing address"),
             LedgerError::MissingEcdsaPublicKey => write!(f, "Missing ecdsa public key"),
             LedgerError::InvalidEcdsaPublicKey => write!(f, "Invalid ecdsa public key"),
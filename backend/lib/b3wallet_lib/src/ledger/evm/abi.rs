@@ -0,0 +1,588 @@
+use super::{address::EvmAddress, error::EvmError, u256::U256};
+use serde_json::{json, Value};
+use std::fmt;
+use tiny_keccak::{Hasher, Keccak};
+
+/// A value to encode following the Solidity contract ABI.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AbiToken {
+    Address(EvmAddress),
+    Uint(U256),
+    /// A signed integer, as its two's complement word.
+    Int(U256),
+    Bool(bool),
+    /// `bytes1` to `bytes32`, padded on the right.
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    /// `T[]`, the length is encoded before the items.
+    Array(Vec<AbiToken>),
+    /// `T[k]`
+    FixedArray(Vec<AbiToken>),
+    Tuple(Vec<AbiToken>),
+}
+
+impl AbiToken {
+    /// Dynamic values are encoded in the tail and referenced by their offset in the head.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            AbiToken::Bytes(_) | AbiToken::String(_) | AbiToken::Array(_) => true,
+            AbiToken::FixedArray(tokens) | AbiToken::Tuple(tokens) => {
+                tokens.iter().any(|token| token.is_dynamic())
+            }
+            _ => false,
+        }
+    }
+
+    /// The size of the value in the head.
+    fn head_size(&self) -> usize {
+        match self {
+            AbiToken::FixedArray(tokens) | AbiToken::Tuple(tokens) if !self.is_dynamic() => {
+                tokens.iter().map(|token| token.head_size()).sum()
+            }
+            _ => 32,
+        }
+    }
+
+    /// The value as JSON, numbers are decimal strings and bytes are hex strings.
+    pub fn to_json(&self) -> Value {
+        match self {
+            AbiToken::Address(address) => json!(address.to_string()),
+            AbiToken::Bool(value) => json!(value),
+            AbiToken::String(string) => json!(string),
+            AbiToken::FixedBytes(bytes) | AbiToken::Bytes(bytes) => {
+                json!(format!("0x{}", hex::encode(bytes)))
+            }
+            AbiToken::Array(tokens) | AbiToken::FixedArray(tokens) | AbiToken::Tuple(tokens) => {
+                Value::Array(tokens.iter().map(|token| token.to_json()).collect())
+            }
+            AbiToken::Uint(_) | AbiToken::Int(_) => json!(self.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AbiToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |tokens: &Vec<AbiToken>| {
+            tokens
+                .iter()
+                .map(|token| token.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self {
+            AbiToken::Address(address) => write!(f, "{}", address),
+            AbiToken::Uint(value) => write!(f, "{}", value),
+            AbiToken::Int(value) => {
+                if value.to_be_bytes()[0] & 0x80 != 0 {
+                    write!(f, "-{}", value.wrapping_neg())
+                } else {
+                    write!(f, "{}", value)
+                }
+            }
+            AbiToken::Bool(value) => write!(f, "{}", value),
+            AbiToken::FixedBytes(bytes) | AbiToken::Bytes(bytes) => {
+                write!(f, "0x{}", hex::encode(bytes))
+            }
+            AbiToken::String(string) => write!(f, "{:?}", string),
+            AbiToken::Array(tokens) | AbiToken::FixedArray(tokens) => {
+                write!(f, "[{}]", join(tokens))
+            }
+            AbiToken::Tuple(tokens) => write!(f, "({})", join(tokens)),
+        }
+    }
+}
+
+/// The largest `k` of a `T[k]` type, well above what contracts declare.
+pub const MAX_FIXED_ARRAY_SIZE: usize = 1024;
+
+/// The type of a value in the contract ABI, e.g. `uint256` or `(address,bytes)[]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamType {
+    Address,
+    Uint(usize),
+    Int(usize),
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    /// The canonical name used in function signatures.
+    pub fn signature(&self) -> String {
+        match self {
+            ParamType::Address => "address".to_string(),
+            ParamType::Uint(bits) => format!("uint{}", bits),
+            ParamType::Int(bits) => format!("int{}", bits),
+            ParamType::Bool => "bool".to_string(),
+            ParamType::FixedBytes(size) => format!("bytes{}", size),
+            ParamType::Bytes => "bytes".to_string(),
+            ParamType::String => "string".to_string(),
+            ParamType::Array(kind) => format!("{}[]", kind.signature()),
+            ParamType::FixedArray(kind, size) => format!("{}[{}]", kind.signature(), size),
+            ParamType::Tuple(kinds) => format!(
+                "({})",
+                kinds
+                    .iter()
+                    .map(|kind| kind.signature())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(kind, _) => kind.is_dynamic(),
+            ParamType::Tuple(kinds) => kinds.iter().any(|kind| kind.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    fn head_size(&self) -> usize {
+        match self {
+            ParamType::FixedArray(kind, size) if !self.is_dynamic() => {
+                kind.head_size().saturating_mul(*size)
+            }
+            ParamType::Tuple(kinds) if !self.is_dynamic() => {
+                kinds.iter().map(|kind| kind.head_size()).sum()
+            }
+            _ => 32,
+        }
+    }
+}
+
+/// The first 4 bytes of the keccak hash of the signature, e.g. `transfer(address,uint256)`.
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let mut keccak = Keccak::v256();
+    keccak.update(signature.as_bytes());
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&output[..4]);
+
+    selector
+}
+
+/// Encode the arguments of a call, without selector.
+pub fn encode(tokens: &[AbiToken]) -> Result<Vec<u8>, EvmError> {
+    let head_size: usize = tokens.iter().map(|token| token.head_size()).sum();
+
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+
+    for token in tokens {
+        if token.is_dynamic() {
+            let offset = U256::from((head_size + tail.len()) as u64);
+
+            head.extend_from_slice(&offset.to_be_bytes());
+            tail.extend(encode_token(token)?);
+        } else {
+            head.extend(encode_token(token)?);
+        }
+    }
+
+    head.extend(tail);
+
+    Ok(head)
+}
+
+/// Encode a call of the function: its selector followed by the arguments.
+pub fn encode_function_call(signature: &str, tokens: &[AbiToken]) -> Result<Vec<u8>, EvmError> {
+    let mut data = function_selector(signature).to_vec();
+
+    data.extend(encode(tokens)?);
+
+    Ok(data)
+}
+
+fn encode_token(token: &AbiToken) -> Result<Vec<u8>, EvmError> {
+    match token {
+        AbiToken::Address(address) => {
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(address.as_bytes());
+
+            Ok(word.to_vec())
+        }
+        AbiToken::Uint(value) | AbiToken::Int(value) => Ok(value.to_be_bytes().to_vec()),
+        AbiToken::Bool(value) => Ok(U256::from(*value as u64).to_be_bytes().to_vec()),
+        AbiToken::FixedBytes(bytes) => {
+            if bytes.is_empty() || bytes.len() > 32 {
+                return Err(EvmError::InvalidAbiValue(format!(
+                    "bytes{} is not a fixed bytes type",
+                    bytes.len()
+                )));
+            }
+
+            Ok(pad_right(bytes))
+        }
+        AbiToken::Bytes(bytes) => Ok(encode_bytes(bytes)),
+        AbiToken::String(string) => Ok(encode_bytes(string.as_bytes())),
+        AbiToken::Array(tokens) => {
+            let mut result = U256::from(tokens.len() as u64).to_be_bytes().to_vec();
+
+            result.extend(encode(tokens)?);
+
+            Ok(result)
+        }
+        AbiToken::FixedArray(tokens) | AbiToken::Tuple(tokens) => encode(tokens),
+    }
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut result = U256::from(bytes.len() as u64).to_be_bytes().to_vec();
+
+    if !bytes.is_empty() {
+        result.extend(pad_right(bytes));
+    }
+
+    result
+}
+
+fn pad_right(bytes: &[u8]) -> Vec<u8> {
+    let mut result = bytes.to_vec();
+
+    let padding = (32 - bytes.len() % 32) % 32;
+    result.resize(bytes.len() + padding, 0);
+
+    result
+}
+
+/// Decode ABI encoded values, e.g. the return data of an `eth_call`.
+pub fn decode(kinds: &[ParamType], data: &[u8]) -> Result<Vec<AbiToken>, EvmError> {
+    let mut tokens = Vec::with_capacity(kinds.len());
+    let mut offset = 0;
+
+    for kind in kinds {
+        if kind.is_dynamic() {
+            let tail = read_usize(data, offset)?;
+
+            tokens.push(decode_token(kind, data, tail)?);
+        } else {
+            tokens.push(decode_token(kind, data, offset)?);
+        }
+
+        offset += kind.head_size();
+    }
+
+    Ok(tokens)
+}
+
+fn decode_token(kind: &ParamType, data: &[u8], at: usize) -> Result<AbiToken, EvmError> {
+    match kind {
+        ParamType::Address => {
+            let word = read_word(data, at)?;
+
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&word[12..]);
+
+            Ok(AbiToken::Address(EvmAddress::from_bytes(address)))
+        }
+        ParamType::Uint(_) => Ok(AbiToken::Uint(U256::from_be_bytes(read_word(data, at)?))),
+        ParamType::Int(_) => Ok(AbiToken::Int(U256::from_be_bytes(read_word(data, at)?))),
+        ParamType::Bool => {
+            let value = U256::from_be_bytes(read_word(data, at)?);
+
+            match value.to_u64() {
+                Some(0) => Ok(AbiToken::Bool(false)),
+                Some(1) => Ok(AbiToken::Bool(true)),
+                _ => Err(EvmError::InvalidAbiValue(format!(
+                    "{} is not a bool",
+                    value
+                ))),
+            }
+        }
+        ParamType::FixedBytes(size) => {
+            let word = read_word(data, at)?;
+
+            Ok(AbiToken::FixedBytes(word[..*size].to_vec()))
+        }
+        ParamType::Bytes => Ok(AbiToken::Bytes(read_bytes(data, at)?)),
+        ParamType::String => {
+            let bytes = read_bytes(data, at)?;
+
+            String::from_utf8(bytes)
+                .map(AbiToken::String)
+                .map_err(|err| EvmError::InvalidAbiValue(err.to_string()))
+        }
+        ParamType::Array(kind) => {
+            let size = read_usize(data, at)?;
+            let items = data.get(at + 32..).unwrap_or_default();
+
+            // every item takes at least a word, a bigger size can only be garbage
+            if size > items.len() / 32 {
+                return Err(EvmError::InvalidAbiValue(format!(
+                    "array of {} items in {} bytes",
+                    size,
+                    items.len()
+                )));
+            }
+
+            let kinds = vec![*kind.clone(); size];
+
+            Ok(AbiToken::Array(decode(&kinds, items)?))
+        }
+        ParamType::FixedArray(kind, size) => {
+            let items = &data[at.min(data.len())..];
+
+            // every item takes at least a word
+            if size.saturating_mul(32) > items.len() {
+                return Err(EvmError::InvalidAbiValue(format!(
+                    "array of {} items in {} bytes",
+                    size,
+                    items.len()
+                )));
+            }
+
+            let kinds = vec![*kind.clone(); *size];
+
+            Ok(AbiToken::FixedArray(decode(&kinds, items)?))
+        }
+        ParamType::Tuple(kinds) => Ok(AbiToken::Tuple(decode(kinds, &data[at.min(data.len())..])?)),
+    }
+}
+
+fn read_word(data: &[u8], at: usize) -> Result<[u8; 32], EvmError> {
+    let bytes = data
+        .get(at..at.saturating_add(32))
+        .ok_or(EvmError::InvalidAbiValue(format!(
+            "no word at {} in {} bytes",
+            at,
+            data.len()
+        )))?;
+
+    let mut word = [0u8; 32];
+    word.copy_from_slice(bytes);
+
+    Ok(word)
+}
+
+fn read_usize(data: &[u8], at: usize) -> Result<usize, EvmError> {
+    let value = U256::from_be_bytes(read_word(data, at)?);
+
+    value
+        .to_u64()
+        .filter(|&value| value <= data.len() as u64)
+        .map(|value| value as usize)
+        .ok_or(EvmError::InvalidAbiValue(format!(
+            "{} is out of the {} bytes",
+            value,
+            data.len()
+        )))
+}
+
+fn read_bytes(data: &[u8], at: usize) -> Result<Vec<u8>, EvmError> {
+    let size = read_usize(data, at)?;
+
+    data.get(at + 32..at + 32 + size)
+        .map(|bytes| bytes.to_vec())
+        .ok_or(EvmError::InvalidAbiValue(format!(
+            "{} bytes at {} in {} bytes",
+            size,
+            at + 32,
+            data.len()
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> String {
+        words.concat()
+    }
+
+    #[test]
+    fn test_encode_static_call() {
+        // baz(uint32,bool) from the Solidity ABI specification
+        let data = encode_function_call(
+            "baz(uint32,bool)",
+            &[AbiToken::Uint(U256::from(69u64)), AbiToken::Bool(true)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            words(&[
+                "cdcd77c0",
+                "0000000000000000000000000000000000000000000000000000000000000045",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_encode_dynamic_call() {
+        // sam(bytes,bool,uint256[]) from the Solidity ABI specification
+        let data = encode_function_call(
+            "sam(bytes,bool,uint256[])",
+            &[
+                AbiToken::Bytes(b"dave".to_vec()),
+                AbiToken::Bool(true),
+                AbiToken::Array(vec![
+                    AbiToken::Uint(U256::from(1u64)),
+                    AbiToken::Uint(U256::from(2u64)),
+                    AbiToken::Uint(U256::from(3u64)),
+                ]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            words(&[
+                "a5643bf2",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "6461766500000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+            ])
+        );
+
+        // f(uint256,uint32[],bytes10,bytes) from the Solidity ABI specification
+        let data = encode_function_call(
+            "f(uint256,uint32[],bytes10,bytes)",
+            &[
+                AbiToken::Uint(U256::from(0x123u64)),
+                AbiToken::Array(vec![
+                    AbiToken::Uint(U256::from(0x456u64)),
+                    AbiToken::Uint(U256::from(0x789u64)),
+                ]),
+                AbiToken::FixedBytes(b"1234567890".to_vec()),
+                AbiToken::Bytes(b"Hello, world!".to_vec()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            words(&[
+                "8be65246",
+                "0000000000000000000000000000000000000000000000000000000000000123",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "3132333435363738393000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000456",
+                "0000000000000000000000000000000000000000000000000000000000000789",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_encode_erc20_calls() {
+        let to = EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap();
+
+        // 1000 tokens of 18 decimals, far above u64::MAX
+        let amount = U256::parse("1000000000000000000000").unwrap();
+
+        let data = encode_function_call(
+            "transfer(address,uint256)",
+            &[AbiToken::Address(to), AbiToken::Uint(amount)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            words(&[
+                "a9059cbb",
+                "0000000000000000000000007a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6",
+                "00000000000000000000000000000000000000000000003635c9adc5dea00000",
+            ])
+        );
+
+        // the usual unlimited approval
+        let data = encode_function_call(
+            "approve(address,uint256)",
+            &[AbiToken::Address(to), AbiToken::Uint(U256::MAX)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            words(&[
+                "095ea7b3",
+                "0000000000000000000000007a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let kinds = vec![
+            ParamType::Uint(256),
+            ParamType::Array(Box::new(ParamType::Uint(32))),
+            ParamType::FixedBytes(10),
+            ParamType::Bytes,
+        ];
+
+        let tokens = vec![
+            AbiToken::Uint(U256::from(0x123u64)),
+            AbiToken::Array(vec![
+                AbiToken::Uint(U256::from(0x456u64)),
+                AbiToken::Uint(U256::from(0x789u64)),
+            ]),
+            AbiToken::FixedBytes(b"1234567890".to_vec()),
+            AbiToken::Bytes(b"Hello, world!".to_vec()),
+        ];
+
+        let data = encode(&tokens).unwrap();
+
+        assert_eq!(decode(&kinds, &data).unwrap(), tokens);
+
+        // a tuple with a dynamic member and a negative int
+        let kinds = vec![
+            ParamType::Tuple(vec![ParamType::Address, ParamType::String]),
+            ParamType::Int(256),
+            ParamType::Bool,
+        ];
+
+        let tokens = vec![
+            AbiToken::Tuple(vec![
+                AbiToken::Address(
+                    EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap(),
+                ),
+                AbiToken::String("dave".to_string()),
+            ]),
+            AbiToken::Int(U256::from(5u64).wrapping_neg()),
+            AbiToken::Bool(true),
+        ];
+
+        let data = encode(&tokens).unwrap();
+        let decoded = decode(&kinds, &data).unwrap();
+
+        assert_eq!(decoded, tokens);
+        assert_eq!(decoded[1].to_string(), "-5");
+        assert_eq!(
+            decoded[0].to_string(),
+            "(0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6, \"dave\")"
+        );
+
+        // truncated data is an error, not a panic
+        assert!(decode(&kinds, &data[..data.len() - 32]).is_err());
+        assert!(decode(&[ParamType::Bytes], &[0xff; 32]).is_err());
+
+        // a fixed array longer than the data is rejected before its items are allocated
+        let kind = ParamType::FixedArray(Box::new(ParamType::Uint(256)), 3);
+        assert!(decode(&[kind.clone()], &[0; 64]).is_err());
+        assert_eq!(
+            decode(&[kind], &[0; 96]).unwrap(),
+            vec![AbiToken::FixedArray(vec![AbiToken::Uint(U256::ZERO); 3])]
+        );
+    }
+}
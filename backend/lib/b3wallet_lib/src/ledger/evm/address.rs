@@ -0,0 +1,218 @@
+use super::{error::EvmError, utils::keccak256};
+use candid::{
+    types::{Serializer, Type},
+    CandidType,
+};
+use libsecp256k1::PublicKey;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A 20 byte EVM account or contract address.
+/// It is parsed from `0x` followed by 40 hex digits, mixed case digits must carry a valid
+/// EIP-55 checksum, and it always displays checksummed. It travels as a candid `text`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EvmAddress([u8; 20]);
+
+impl EvmAddress {
+    pub const ZERO: EvmAddress = EvmAddress([0; 20]);
+
+    pub const fn from_bytes(bytes: [u8; 20]) -> Self {
+        EvmAddress(bytes)
+    }
+
+    /// The address of the account, the last 20 bytes of the keccak hash of the public key.
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        let hash = keccak256(&public_key.serialize()[1..]);
+
+        let mut bytes = [0u8; 20];
+        bytes.copy_from_slice(&hash[12..]);
+
+        EvmAddress(bytes)
+    }
+
+    /// All lowercase or all uppercase digits carry no checksum and are accepted as they are.
+    pub fn parse(address: &str) -> Result<Self, EvmError> {
+        let invalid = || EvmError::InvalidAddress(address.to_string());
+
+        let digits = address.strip_prefix("0x").ok_or_else(invalid)?;
+
+        if digits.len() != 40 {
+            return Err(invalid());
+        }
+
+        let decoded = hex::decode(digits).map_err(|_| invalid())?;
+
+        let mut bytes = [0u8; 20];
+        bytes.copy_from_slice(&decoded);
+
+        let parsed = EvmAddress(bytes);
+
+        let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+
+        if has_lower && has_upper && parsed.to_checksum()[2..] != *digits {
+            return Err(EvmError::InvalidChecksum(address.to_string()));
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == EvmAddress::ZERO
+    }
+
+    /// The EIP-55 form: a hex letter is uppercase when the matching nibble
+    /// of the keccak hash of the lowercase address is 8 or more.
+    pub fn to_checksum(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = keccak256(lower.as_bytes());
+
+        let digits: String = lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+
+        format!("0x{}", digits)
+    }
+}
+
+impl From<[u8; 20]> for EvmAddress {
+    fn from(bytes: [u8; 20]) -> Self {
+        EvmAddress(bytes)
+    }
+}
+
+impl FromStr for EvmAddress {
+    type Err = EvmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EvmAddress::parse(s)
+    }
+}
+
+impl fmt::Display for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_checksum())
+    }
+}
+
+impl fmt::Debug for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl CandidType for EvmAddress {
+    fn _ty() -> Type {
+        String::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_checksum().idl_serialize(serializer)
+    }
+}
+
+impl Serialize for EvmAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_checksum().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EvmAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let address = String::deserialize(deserializer)?;
+
+        EvmAddress::parse(&address).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evm_address_checksum() {
+        // the test vectors of EIP-55
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address = EvmAddress::parse(checksummed).unwrap();
+
+            assert_eq!(address.to_string(), checksummed);
+
+            // without a checksum the address is taken as it is
+            let lower = EvmAddress::parse(&checksummed.to_lowercase()).unwrap();
+            let upper = EvmAddress::parse(&format!("0x{}", checksummed[2..].to_uppercase()));
+
+            assert_eq!(lower, address);
+            assert_eq!(upper, Ok(address));
+        }
+    }
+
+    #[test]
+    fn test_evm_address_invalid() {
+        // one letter of 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed in the wrong case
+        assert_eq!(
+            EvmAddress::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(EvmError::InvalidChecksum(
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".to_string()
+            ))
+        );
+
+        for invalid in [
+            "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beae",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaedff",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beazz",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaé",
+        ] {
+            assert_eq!(
+                EvmAddress::parse(invalid),
+                Err(EvmError::InvalidAddress(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_evm_address_candid() {
+        let address = EvmAddress::parse("0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359").unwrap();
+
+        let bytes = candid::encode_one(address).unwrap();
+
+        // on the wire it is checksummed text
+        assert_eq!(
+            bytes,
+            candid::encode_one("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359").unwrap()
+        );
+        assert_eq!(candid::decode_one::<EvmAddress>(&bytes).unwrap(), address);
+
+        let bad_checksum =
+            candid::encode_one("0xFb6916095ca1df60bB79Ce92cE3Ea74c37c5d359").unwrap();
+        assert!(candid::decode_one::<EvmAddress>(&bad_checksum).is_err());
+    }
+}
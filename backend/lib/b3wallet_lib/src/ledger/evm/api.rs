@@ -1,40 +1,411 @@
-use crate::ledger::{
-    chain::ChainTrait,
-    error::LedgerError,
-    types::{Balance, ChainId, EvmPending, PendingEnum, SendResult},
+use crate::{
+    ledger::{
+        chain::ChainTrait,
+        error::LedgerError,
+        subaccount::SubaccountEcdsaTrait,
+        types::{Balance, CachedBalance, ChainId, EvmPending, PendingEnum, SendResult},
+    },
+    store::with_setting,
 };
 
-use b3_utils::ledger::currency::TokenAmount;
-use candid::CandidType;
+use super::{
+    address::EvmAddress,
+    contract::ContractAbi,
+    create2::EvmCreate2,
+    error::EvmError,
+    evm::EvmSignTrait,
+    gas::{EvmFeeSuggestion, EvmFees},
+    london::EvmTransaction1559,
+    network::EvmNetwork,
+    nft::{decode_nft_balance, nft_balance_data, EvmNft, EvmNftApproval, EvmNftStandard},
+    rpc::{EvmRpc, EvmTransactionStatus, RpcService},
+    types::EvmNonceStatus,
+    u256::U256,
+};
+
+use b3_utils::{ledger::currency::TokenAmount, Subaccount};
+use libsecp256k1::PublicKey;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use async_trait::async_trait;
 
-#[derive(CandidType, Clone, Deserialize, Serialize, PartialEq, Debug)]
+/// The gas used by a plain ether transfer.
+pub const EVM_TRANSFER_GAS_LIMIT: u64 = 21_000;
+
+/// The number of blocks the priority fee suggestion is taken from.
+pub const EVM_FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Contract calls can use more gas than estimated when the state changes before
+/// they are mined, so 20% is added to their estimate.
+fn with_gas_margin(gas: u64, data: &str) -> u64 {
+    if data.trim_start_matches("0x").is_empty() {
+        gas
+    } else {
+        gas.saturating_add(gas / 5)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
 pub struct EvmChain {
     pub chain_id: ChainId,
-    pub address: String,
+    pub address: EvmAddress,
+    /// The signing key of the account. Chains stored before it was kept here have none,
+    /// it is filled in from their ledger when the state is loaded, see
+    /// [crate::ledger::ledger::Ledger::migrate].
+    #[serde(default)]
+    pub subaccount: Option<Subaccount>,
+    #[serde(default)]
+    pub ecdsa_public_key: Option<PublicKey>,
     pub pendings: Vec<EvmPending>,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
+    /// The next nonce, as far as the wallet knows.
+    #[serde(default)]
+    pub nonce: u64,
+    #[serde(default)]
+    pub nfts: Vec<EvmNft>,
+    #[serde(default)]
+    pub nft_approvals: Vec<EvmNftApproval>,
+}
+
+impl EvmChain {
+    /// The registry entry of the chain, None if the network was removed since the chain was added.
+    pub fn network(&self) -> Option<EvmNetwork> {
+        with_setting(|setting| setting.evm_network(self.chain_id)).ok()
+    }
+
+    /// The JSON-RPC client of the chain, served by the EVM RPC canister
+    /// through the provider of the network registry.
+    pub fn rpc(&self) -> EvmRpc {
+        let service = self
+            .network()
+            .and_then(|network| network.rpc_service)
+            .unwrap_or(RpcService::Chain(self.chain_id));
+
+        EvmRpc::new(service)
+    }
+
+    /// The wallet only signs EIP-1559 transactions.
+    pub fn check_eip1559(&self) -> Result<(), EvmError> {
+        match self.network() {
+            Some(network) if !network.eip1559 => Err(EvmError::Eip1559NotSupported(self.chain_id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Compare the local nonce with the transaction count of the node.
+    pub async fn nonce_status(&self) -> Result<EvmNonceStatus, LedgerError> {
+        let rpc = self.rpc();
+        let address = self.address.to_string();
+
+        let latest = rpc
+            .get_transaction_count(&address, "latest")
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        let pending = rpc
+            .get_transaction_count(&address, "pending")
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        Ok(EvmNonceStatus::new(self.nonce, latest, pending))
+    }
+
+    /// The nonce for the next transaction, reconciled with the node.
+    /// Transactions sent from this address outside of the wallet move the nonce forward.
+    pub async fn next_nonce(&self) -> Result<u64, LedgerError> {
+        let pending = self
+            .rpc()
+            .get_transaction_count(&self.address.to_string(), "pending")
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        Ok(self.nonce.max(pending))
+    }
+
+    /// Mark the nonce as used, so the next transaction does not take it again.
+    pub fn use_nonce(&mut self, nonce: u64) {
+        self.nonce = self.nonce.max(nonce + 1);
+    }
+
+    /// Take the next nonce, given the pending transaction count of the node.
+    pub fn reserve_nonce(&mut self, pending: u64) -> u64 {
+        let nonce = self.nonce.max(pending);
+
+        self.use_nonce(nonce);
+
+        nonce
+    }
+
+    /// Give back a reserved nonce that ended up unused.
+    /// Only the last reserved nonce can be given back, otherwise it would leave a gap.
+    pub fn release_nonce(&mut self, nonce: u64) {
+        if self.nonce == nonce + 1 {
+            self.nonce = nonce;
+        }
+    }
+
+    /// Forget the locally used nonces and continue from the given one.
+    pub fn reset_nonce(&mut self, nonce: u64) {
+        self.nonce = nonce;
+    }
+
+    /// Ask the chain for the fees of a transaction.
+    /// The gas is only estimated when no gas limit is given, failing calls are left out
+    /// of the suggestion so the defaults of the chain can take over.
+    pub async fn suggest_fees(
+        &self,
+        to: &str,
+        value: U256,
+        data: &str,
+        gas_limit: Option<u64>,
+    ) -> EvmFeeSuggestion {
+        let rpc = self.rpc();
+
+        let gas_limit = match gas_limit {
+            Some(gas_limit) => Some(gas_limit),
+            None => rpc
+                .estimate_gas(&self.address.to_string(), to, value, data)
+                .await
+                .ok()
+                .map(|gas| with_gas_margin(gas, data)),
+        };
+
+        let fee_history = rpc.fee_history(EVM_FEE_HISTORY_BLOCKS).await.ok();
+
+        EvmFeeSuggestion {
+            gas_limit,
+            base_fee_per_gas: fee_history.as_ref().map(|history| history.base_fee_per_gas),
+            max_priority_fee_per_gas: fee_history.map(|history| history.max_priority_fee_per_gas),
+        }
+    }
+
+    /// Build an unsigned EIP-1559 ether transfer.
+    pub fn transfer_transaction(
+        &self,
+        to: String,
+        value: U256,
+        nonce: u64,
+        fees: EvmFees,
+    ) -> EvmTransaction1559 {
+        EvmTransaction1559 {
+            chain_id: self.chain_id,
+            nonce,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            gas_limit: fees.gas_limit,
+            to,
+            value,
+            data: "0x".to_string(),
+            access_list: vec![],
+            v: "0x00".to_string(),
+            r: "0x00".to_string(),
+            s: "0x00".to_string(),
+        }
+    }
+
+    /// Sign the transaction with the threshold ECDSA key of the account.
+    /// Returns the serialized signed transaction.
+    pub async fn sign_transaction(
+        &self,
+        transaction: &mut EvmTransaction1559,
+    ) -> Result<Vec<u8>, LedgerError> {
+        let message = transaction.unsigned_hash();
+
+        let (subaccount, public_key) = match (&self.subaccount, self.ecdsa_public_key) {
+            (Some(subaccount), Some(public_key)) => (subaccount, public_key),
+            _ => return Err(LedgerError::MissingEcdsaPublicKey),
+        };
+
+        let signature = subaccount.sign_with_ecdsa(message).await?;
+
+        transaction
+            .sign(signature, public_key)
+            .map_err(LedgerError::EvmError)
+    }
+
+    /// Call a view function of the contract and return its outputs as a JSON array.
+    /// `args` is the JSON array of arguments, see `AbiFunction::encode_input_json`.
+    pub async fn read_contract(
+        &self,
+        contract_address: &EvmAddress,
+        abi: &str,
+        function: &str,
+        args: &str,
+    ) -> Result<String, LedgerError> {
+        let abi = ContractAbi::from_json(abi).map_err(LedgerError::EvmError)?;
+
+        let function = abi.function(function).map_err(LedgerError::EvmError)?;
+
+        let calldata = function
+            .encode_input_json(args)
+            .map_err(LedgerError::EvmError)?;
+
+        let output = self
+            .rpc()
+            .eth_call(&contract_address.to_string(), &calldata)
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        let tokens = function
+            .decode_output(&output)
+            .map_err(LedgerError::EvmError)?;
+
+        let outputs = tokens.iter().map(|token| token.to_json()).collect();
+
+        Ok(Value::Array(outputs).to_string())
+    }
+
+    /// Run the factory call of a CREATE2 deployment against the latest block
+    /// and check the contract lands on the predicted address.
+    pub async fn simulate_create2(&self, create2: &EvmCreate2) -> Result<EvmAddress, LedgerError> {
+        let output = self
+            .rpc()
+            .eth_call(&create2.factory.to_string(), &create2.calldata())
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        create2.check_output(&output).map_err(LedgerError::EvmError)
+    }
+
+    /// How many of the NFT the account holds on chain.
+    pub async fn nft_balance(
+        &self,
+        contract_address: &EvmAddress,
+        token_id: U256,
+        standard: EvmNftStandard,
+    ) -> Result<U256, LedgerError> {
+        let calldata =
+            nft_balance_data(standard, &self.address, token_id).map_err(LedgerError::EvmError)?;
+
+        let output = self
+            .rpc()
+            .eth_call(&contract_address.to_string(), &calldata)
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        decode_nft_balance(standard, &self.address, &output).map_err(LedgerError::EvmError)
+    }
+
+    /// Add the NFT to the holdings, replacing what was known of it.
+    pub fn add_nft(&mut self, nft: EvmNft) {
+        self.remove_nft(&nft.contract_address, nft.token_id);
+
+        if !nft.amount.is_zero() {
+            self.nfts.push(nft);
+        }
+    }
+
+    pub fn remove_nft(&mut self, contract_address: &EvmAddress, token_id: U256) -> Option<EvmNft> {
+        let index = self
+            .nfts
+            .iter()
+            .position(|nft| nft.is(contract_address, token_id))?;
+
+        Some(self.nfts.remove(index))
+    }
+
+    /// Take a sent amount off the holdings, the NFT leaves them when none is left.
+    /// A sent ERC-721 token also loses its single token approvals, as the contract clears them.
+    pub fn spend_nft(&mut self, contract_address: &EvmAddress, token_id: U256, amount: U256) {
+        if let Some(mut nft) = self.remove_nft(contract_address, token_id) {
+            if nft.standard == EvmNftStandard::ERC1155 {
+                nft.amount = nft.amount.checked_sub(&amount).unwrap_or(U256::ZERO);
+
+                self.add_nft(nft);
+            }
+        }
+
+        self.nft_approvals.retain(|approval| {
+            !(approval.contract_address == *contract_address && approval.token_id == Some(token_id))
+        });
+    }
+
+    /// Keep the approval until it is revoked.
+    /// An ERC-721 token has a single approved operator, so a new one replaces the previous.
+    pub fn add_nft_approval(&mut self, approval: EvmNftApproval) {
+        let replaced = |existing: &EvmNftApproval| match approval.token_id {
+            Some(_) => {
+                existing.contract_address == approval.contract_address
+                    && existing.token_id == approval.token_id
+            }
+            None => existing.is(&approval.contract_address, &approval.operator, None),
+        };
+
+        self.nft_approvals.retain(|existing| !replaced(existing));
+
+        self.nft_approvals.push(approval);
+    }
+
+    pub fn remove_nft_approval(
+        &mut self,
+        contract_address: &EvmAddress,
+        operator: &EvmAddress,
+        token_id: Option<U256>,
+    ) -> Option<EvmNftApproval> {
+        let index = self
+            .nft_approvals
+            .iter()
+            .position(|approval| approval.is(contract_address, operator, token_id))?;
+
+        Some(self.nft_approvals.remove(index))
+    }
+
+    /// Broadcast a signed transaction and return its hash.
+    pub async fn broadcast(&self, signed_tx: &Vec<u8>) -> Result<String, LedgerError> {
+        self.rpc()
+            .send_raw_transaction(signed_tx)
+            .await
+            .map_err(LedgerError::EvmError)
+    }
 }
 
 #[async_trait]
 impl ChainTrait for EvmChain {
     fn address(&self) -> String {
-        let address = self.address.clone();
-
-        address
+        self.address.to_string()
     }
 
     async fn balance(&self) -> Result<Balance, LedgerError> {
-        Ok(Balance::from(0u8))
+        self.rpc()
+            .get_balance(&self.address.to_string())
+            .await
+            .map_err(LedgerError::EvmError)
     }
 
+    /// A copy of the chain can not reserve the nonce it uses,
+    /// EVM tokens are sent with [crate::evm_transfer::evm_transfer] instead.
     async fn send(&self, _to: String, _amount: TokenAmount) -> Result<SendResult, LedgerError> {
-        todo!("implement the async method for EVM...")
+        Err(LedgerError::EvmError(EvmError::UntrackedTransfer))
     }
 
-    async fn check_pending(&self, _pending_index: usize) -> Result<(), LedgerError> {
-        todo!("implement the async method for EVM...")
+    async fn check_pending(&self, pending_index: usize) -> Result<(), LedgerError> {
+        let EvmPending { tx_hash } = self
+            .pendings
+            .get(pending_index)
+            .ok_or(LedgerError::PendingIndexError(pending_index))?;
+
+        if tx_hash.is_empty() {
+            return Err(LedgerError::EvmError(EvmError::MissingTransactionHash(
+                pending_index,
+            )));
+        }
+
+        let status = self
+            .rpc()
+            .transaction_status(tx_hash)
+            .await
+            .map_err(LedgerError::EvmError)?;
+
+        let error = match status {
+            EvmTransactionStatus::Success(_) => return Ok(()),
+            EvmTransactionStatus::Reverted(_) => EvmError::TransactionReverted(tx_hash.clone()),
+            EvmTransactionStatus::Pending => EvmError::TransactionPending(tx_hash.clone()),
+            EvmTransactionStatus::Dropped => EvmError::TransactionDropped(tx_hash.clone()),
+        };
+
+        Err(LedgerError::EvmError(error))
     }
 
     fn pendings(&self) -> Vec<PendingEnum> {
@@ -57,4 +428,484 @@ impl ChainTrait for EvmChain {
     fn clear_pending(&mut self) {
         self.pendings.clear();
     }
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::evm::{
+        abi::{encode, AbiToken},
+        create2::DETERMINISTIC_DEPLOYMENT_PROXY,
+        gas::EvmGasConfig,
+        mock::{reset_mock_node, with_mock_node},
+        types::PublicKeyTrait,
+        utils::vec_u8_to_string,
+    };
+    use crate::store::with_setting_mut;
+    use b3_utils::Environment;
+    use futures::executor::block_on;
+    use libsecp256k1::{sign, Message, SecretKey};
+
+    fn evm_chain(secret_key: &SecretKey) -> EvmChain {
+        let ecdsa_public_key = PublicKey::from_secret_key(secret_key);
+
+        EvmChain {
+            chain_id: 1,
+            address: ecdsa_public_key.to_address(),
+            subaccount: Some(Subaccount::new(Environment::Production, 0)),
+            ecdsa_public_key: Some(ecdsa_public_key),
+            pendings: vec![],
+            cached_balance: None,
+            nonce: 0,
+            nfts: vec![],
+            nft_approvals: vec![],
+        }
+    }
+
+    #[test]
+    fn test_evm_balance() {
+        reset_mock_node();
+
+        let chain = evm_chain(&SecretKey::parse(&[1; 32]).unwrap());
+
+        with_mock_node(|node| {
+            node.balances.insert(
+                chain.address.to_string().to_lowercase(),
+                1_500_000_000_000_000_000,
+            )
+        });
+
+        let balance = block_on(chain.balance()).unwrap();
+
+        assert_eq!(balance, Balance::from(1_500_000_000_000_000_000u128));
+    }
+
+    #[test]
+    fn test_evm_sign_and_broadcast() {
+        reset_mock_node();
+
+        let secret_key = SecretKey::parse(&[1; 32]).unwrap();
+        let chain = evm_chain(&secret_key);
+
+        with_mock_node(|node| {
+            node.nonces
+                .insert(chain.address.to_string().to_lowercase(), 7);
+        });
+
+        let rpc = chain.rpc();
+
+        let nonce =
+            block_on(rpc.get_transaction_count(&chain.address.to_string(), "pending")).unwrap();
+
+        assert_eq!(nonce, 7);
+
+        let mut transaction = chain.transfer_transaction(
+            "0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6".to_string(),
+            U256::from(1_000u64),
+            nonce,
+            EvmFees {
+                gas_limit: EVM_TRANSFER_GAS_LIMIT,
+                max_fee_per_gas: 30_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+            },
+        );
+
+        // the canister signs through the management canister, here the key is local
+        let message = Message::parse_slice(&transaction.unsigned_hash()).unwrap();
+        let (signature, _) = sign(&message, &secret_key);
+
+        let signed = transaction
+            .sign(
+                signature.serialize().to_vec(),
+                chain.ecdsa_public_key.unwrap(),
+            )
+            .unwrap();
+
+        let tx_hash = block_on(rpc.send_raw_transaction(&signed)).unwrap();
+
+        assert_eq!(tx_hash, format!("0x{}", transaction.tx_id()));
+
+        let broadcast = with_mock_node(|node| node.raw_transactions.clone());
+
+        assert_eq!(broadcast, vec![format!("0x{}", vec_u8_to_string(&signed))]);
+    }
+
+    #[test]
+    fn test_evm_check_pending() {
+        reset_mock_node();
+
+        let secret_key = SecretKey::parse(&[2; 32]).unwrap();
+        let mut chain = evm_chain(&secret_key);
+
+        let mut transaction = chain.transfer_transaction(
+            "0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6".to_string(),
+            U256::from(1_000u64),
+            0,
+            EvmFees {
+                gas_limit: EVM_TRANSFER_GAS_LIMIT,
+                max_fee_per_gas: 30_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+            },
+        );
+
+        let message = Message::parse_slice(&transaction.unsigned_hash()).unwrap();
+        let (signature, _) = sign(&message, &secret_key);
+
+        let signed = transaction
+            .sign(
+                signature.serialize().to_vec(),
+                chain.ecdsa_public_key.unwrap(),
+            )
+            .unwrap();
+
+        let tx_hash = block_on(chain.broadcast(&signed)).unwrap();
+
+        chain.add_pending(PendingEnum::new_evm(tx_hash.clone()));
+        chain.add_pending(PendingEnum::new_evm("0xdead".to_string()));
+
+        assert_eq!(
+            block_on(chain.check_pending(0)),
+            Err(LedgerError::EvmError(EvmError::TransactionPending(
+                tx_hash.clone()
+            )))
+        );
+
+        assert_eq!(
+            block_on(chain.check_pending(1)),
+            Err(LedgerError::EvmError(EvmError::TransactionDropped(
+                "0xdead".to_string()
+            )))
+        );
+
+        with_mock_node(|node| node.receipts.insert(tx_hash.clone(), (100, false)));
+
+        assert_eq!(
+            block_on(chain.check_pending(0)),
+            Err(LedgerError::EvmError(EvmError::TransactionReverted(
+                tx_hash.clone()
+            )))
+        );
+
+        with_mock_node(|node| node.receipts.insert(tx_hash.clone(), (100, true)));
+
+        assert_eq!(block_on(chain.check_pending(0)), Ok(()));
+
+        assert_eq!(
+            block_on(chain.check_pending(2)),
+            Err(LedgerError::PendingIndexError(2))
+        );
+    }
+
+    #[test]
+    fn test_evm_nonce_management() {
+        reset_mock_node();
+
+        let mut chain = evm_chain(&SecretKey::parse(&[3; 32]).unwrap());
+
+        // transactions sent from this address outside of the wallet
+        with_mock_node(|node| {
+            node.nonces
+                .insert(chain.address.to_string().to_lowercase(), 4)
+        });
+
+        assert_eq!(block_on(chain.next_nonce()).unwrap(), 4);
+
+        assert_eq!(chain.reserve_nonce(4), 4);
+        assert_eq!(chain.reserve_nonce(4), 5);
+
+        // the second transaction failed to sign
+        chain.release_nonce(5);
+        // only the last reserved nonce can be released
+        chain.release_nonce(3);
+
+        assert_eq!(chain.nonce, 5);
+
+        chain.use_nonce(5);
+
+        assert_eq!(block_on(chain.next_nonce()).unwrap(), 6);
+
+        // the node has seen the transaction with nonce 4 but not 5
+        with_mock_node(|node| {
+            node.nonces
+                .insert(chain.address.to_string().to_lowercase(), 5)
+        });
+
+        let status = block_on(chain.nonce_status()).unwrap();
+
+        assert_eq!(status.gaps, vec![5]);
+        assert_eq!(status.next_nonce(), 6);
+
+        chain.reset_nonce(status.pending);
+
+        let status = block_on(chain.nonce_status()).unwrap();
+
+        assert!(!status.has_gaps());
+        assert_eq!(status.next_nonce(), 5);
+    }
+
+    #[test]
+    fn test_evm_suggest_fees() {
+        reset_mock_node();
+
+        let chain = evm_chain(&SecretKey::parse(&[4; 32]).unwrap());
+
+        with_mock_node(|node| {
+            node.estimated_gas = Some(50_000);
+            node.base_fee_per_gas = 20_000_000_000;
+            node.max_priority_fee_per_gas = 1_000_000_000;
+        });
+
+        let to = "0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6";
+
+        // a plain transfer is estimated as is
+        let suggestion = block_on(chain.suggest_fees(to, U256::from(1_000u64), "0x", None));
+
+        assert_eq!(
+            suggestion,
+            EvmFeeSuggestion {
+                gas_limit: Some(50_000),
+                base_fee_per_gas: Some(20_000_000_000),
+                max_priority_fee_per_gas: Some(1_000_000_000),
+            }
+        );
+
+        // a contract call gets a margin on top of the estimate
+        let suggestion = block_on(chain.suggest_fees(to, U256::ZERO, "0xa9059cbb", None));
+
+        assert_eq!(suggestion.gas_limit, Some(60_000));
+
+        // a given gas limit is not estimated
+        let suggestion = block_on(chain.suggest_fees(to, U256::ZERO, "0xa9059cbb", Some(80_000)));
+
+        assert_eq!(suggestion.gas_limit, Some(80_000));
+
+        // a failing estimation falls back to the defaults of the chain
+        with_mock_node(|node| node.estimated_gas = None);
+
+        let suggestion = block_on(chain.suggest_fees(to, U256::ZERO, "0xa9059cbb", None));
+
+        assert_eq!(suggestion.gas_limit, None);
+
+        let config = EvmGasConfig {
+            gas_limit: Some(100_000),
+            max_fee_per_gas_cap: Some(40_000_000_000),
+            ..Default::default()
+        };
+
+        // 2 * 20 gwei + 1 gwei is above the cap
+        assert_eq!(
+            config.resolve(None, None, None, &suggestion),
+            Err(EvmError::MaxFeePerGasAboveCap(
+                41_000_000_000,
+                40_000_000_000
+            ))
+        );
+
+        let fees = config
+            .resolve(None, Some(40_000_000_000), None, &suggestion)
+            .unwrap();
+
+        assert_eq!(fees.gas_limit, 100_000);
+        assert_eq!(fees.max_fee(), 4_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_evm_read_contract() {
+        reset_mock_node();
+
+        let chain = evm_chain(&SecretKey::parse(&[5; 32]).unwrap());
+
+        let token = EvmAddress::parse("0xdac17f958d2ee523a2206206994597c13d831ec7").unwrap();
+
+        with_mock_node(|node| {
+            node.call_results.insert(
+                token.to_string().to_lowercase(),
+                U256::parse("1000000000000000000000")
+                    .unwrap()
+                    .to_be_bytes()
+                    .to_vec(),
+            )
+        });
+
+        let abi = r#"[{
+            "type": "function",
+            "name": "balanceOf",
+            "stateMutability": "view",
+            "inputs": [{ "name": "account", "type": "address" }],
+            "outputs": [{ "name": "", "type": "uint256" }]
+        }]"#;
+
+        let args = format!("[\"{}\"]", chain.address);
+
+        let balance = block_on(chain.read_contract(&token, abi, "balanceOf", &args)).unwrap();
+
+        assert_eq!(balance, r#"["1000000000000000000000"]"#);
+
+        // an unknown contract reverts
+        assert!(block_on(chain.read_contract(
+            &EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap(),
+            abi,
+            "balanceOf",
+            &args
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_evm_simulate_create2() {
+        reset_mock_node();
+
+        let chain = evm_chain(&SecretKey::parse(&[6; 32]).unwrap());
+
+        let factory = DETERMINISTIC_DEPLOYMENT_PROXY;
+        let create2 = EvmCreate2::new(factory, &[7; 32], vec![0x60, 0x80, 0x60, 0x40]).unwrap();
+
+        // the factory is not deployed on this chain
+        assert!(block_on(chain.simulate_create2(&create2)).is_err());
+
+        with_mock_node(|node| {
+            node.call_results.insert(
+                factory.to_string().to_lowercase(),
+                create2.address().as_bytes().to_vec(),
+            )
+        });
+
+        assert_eq!(
+            block_on(chain.simulate_create2(&create2)),
+            Ok(create2.address())
+        );
+
+        // another salt deploys somewhere else
+        let other = EvmCreate2::new(factory, &[8; 32], create2.init_code.clone()).unwrap();
+
+        assert_eq!(
+            block_on(chain.simulate_create2(&other)),
+            Err(LedgerError::EvmError(EvmError::Create2AddressMismatch(
+                other.address().to_string(),
+                create2.address().to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_evm_network_registry() {
+        let chain = evm_chain(&SecretKey::parse(&[9; 32]).unwrap());
+
+        assert_eq!(chain.network().unwrap().name, "Ethereum");
+        assert_eq!(chain.rpc().service, RpcService::Chain(1));
+        assert_eq!(chain.check_eip1559(), Ok(()));
+
+        let mut network = chain.network().unwrap();
+        network.rpc_service = Some(RpcService::Provider(3));
+        network.eip1559 = false;
+
+        with_setting_mut(|setting| setting.set_evm_network(1, Some(network)));
+
+        assert_eq!(chain.rpc().service, RpcService::Provider(3));
+        assert_eq!(chain.check_eip1559(), Err(EvmError::Eip1559NotSupported(1)));
+
+        // the chain outlives its network, with the defaults
+        with_setting_mut(|setting| setting.set_evm_network(1, None));
+
+        assert_eq!(chain.network(), None);
+        assert_eq!(chain.rpc().service, RpcService::Chain(1));
+        assert_eq!(chain.check_eip1559(), Ok(()));
+    }
+
+    #[test]
+    fn test_evm_nft_registry() {
+        reset_mock_node();
+
+        let mut chain = evm_chain(&SecretKey::parse(&[3; 32]).unwrap());
+
+        let collection = EvmAddress::parse("0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D").unwrap();
+        let operator = EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap();
+        let token_id = U256::from(42u64);
+
+        // ownerOf answers with the address of the account
+        with_mock_node(|node| {
+            node.call_results.insert(
+                collection.to_string().to_lowercase(),
+                encode(&[AbiToken::Address(chain.address)]).unwrap(),
+            )
+        });
+
+        let balance =
+            block_on(chain.nft_balance(&collection, token_id, EvmNftStandard::ERC721)).unwrap();
+        assert_eq!(balance, U256::from(1u64));
+
+        chain.add_nft(EvmNft::new(
+            collection,
+            token_id,
+            EvmNftStandard::ERC721,
+            balance,
+        ));
+        chain.add_nft_approval(EvmNftApproval::new(
+            collection,
+            operator,
+            Some(token_id),
+            "0x01".to_string(),
+        ));
+        chain.add_nft_approval(EvmNftApproval::new(
+            collection,
+            operator,
+            None,
+            "0x02".to_string(),
+        ));
+
+        // a token has one approved operator, the new approval replaces the old one
+        chain.add_nft_approval(EvmNftApproval::new(
+            collection,
+            EvmAddress::parse("0xbb9bc244d798123fde783fcc1c72d3bb8c189413").unwrap(),
+            Some(token_id),
+            "0x03".to_string(),
+        ));
+
+        assert_eq!(chain.nfts.len(), 1);
+        assert_eq!(
+            chain
+                .nft_approvals
+                .iter()
+                .map(|approval| approval.tx_hash.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["0x02", "0x03"]
+        );
+
+        // sending the token clears it and its single token approval, not the collection one
+        chain.spend_nft(&collection, token_id, U256::from(1u64));
+
+        assert!(chain.nfts.is_empty());
+        assert_eq!(chain.nft_approvals.len(), 1);
+
+        let revoked = chain.remove_nft_approval(&collection, &operator, None);
+        assert_eq!(
+            revoked.map(|approval| approval.tx_hash),
+            Some("0x02".to_string())
+        );
+        assert!(chain.nft_approvals.is_empty());
+
+        // ERC-1155 holdings go down by the amount sent
+        chain.add_nft(EvmNft::new(
+            collection,
+            token_id,
+            EvmNftStandard::ERC1155,
+            U256::from(5u64),
+        ));
+        chain.spend_nft(&collection, token_id, U256::from(2u64));
+        assert_eq!(chain.nfts[0].amount, U256::from(3u64));
+        chain.spend_nft(&collection, token_id, U256::from(3u64));
+        assert!(chain.nfts.is_empty());
+    }
 }
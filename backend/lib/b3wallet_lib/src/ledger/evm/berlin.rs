@@ -1,5 +1,7 @@
 use super::error::EvmError;
 use super::evm::{decode_access_list, encode_access_list, get_recovery_id, EvmSignTrait};
+use super::summary::EvmTransactionSummary;
+use super::u256::U256;
 use super::utils::{
     remove_leading, string_to_vec_u8, u64_to_vec_u8, vec_u8_to_string, vec_u8_to_u64,
 };
@@ -14,7 +16,7 @@ pub struct EvmTransaction2930 {
     pub gas_price: u64,
     pub gas_limit: u64,
     pub to: String,
-    pub value: u64,
+    pub value: U256,
     pub data: String,
     pub access_list: Vec<(String, Vec<String>)>,
     pub v: String,
@@ -22,8 +24,10 @@ pub struct EvmTransaction2930 {
     pub s: String,
 }
 
-impl From<Vec<u8>> for EvmTransaction2930 {
-    fn from(data: Vec<u8>) -> Self {
+impl TryFrom<Vec<u8>> for EvmTransaction2930 {
+    type Error = EvmError;
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
         let rlp = rlp::Rlp::new(&data[1..]);
 
         let chain_id_hex = rlp.at(0).as_val::<Vec<u8>>();
@@ -42,7 +46,7 @@ impl From<Vec<u8>> for EvmTransaction2930 {
         let to = vec_u8_to_string(&to_hex);
 
         let value_hex = rlp.at(5).as_val::<Vec<u8>>();
-        let value = vec_u8_to_u64(&value_hex);
+        let value = U256::from_be_slice(&value_hex)?;
 
         let data_tx_hex = rlp.at(6).as_val::<Vec<u8>>();
         let data_tx = vec_u8_to_string(&data_tx_hex);
@@ -57,7 +61,7 @@ impl From<Vec<u8>> for EvmTransaction2930 {
 
         let s_hex = rlp.at(10).as_val::<Vec<u8>>();
         let s = vec_u8_to_string(&s_hex);
-        EvmTransaction2930 {
+        Ok(EvmTransaction2930 {
             chain_id,
             nonce,
             gas_price,
@@ -69,7 +73,7 @@ impl From<Vec<u8>> for EvmTransaction2930 {
             v,
             r,
             s,
-        }
+        })
     }
 }
 impl EvmSignTrait for EvmTransaction2930 {
@@ -81,7 +85,7 @@ impl EvmSignTrait for EvmTransaction2930 {
 
         let s = vec_u8_to_string(&s_remove_leading_zeros);
 
-        let message = self.unsigned_serialized();
+        let message = self.unsigned_hash();
 
         let recovery_id = get_recovery_id(&message, &signature, &public_key)?;
 
@@ -152,7 +156,7 @@ impl EvmSignTrait for EvmTransaction2930 {
             u64_to_vec_u8(&self.gas_price),
             u64_to_vec_u8(&self.gas_limit),
             string_to_vec_u8(&self.to),
-            u64_to_vec_u8(&self.value),
+            self.value.to_be_bytes_trimmed(),
             string_to_vec_u8(&self.data),
         ];
 
@@ -188,7 +192,7 @@ impl EvmSignTrait for EvmTransaction2930 {
         let to = string_to_vec_u8(&self.to[..]);
         stream.append(&to);
 
-        let value = u64_to_vec_u8(&self.value);
+        let value = self.value.to_be_bytes_trimmed();
         stream.append(&value);
 
         let data = string_to_vec_u8(&self.data[..]);
@@ -249,6 +253,18 @@ impl EvmSignTrait for EvmTransaction2930 {
 
         tx_id
     }
+
+    fn summary(&self) -> EvmTransactionSummary {
+        EvmTransactionSummary::new(
+            self.chain_id,
+            self.nonce,
+            &self.to,
+            self.value,
+            &self.data,
+            self.gas_limit,
+            self.gas_price,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -263,7 +279,7 @@ mod tests {
             nonce: 0,
             gas_limit: 0,
             to: "0x".to_string(),
-            value: 0,
+            value: U256::ZERO,
             data: "0x".to_string(),
             access_list: vec![],
             v: "".to_string(),
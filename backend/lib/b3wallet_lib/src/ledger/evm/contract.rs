@@ -0,0 +1,538 @@
+use super::{
+    abi::{decode, encode, function_selector, AbiToken, ParamType, MAX_FIXED_ARRAY_SIZE},
+    address::EvmAddress,
+    error::EvmError,
+    u256::U256,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize, Clone, Debug)]
+struct JsonParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    components: Vec<JsonParam>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct JsonEntry {
+    #[serde(rename = "type", default = "default_entry_type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<JsonParam>,
+    #[serde(default)]
+    outputs: Vec<JsonParam>,
+    #[serde(default, rename = "stateMutability")]
+    state_mutability: String,
+}
+
+fn default_entry_type() -> String {
+    "function".to_string()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiParam {
+    pub name: String,
+    pub kind: ParamType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub outputs: Vec<AbiParam>,
+    pub state_mutability: String,
+}
+
+/// The functions of a contract, read from its JSON ABI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractAbi {
+    pub functions: Vec<AbiFunction>,
+}
+
+impl ContractAbi {
+    /// Accepts the ABI array, or a compiler artifact with an `abi` field.
+    pub fn from_json(json: &str) -> Result<Self, EvmError> {
+        let mut value: Value =
+            serde_json::from_str(json).map_err(|err| EvmError::InvalidAbi(err.to_string()))?;
+
+        if let Some(abi) = value.get_mut("abi") {
+            value = abi.take();
+        }
+
+        let entries: Vec<JsonEntry> =
+            serde_json::from_value(value).map_err(|err| EvmError::InvalidAbi(err.to_string()))?;
+
+        let functions = entries
+            .into_iter()
+            .filter(|entry| entry.kind == "function")
+            .map(|entry| {
+                Ok(AbiFunction {
+                    name: entry.name,
+                    inputs: parse_params(&entry.inputs)?,
+                    outputs: parse_params(&entry.outputs)?,
+                    state_mutability: entry.state_mutability,
+                })
+            })
+            .collect::<Result<Vec<AbiFunction>, EvmError>>()?;
+
+        Ok(ContractAbi { functions })
+    }
+
+    /// Find the function by name, or by its full signature when it is overloaded.
+    pub fn function(&self, name: &str) -> Result<&AbiFunction, EvmError> {
+        if name.contains('(') {
+            return self
+                .functions
+                .iter()
+                .find(|function| function.signature() == name)
+                .ok_or(EvmError::FunctionNotFound(name.to_string()));
+        }
+
+        let mut functions = self
+            .functions
+            .iter()
+            .filter(|function| function.name == name);
+
+        match (functions.next(), functions.next()) {
+            (Some(function), None) => Ok(function),
+            (Some(_), Some(_)) => Err(EvmError::FunctionNotFound(format!(
+                "{} is overloaded, use its signature",
+                name
+            ))),
+            (None, _) => Err(EvmError::FunctionNotFound(name.to_string())),
+        }
+    }
+}
+
+impl AbiFunction {
+    /// The canonical signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        let kinds = self
+            .inputs
+            .iter()
+            .map(|input| input.kind.signature())
+            .collect::<Vec<String>>();
+
+        format!("{}({})", self.name, kinds.join(","))
+    }
+
+    /// View and pure functions can be called with `eth_call`, without a transaction.
+    pub fn is_read_only(&self) -> bool {
+        self.state_mutability == "view" || self.state_mutability == "pure"
+    }
+
+    /// Build the calldata from the arguments given as a JSON array.
+    /// Integers can be numbers or strings, decimal or `0x` hex, bytes are `0x` hex strings
+    /// and tuples are arrays of their components.
+    pub fn encode_input_json(&self, args: &str) -> Result<Vec<u8>, EvmError> {
+        let tokens = self.tokenize_json(args)?;
+
+        self.encode_input(&tokens)
+    }
+
+    pub fn tokenize_json(&self, args: &str) -> Result<Vec<AbiToken>, EvmError> {
+        let args: Value =
+            serde_json::from_str(args).map_err(|err| EvmError::InvalidAbiValue(err.to_string()))?;
+
+        let args = args.as_array().ok_or(EvmError::InvalidAbiValue(
+            "the arguments are not an array".to_string(),
+        ))?;
+
+        if args.len() != self.inputs.len() {
+            return Err(EvmError::InvalidAbiValue(format!(
+                "{} expects {} arguments, got {}",
+                self.signature(),
+                self.inputs.len(),
+                args.len()
+            )));
+        }
+
+        self.inputs
+            .iter()
+            .zip(args)
+            .map(|(input, arg)| tokenize(&input.kind, arg))
+            .collect()
+    }
+
+    pub fn encode_input(&self, tokens: &[AbiToken]) -> Result<Vec<u8>, EvmError> {
+        let mut data = function_selector(&self.signature()).to_vec();
+
+        data.extend(encode(tokens)?);
+
+        Ok(data)
+    }
+
+    /// Decode the arguments of a call to this function.
+    pub fn decode_input(&self, calldata: &[u8]) -> Result<Vec<AbiToken>, EvmError> {
+        if calldata.len() < 4 || calldata[..4] != function_selector(&self.signature()) {
+            return Err(EvmError::InvalidAbiValue(format!(
+                "the calldata is not a call of {}",
+                self.signature()
+            )));
+        }
+
+        decode(&kinds(&self.inputs), &calldata[4..])
+    }
+
+    /// Decode the return data of the function.
+    pub fn decode_output(&self, data: &[u8]) -> Result<Vec<AbiToken>, EvmError> {
+        decode(&kinds(&self.outputs), data)
+    }
+
+    /// Describe the call for a human, e.g. `transfer(address to = 0x7a9d…, uint256 amount = 1000)`.
+    pub fn describe(&self, tokens: &[AbiToken]) -> String {
+        let args = self
+            .inputs
+            .iter()
+            .zip(tokens)
+            .map(|(input, token)| {
+                if input.name.is_empty() {
+                    format!("{} {}", input.kind.signature(), token)
+                } else {
+                    format!("{} {} = {}", input.kind.signature(), input.name, token)
+                }
+            })
+            .collect::<Vec<String>>();
+
+        format!("{}({})", self.name, args.join(", "))
+    }
+}
+
+fn kinds(params: &[AbiParam]) -> Vec<ParamType> {
+    params.iter().map(|param| param.kind.clone()).collect()
+}
+
+fn parse_params(params: &[JsonParam]) -> Result<Vec<AbiParam>, EvmError> {
+    params
+        .iter()
+        .map(|param| {
+            Ok(AbiParam {
+                name: param.name.clone(),
+                kind: parse_type(&param.kind, &param.components)?,
+            })
+        })
+        .collect()
+}
+
+/// Parse a non tuple type name, e.g. `uint256` or `address[]`.
+pub fn parse_param_type(kind: &str) -> Result<ParamType, EvmError> {
+    parse_type(kind, &[])
+}
+
+fn parse_type(kind: &str, components: &[JsonParam]) -> Result<ParamType, EvmError> {
+    let invalid = || EvmError::InvalidAbi(format!("unsupported type {}", kind));
+
+    if let Some(inner) = kind.strip_suffix(']') {
+        let open = inner.rfind('[').ok_or_else(invalid)?;
+        let item = Box::new(parse_type(&inner[..open], components)?);
+
+        return match &inner[open + 1..] {
+            "" => Ok(ParamType::Array(item)),
+            size => {
+                let size = size.parse::<usize>().map_err(|_| invalid())?;
+
+                if size == 0 || size > MAX_FIXED_ARRAY_SIZE {
+                    return Err(invalid());
+                }
+
+                Ok(ParamType::FixedArray(item, size))
+            }
+        };
+    }
+
+    let bits = |prefix: &str| -> Result<usize, EvmError> {
+        let bits = match &kind[prefix.len()..] {
+            "" => 256,
+            bits => bits.parse::<usize>().map_err(|_| invalid())?,
+        };
+
+        if bits == 0 || bits > 256 || bits % 8 != 0 {
+            return Err(invalid());
+        }
+
+        Ok(bits)
+    };
+
+    match kind {
+        "address" => Ok(ParamType::Address),
+        "bool" => Ok(ParamType::Bool),
+        "string" => Ok(ParamType::String),
+        "bytes" => Ok(ParamType::Bytes),
+        "tuple" => Ok(ParamType::Tuple(
+            parse_params(components)?
+                .into_iter()
+                .map(|param| param.kind)
+                .collect(),
+        )),
+        _ if kind.starts_with("uint") => Ok(ParamType::Uint(bits("uint")?)),
+        _ if kind.starts_with("int") => Ok(ParamType::Int(bits("int")?)),
+        _ if kind.starts_with("bytes") => {
+            let size = kind[5..].parse::<usize>().map_err(|_| invalid())?;
+
+            if size == 0 || size > 32 {
+                return Err(invalid());
+            }
+
+            Ok(ParamType::FixedBytes(size))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Turn a JSON argument into the token of its ABI type, checking it fits.
+pub fn tokenize(kind: &ParamType, value: &Value) -> Result<AbiToken, EvmError> {
+    let invalid =
+        || EvmError::InvalidAbiValue(format!("{} is not a valid {}", value, kind.signature()));
+
+    match kind {
+        ParamType::Address => {
+            let address = value.as_str().ok_or_else(invalid)?;
+
+            Ok(AbiToken::Address(EvmAddress::parse(address)?))
+        }
+        ParamType::Uint(bits) => {
+            let number = json_integer(value).ok_or_else(invalid)?;
+
+            let value = U256::parse(&number).map_err(|_| invalid())?;
+
+            if value.bits() > *bits {
+                return Err(invalid());
+            }
+
+            Ok(AbiToken::Uint(value))
+        }
+        ParamType::Int(bits) => {
+            let number = json_integer(value).ok_or_else(invalid)?;
+
+            let (negative, digits) = match number.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, number.as_str()),
+            };
+
+            let magnitude = U256::parse(digits).map_err(|_| invalid())?;
+
+            // the lowest value of intN is -2^(N-1), a lone bit at N-1
+            let fits = magnitude.bits() < *bits
+                || (negative
+                    && magnitude.bits() == *bits
+                    && magnitude
+                        .to_be_bytes()
+                        .iter()
+                        .map(|b| b.count_ones())
+                        .sum::<u32>()
+                        == 1);
+
+            if !fits {
+                return Err(invalid());
+            }
+
+            if negative {
+                Ok(AbiToken::Int(magnitude.wrapping_neg()))
+            } else {
+                Ok(AbiToken::Int(magnitude))
+            }
+        }
+        ParamType::Bool => value.as_bool().map(AbiToken::Bool).ok_or_else(invalid),
+        ParamType::FixedBytes(size) => {
+            let bytes = json_bytes(value).ok_or_else(invalid)?;
+
+            if bytes.len() != *size {
+                return Err(invalid());
+            }
+
+            Ok(AbiToken::FixedBytes(bytes))
+        }
+        ParamType::Bytes => json_bytes(value).map(AbiToken::Bytes).ok_or_else(invalid),
+        ParamType::String => value
+            .as_str()
+            .map(|string| AbiToken::String(string.to_string()))
+            .ok_or_else(invalid),
+        ParamType::Array(item) => {
+            let items = value.as_array().ok_or_else(invalid)?;
+
+            let tokens = items
+                .iter()
+                .map(|value| tokenize(item, value))
+                .collect::<Result<Vec<AbiToken>, EvmError>>()?;
+
+            Ok(AbiToken::Array(tokens))
+        }
+        ParamType::FixedArray(item, size) => {
+            let items = value.as_array().ok_or_else(invalid)?;
+
+            if items.len() != *size {
+                return Err(invalid());
+            }
+
+            let tokens = items
+                .iter()
+                .map(|value| tokenize(item, value))
+                .collect::<Result<Vec<AbiToken>, EvmError>>()?;
+
+            Ok(AbiToken::FixedArray(tokens))
+        }
+        ParamType::Tuple(kinds) => {
+            let items = value.as_array().ok_or_else(invalid)?;
+
+            if items.len() != kinds.len() {
+                return Err(invalid());
+            }
+
+            let tokens = kinds
+                .iter()
+                .zip(items)
+                .map(|(kind, value)| tokenize(kind, value))
+                .collect::<Result<Vec<AbiToken>, EvmError>>()?;
+
+            Ok(AbiToken::Tuple(tokens))
+        }
+    }
+}
+
+/// Integers come as JSON numbers or, when too big for JSON, as strings.
+fn json_integer(value: &Value) -> Option<String> {
+    match value {
+        Value::String(number) => Some(number.clone()),
+        Value::Number(number) if number.is_u64() || number.is_i64() => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn json_bytes(value: &Value) -> Option<Vec<u8>> {
+    let digits = value.as_str()?.strip_prefix("0x")?;
+
+    hex::decode(digits).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERC20_ABI: &str = r#"[
+        {
+            "type": "function",
+            "name": "transfer",
+            "stateMutability": "nonpayable",
+            "inputs": [
+                { "name": "to", "type": "address" },
+                { "name": "amount", "type": "uint256" }
+            ],
+            "outputs": [{ "name": "", "type": "bool" }]
+        },
+        {
+            "type": "function",
+            "name": "balanceOf",
+            "stateMutability": "view",
+            "inputs": [{ "name": "account", "type": "address" }],
+            "outputs": [{ "name": "", "type": "uint256" }]
+        },
+        {
+            "type": "event",
+            "name": "Transfer",
+            "inputs": [
+                { "name": "from", "type": "address", "indexed": true },
+                { "name": "to", "type": "address", "indexed": true },
+                { "name": "value", "type": "uint256", "indexed": false }
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn test_contract_call_from_json_abi() {
+        let abi = ContractAbi::from_json(ERC20_ABI).unwrap();
+
+        // events are not callable
+        assert_eq!(abi.functions.len(), 2);
+
+        let transfer = abi.function("transfer").unwrap();
+
+        assert_eq!(transfer.signature(), "transfer(address,uint256)");
+        assert!(!transfer.is_read_only());
+
+        let calldata = transfer
+            .encode_input_json(
+                r#"["0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6", "1000000000000000000000"]"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            hex::encode(&calldata),
+            "a9059cbb\
+             0000000000000000000000007a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6\
+             00000000000000000000000000000000000000000000003635c9adc5dea00000"
+        );
+
+        let args = transfer.decode_input(&calldata).unwrap();
+
+        assert_eq!(
+            transfer.describe(&args),
+            "transfer(address to = 0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6, uint256 amount = 1000000000000000000000)"
+        );
+
+        let balance_of = abi.function("balanceOf(address)").unwrap();
+
+        assert!(balance_of.is_read_only());
+
+        let output = balance_of
+            .decode_output(&U256::from(12345u64).to_be_bytes())
+            .unwrap();
+
+        assert_eq!(output, vec![AbiToken::Uint(U256::from(12345u64))]);
+        assert_eq!(output[0].to_json(), serde_json::json!("12345"));
+
+        assert_eq!(
+            abi.function("approve"),
+            Err(EvmError::FunctionNotFound("approve".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_tokenize_checks_types() {
+        let kind = |kind: &str| parse_type(kind, &[]).unwrap();
+
+        assert_eq!(
+            kind("uint8[2][]"),
+            ParamType::Array(Box::new(ParamType::FixedArray(
+                Box::new(ParamType::Uint(8)),
+                2
+            )))
+        );
+        assert!(parse_type("uint7", &[]).is_err());
+        assert!(parse_type("bytes33", &[]).is_err());
+        assert!(parse_type("function", &[]).is_err());
+        assert!(parse_type("uint8[1024]", &[]).is_ok());
+        assert!(parse_type("uint8[1025]", &[]).is_err());
+        assert!(parse_type("uint8[0]", &[]).is_err());
+
+        assert!(tokenize(&kind("uint8"), &serde_json::json!(255)).is_ok());
+        assert!(tokenize(&kind("uint8"), &serde_json::json!(256)).is_err());
+        assert!(tokenize(&kind("uint256"), &serde_json::json!(-1)).is_err());
+
+        assert_eq!(
+            tokenize(&kind("int8"), &serde_json::json!(-128)).unwrap(),
+            AbiToken::Int(U256::from(128u64).wrapping_neg())
+        );
+        assert!(tokenize(&kind("int8"), &serde_json::json!(128)).is_err());
+        assert!(tokenize(&kind("int8"), &serde_json::json!("-129")).is_err());
+
+        assert!(tokenize(&kind("bytes4"), &serde_json::json!("0xa9059cbb")).is_ok());
+        assert!(tokenize(&kind("bytes4"), &serde_json::json!("0xa9059c")).is_err());
+        assert!(tokenize(&kind("address"), &serde_json::json!("0x7a9d")).is_err());
+        assert_eq!(
+            tokenize(
+                &kind("address"),
+                &serde_json::json!("0x7A9d2f53fEA15e31F0a89d7f5d9e0E82b0b88ad6")
+            ),
+            Err(EvmError::InvalidChecksum(
+                "0x7A9d2f53fEA15e31F0a89d7f5d9e0E82b0b88ad6".to_string()
+            ))
+        );
+        assert!(tokenize(&kind("bool"), &serde_json::json!("true")).is_err());
+    }
+}
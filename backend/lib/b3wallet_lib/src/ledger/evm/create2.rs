@@ -0,0 +1,172 @@
+use super::{address::EvmAddress, error::EvmError, utils::keccak256};
+
+/// The keyless deterministic deployment proxy, 0x4e59b44847b379578588920cA78FbF26c0B4956C
+/// on most EVM chains. It takes the 32 bytes salt followed by the init code as calldata
+/// and returns the 20 bytes address of the deployed contract.
+pub const DETERMINISTIC_DEPLOYMENT_PROXY: EvmAddress = EvmAddress::from_bytes([
+    0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88, 0x92, 0x0c, 0xa7, 0x8f, 0xbf, 0x26,
+    0xc0, 0xb4, 0x95, 0x6c,
+]);
+
+/// The EIP-1014 address of a contract created by `deployer` with CREATE2:
+/// `keccak256(0xff ++ deployer ++ salt ++ keccak256(init_code))[12..]`.
+pub fn create2_address(deployer: &EvmAddress, salt: &[u8; 32], init_code: &[u8]) -> EvmAddress {
+    let mut preimage = Vec::with_capacity(1 + 20 + 32 + 32);
+    preimage.push(0xff);
+    preimage.extend_from_slice(deployer.as_bytes());
+    preimage.extend_from_slice(salt);
+    preimage.extend_from_slice(&keccak256(init_code));
+
+    let hash = keccak256(&preimage);
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+
+    EvmAddress::from_bytes(address)
+}
+
+/// A contract deployed through a CREATE2 factory, its address is known before it is deployed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvmCreate2 {
+    pub factory: EvmAddress,
+    pub salt: [u8; 32],
+    pub init_code: Vec<u8>,
+}
+
+impl EvmCreate2 {
+    pub fn new(factory: EvmAddress, salt: &[u8], init_code: Vec<u8>) -> Result<Self, EvmError> {
+        let salt = salt
+            .try_into()
+            .map_err(|_| EvmError::InvalidSalt(salt.len()))?;
+
+        Ok(EvmCreate2 {
+            factory,
+            salt,
+            init_code,
+        })
+    }
+
+    /// Where the factory deploys the contract.
+    pub fn address(&self) -> EvmAddress {
+        create2_address(&self.factory, &self.salt, &self.init_code)
+    }
+
+    /// The calldata of the factory: the salt followed by the init code.
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut data = self.salt.to_vec();
+        data.extend_from_slice(&self.init_code);
+
+        data
+    }
+
+    /// Check the output of the factory call is the predicted address.
+    /// The factory answers with nothing when the deployment fails, e.g. when the address is taken.
+    pub fn check_output(&self, output: &[u8]) -> Result<EvmAddress, EvmError> {
+        let expected = self.address();
+
+        let deployed = match output.len() {
+            20 => output,
+            // factories returning the address as an ABI word
+            32 => &output[12..],
+            _ => return Err(EvmError::Create2Failed(expected.to_string())),
+        };
+
+        let mut address = [0u8; 20];
+        address.copy_from_slice(deployed);
+        let deployed = EvmAddress::from_bytes(address);
+
+        if deployed != expected {
+            return Err(EvmError::Create2AddressMismatch(
+                expected.to_string(),
+                deployed.to_string(),
+            ));
+        }
+
+        Ok(deployed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create2_address() {
+        // the examples of EIP-1014
+        let zero = EvmAddress::ZERO;
+        let deadbeef = EvmAddress::parse("0x00000000000000000000000000000000deadbeef").unwrap();
+
+        let mut cafebabe = [0u8; 32];
+        cafebabe[28..].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe]);
+
+        for (deployer, salt, init_code, expected) in [
+            (
+                zero,
+                [0u8; 32],
+                vec![0x00],
+                "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38",
+            ),
+            (
+                EvmAddress::parse("0xdeadbeef00000000000000000000000000000000").unwrap(),
+                [0u8; 32],
+                vec![0x00],
+                "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3",
+            ),
+            (
+                deadbeef,
+                cafebabe,
+                vec![0xde, 0xad, 0xbe, 0xef],
+                "0x60f3f640a8508fC6a86d45DF051962668E1e8AC7",
+            ),
+            (
+                zero,
+                [0u8; 32],
+                vec![],
+                "0xE33C0C7F7df4809055C3ebA6c09CFe4BaF1BD9e0",
+            ),
+        ] {
+            assert_eq!(
+                create2_address(&deployer, &salt, &init_code).to_string(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_create2_factory_call() {
+        let factory = DETERMINISTIC_DEPLOYMENT_PROXY;
+
+        assert_eq!(
+            factory.to_string(),
+            "0x4e59b44847b379578588920cA78FbF26c0B4956C"
+        );
+        assert!(EvmCreate2::new(factory, &[1; 31], vec![0x00]).is_err());
+
+        let create2 = EvmCreate2::new(factory, &[1; 32], vec![0x60, 0x80]).unwrap();
+
+        assert_eq!(
+            create2.calldata(),
+            [[1u8; 32].to_vec(), vec![0x60, 0x80]].concat()
+        );
+
+        let address = create2.address();
+
+        assert_eq!(create2.check_output(address.as_bytes()), Ok(address));
+
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(address.as_bytes());
+        assert_eq!(create2.check_output(&word), Ok(address));
+
+        assert_eq!(
+            create2.check_output(&[]),
+            Err(EvmError::Create2Failed(address.to_string()))
+        );
+        assert_eq!(
+            create2.check_output(&[0xab; 20]),
+            Err(EvmError::Create2AddressMismatch(
+                address.to_string(),
+                EvmAddress::from_bytes([0xab; 20]).to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use super::{
+    abi::encode,
+    contract::{parse_param_type, tokenize},
+    error::EvmError,
+    u256::U256,
+    utils::keccak256,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// The fields a domain may have, in the order EIP-712 lists them.
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TypedField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// EIP-712 typed structured data, as dApps pass it to `eth_signTypedData_v4`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedField>>,
+    #[serde(rename = "primaryType")]
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// Parse the typed data JSON. The `EIP712Domain` type is derived from the
+    /// domain fields when it is left out.
+    pub fn from_json(json: &str) -> Result<Self, EvmError> {
+        let mut typed_data: TypedData = serde_json::from_str(json)
+            .map_err(|err| EvmError::InvalidTypedData(err.to_string()))?;
+
+        if !typed_data.types.contains_key(DOMAIN_TYPE) {
+            let domain = typed_data
+                .domain
+                .as_object()
+                .ok_or_else(|| EvmError::InvalidTypedData("domain is not an object".to_string()))?;
+
+            let fields = DOMAIN_FIELDS
+                .iter()
+                .filter(|(name, _)| domain.contains_key(*name))
+                .map(|(name, kind)| TypedField {
+                    name: name.to_string(),
+                    kind: kind.to_string(),
+                })
+                .collect();
+
+            typed_data.types.insert(DOMAIN_TYPE.to_string(), fields);
+        }
+
+        if !typed_data.types.contains_key(&typed_data.primary_type) {
+            return Err(EvmError::InvalidTypedData(format!(
+                "primary type {} is not defined",
+                typed_data.primary_type
+            )));
+        }
+
+        Ok(typed_data)
+    }
+
+    /// The chain id of the domain, if it names one.
+    pub fn chain_id(&self) -> Result<Option<u64>, EvmError> {
+        let invalid = || EvmError::InvalidTypedData("invalid domain chainId".to_string());
+
+        match self.domain.get("chainId") {
+            None => Ok(None),
+            Some(Value::Number(number)) => number.as_u64().map(Some).ok_or_else(invalid),
+            Some(Value::String(number)) => U256::parse(number)
+                .ok()
+                .and_then(|number| number.to_u64())
+                .map(Some)
+                .ok_or_else(invalid),
+            Some(_) => Err(invalid()),
+        }
+    }
+
+    /// The type with the types it references sorted after it,
+    /// e.g. `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+    pub fn encode_type(&self, name: &str) -> Result<String, EvmError> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(name, &mut dependencies)?;
+        dependencies.remove(name);
+
+        let mut encoded = self.encode_single_type(name)?;
+
+        for dependency in dependencies {
+            encoded.push_str(&self.encode_single_type(&dependency)?);
+        }
+
+        Ok(encoded)
+    }
+
+    pub fn type_hash(&self, name: &str) -> Result<[u8; 32], EvmError> {
+        Ok(keccak256(self.encode_type(name)?.as_bytes()))
+    }
+
+    /// The type hash followed by one word per field.
+    pub fn encode_data(&self, name: &str, data: &Value) -> Result<Vec<u8>, EvmError> {
+        let mut encoded = self.type_hash(name)?.to_vec();
+
+        for field in self.fields(name)? {
+            let value = data.get(&field.name).ok_or_else(|| {
+                EvmError::InvalidTypedData(format!("{} is missing {}", name, field.name))
+            })?;
+
+            encoded.extend(self.encode_value(&field.kind, value)?);
+        }
+
+        Ok(encoded)
+    }
+
+    pub fn hash_struct(&self, name: &str, data: &Value) -> Result<[u8; 32], EvmError> {
+        Ok(keccak256(&self.encode_data(name, data)?))
+    }
+
+    pub fn domain_separator(&self) -> Result<[u8; 32], EvmError> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    /// The hash to sign: `keccak256(0x1901 || domainSeparator || hashStruct(message))`.
+    pub fn signing_hash(&self) -> Result<[u8; 32], EvmError> {
+        let mut encoded = vec![0x19, 0x01];
+        encoded.extend(self.domain_separator()?);
+        encoded.extend(self.hash_struct(&self.primary_type, &self.message)?);
+
+        Ok(keccak256(&encoded))
+    }
+
+    /// The domain and message with their field names, for the consent message.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} for domain {}",
+            self.primary_type,
+            self.describe_value(&self.primary_type, &self.message),
+            self.describe_value(DOMAIN_TYPE, &self.domain)
+        )
+    }
+
+    fn describe_value(&self, kind: &str, value: &Value) -> String {
+        if let Some(item) = array_item(kind) {
+            let items = match value.as_array() {
+                Some(items) => items
+                    .iter()
+                    .map(|value| self.describe_value(item, value))
+                    .collect::<Vec<String>>(),
+                None => return value.to_string(),
+            };
+
+            return format!("[{}]", items.join(", "));
+        }
+
+        if let Some(fields) = self.types.get(kind) {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let value = value.get(&field.name).unwrap_or(&Value::Null);
+
+                    format!(
+                        "{}: {}",
+                        field.name,
+                        self.describe_value(&field.kind, value)
+                    )
+                })
+                .collect::<Vec<String>>();
+
+            return format!("{{{}}}", fields.join(", "));
+        }
+
+        match value {
+            Value::String(string) if kind == "string" => format!("{:?}", string),
+            Value::String(string) => string.clone(),
+            value => value.to_string(),
+        }
+    }
+
+    fn fields(&self, name: &str) -> Result<&Vec<TypedField>, EvmError> {
+        self.types
+            .get(name)
+            .ok_or_else(|| EvmError::InvalidTypedData(format!("type {} is not defined", name)))
+    }
+
+    fn encode_single_type(&self, name: &str) -> Result<String, EvmError> {
+        let fields = self
+            .fields(name)?
+            .iter()
+            .map(|field| format!("{} {}", field.kind, field.name))
+            .collect::<Vec<String>>();
+
+        Ok(format!("{}({})", name, fields.join(",")))
+    }
+
+    fn collect_dependencies(
+        &self,
+        name: &str,
+        dependencies: &mut BTreeSet<String>,
+    ) -> Result<(), EvmError> {
+        if !dependencies.insert(name.to_string()) {
+            return Ok(());
+        }
+
+        for field in self.fields(name)? {
+            let kind = base_type(&field.kind);
+
+            if self.types.contains_key(kind) {
+                self.collect_dependencies(kind, dependencies)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A field takes one word: atomic values are ABI encoded, dynamic ones and
+    /// structs are hashed, and arrays hash the concatenated words of their items.
+    fn encode_value(&self, kind: &str, value: &Value) -> Result<Vec<u8>, EvmError> {
+        let invalid = || EvmError::InvalidTypedData(format!("{} is not a valid {}", value, kind));
+
+        if let Some(item) = array_item(kind) {
+            let items = value.as_array().ok_or_else(invalid)?;
+
+            if let Some(size) = array_size(kind) {
+                if items.len() != size {
+                    return Err(invalid());
+                }
+            }
+
+            let mut encoded = vec![];
+
+            for value in items {
+                encoded.extend(self.encode_value(item, value)?);
+            }
+
+            return Ok(keccak256(&encoded).to_vec());
+        }
+
+        if self.types.contains_key(kind) {
+            return Ok(self.hash_struct(kind, value)?.to_vec());
+        }
+
+        match kind {
+            "string" => {
+                let string = value.as_str().ok_or_else(invalid)?;
+
+                Ok(keccak256(string.as_bytes()).to_vec())
+            }
+            "bytes" => {
+                let bytes = value
+                    .as_str()
+                    .and_then(|bytes| bytes.strip_prefix("0x"))
+                    .and_then(|bytes| hex::decode(bytes).ok())
+                    .ok_or_else(invalid)?;
+
+                Ok(keccak256(&bytes).to_vec())
+            }
+            _ => {
+                let token = tokenize(&parse_param_type(kind)?, value)?;
+
+                encode(&[token])
+            }
+        }
+    }
+}
+
+/// The item type of an array type, e.g. `Person` for `Person[2]`.
+fn array_item(kind: &str) -> Option<&str> {
+    kind.strip_suffix(']')
+        .and_then(|inner| inner.rfind('[').map(|open| &kind[..open]))
+}
+
+fn array_size(kind: &str) -> Option<usize> {
+    let inner = kind.strip_suffix(']')?;
+    let open = inner.rfind('[')?;
+
+    inner[open + 1..].parse().ok()
+}
+
+fn base_type(kind: &str) -> &str {
+    kind.find('[').map(|open| &kind[..open]).unwrap_or(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::evm::{evm::get_rsv_signature, types::PublicKeyTrait};
+    use libsecp256k1::{recover, sign, Message, PublicKey, RecoveryId, SecretKey, Signature};
+
+    // the example of the EIP-712 specification
+    const MAIL: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn test_typed_data_hashes() {
+        let typed_data = TypedData::from_json(MAIL).unwrap();
+
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.type_hash("Mail").unwrap()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(typed_data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed_data.hash_struct("Mail", &typed_data.message).unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(typed_data.signing_hash().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+        assert_eq!(typed_data.chain_id().unwrap(), Some(1));
+
+        assert_eq!(
+            typed_data.describe(),
+            "Mail {from: {name: \"Cow\", wallet: 0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826}, \
+             to: {name: \"Bob\", wallet: 0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB}, \
+             contents: \"Hello, Bob!\"} for domain {name: \"Ether Mail\", version: \"1\", \
+             chainId: 1, verifyingContract: 0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC}"
+        );
+    }
+
+    #[test]
+    fn test_typed_data_domain_and_errors() {
+        // the domain type is derived when it is left out
+        let mut value: Value = serde_json::from_str(MAIL).unwrap();
+        value["types"]
+            .as_object_mut()
+            .unwrap()
+            .remove("EIP712Domain");
+
+        let typed_data = TypedData::from_json(&value.to_string()).unwrap();
+        assert_eq!(
+            hex::encode(typed_data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+
+        let mut missing = value.clone();
+        missing["message"]
+            .as_object_mut()
+            .unwrap()
+            .remove("contents");
+        let typed_data = TypedData::from_json(&missing.to_string()).unwrap();
+        assert!(typed_data.signing_hash().is_err());
+
+        let mut wrong_type = value.clone();
+        wrong_type["message"]["from"]["wallet"] = Value::String("0x1234".to_string());
+        let typed_data = TypedData::from_json(&wrong_type.to_string()).unwrap();
+        assert!(typed_data.signing_hash().is_err());
+
+        let mut unknown_primary = value.clone();
+        unknown_primary["primaryType"] = Value::String("Letter".to_string());
+        assert!(TypedData::from_json(&unknown_primary.to_string()).is_err());
+
+        let mut hex_chain = value;
+        hex_chain["domain"]["chainId"] = Value::String("0x89".to_string());
+        let typed_data = TypedData::from_json(&hex_chain.to_string()).unwrap();
+        assert_eq!(typed_data.chain_id().unwrap(), Some(137));
+    }
+
+    #[test]
+    fn test_typed_data_rsv_signature() {
+        let typed_data = TypedData::from_json(MAIL).unwrap();
+        let hash = typed_data.signing_hash().unwrap();
+
+        // the private key of Cow in the specification
+        let secret_key = SecretKey::parse(&keccak256(b"cow")).unwrap();
+        let public_key = PublicKey::from_secret_key(&secret_key);
+        assert_eq!(
+            public_key.to_address().to_string(),
+            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+        );
+
+        let message = Message::parse(&hash);
+        let (signature, _) = sign(&message, &secret_key);
+
+        let rsv = get_rsv_signature(&hash, &signature.serialize(), &public_key).unwrap();
+        assert_eq!(rsv.len(), 65);
+        assert!(rsv[64] == 27 || rsv[64] == 28);
+
+        let signature = Signature::parse_standard_slice(&rsv[..64]).unwrap();
+        let recovery_id = RecoveryId::parse_rpc(rsv[64]).unwrap();
+        let recovered = recover(&message, &signature, &recovery_id).unwrap();
+        assert_eq!(recovered, public_key);
+    }
+}
@@ -11,6 +11,29 @@ pub enum EvmError {
     InvalidRecoveryId(String),
     InvalidSignature(String),
     InvalidAddress(String),
+    InvalidChecksum(String),
+    RpcError(String),
+    InvalidRpcResponse(String),
+    TransactionPending(String),
+    TransactionReverted(String),
+    TransactionDropped(String),
+    MissingFee(String),
+    GasLimitAboveCap(u64, u64),
+    MaxFeePerGasAboveCap(u64, u64),
+    InvalidU256(String),
+    InvalidAbiValue(String),
+    InvalidAbi(String),
+    FunctionNotFound(String),
+    InvalidTypedData(String),
+    InvalidSalt(usize),
+    Create2Failed(String),
+    Create2AddressMismatch(String, String),
+    UnknownNetwork(u64),
+    InvalidNetwork(String),
+    Eip1559NotSupported(u64),
+    InvalidUserOperation(String),
+    UntrackedTransfer,
+    MissingTransactionHash(usize),
 }
 
 #[rustfmt::skip]
@@ -21,9 +44,32 @@ impl fmt::Display for EvmError {
             EvmError::InvalidTransactionType => write!(f, "Invalid transaction type"),
             EvmError::InvalidMessage(msg) => write!(f, "Invalid message: {}", msg),
             EvmError::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            EvmError::InvalidChecksum(address) => write!(f, "Invalid EIP-55 checksum: {}", address),
             EvmError::InvalidPublicKey(msg) => write!(f, "Invalid public key: {}", msg),
             EvmError::InvalidRecoveryId(msg) => write!(f, "Invalid recovery id: {}", msg),
             EvmError::InvalidSignature(msg) => write!(f, "Invalid signature: {}", msg),
+            EvmError::RpcError(msg) => write!(f, "RPC error: {}", msg),
+            EvmError::InvalidRpcResponse(msg) => write!(f, "Invalid RPC response: {}", msg),
+            EvmError::TransactionPending(tx_hash) => write!(f, "Transaction {} is not mined yet", tx_hash),
+            EvmError::TransactionReverted(tx_hash) => write!(f, "Transaction {} reverted", tx_hash),
+            EvmError::TransactionDropped(tx_hash) => write!(f, "Transaction {} was dropped", tx_hash),
+            EvmError::MissingFee(field) => write!(f, "No {} given, estimated or configured", field),
+            EvmError::GasLimitAboveCap(gas_limit, cap) => write!(f, "Gas limit {} is above the cap of {}", gas_limit, cap),
+            EvmError::MaxFeePerGasAboveCap(max_fee, cap) => write!(f, "Max fee per gas {} is above the cap of {}", max_fee, cap),
+            EvmError::InvalidU256(value) => write!(f, "Invalid uint256: {}", value),
+            EvmError::InvalidAbiValue(msg) => write!(f, "Invalid ABI value: {}", msg),
+            EvmError::InvalidAbi(msg) => write!(f, "Invalid ABI: {}", msg),
+            EvmError::FunctionNotFound(name) => write!(f, "Function not found: {}", name),
+            EvmError::InvalidTypedData(msg) => write!(f, "Invalid typed data: {}", msg),
+            EvmError::InvalidSalt(len) => write!(f, "Invalid CREATE2 salt: expected 32 bytes, got {}", len),
+            EvmError::Create2Failed(address) => write!(f, "CREATE2 deployment to {} would fail", address),
+            EvmError::Create2AddressMismatch(expected, deployed) => write!(f, "CREATE2 factory deploys to {} instead of {}", deployed, expected),
+            EvmError::UnknownNetwork(chain_id) => write!(f, "EVM chain {} is not in the network registry", chain_id),
+            EvmError::InvalidNetwork(msg) => write!(f, "Invalid EVM network: {}", msg),
+            EvmError::Eip1559NotSupported(chain_id) => write!(f, "EVM chain {} does not support EIP-1559 transactions", chain_id),
+            EvmError::InvalidUserOperation(msg) => write!(f, "Invalid user operation: {}", msg),
+            EvmError::UntrackedTransfer => write!(f, "EVM tokens are only sent through the wallet store, which reserves the nonce"),
+            EvmError::MissingTransactionHash(index) => write!(f, "Pending {} has no transaction hash to check, it can only be removed", index),
         }
     }
 }
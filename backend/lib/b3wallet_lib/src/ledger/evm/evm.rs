@@ -6,7 +6,8 @@ use super::berlin::EvmTransaction2930;
 use super::error::EvmError;
 use super::legacy::EvmTransactionLegacy;
 use super::london::EvmTransaction1559;
-use super::utils::{string_to_vec_u8, vec_u8_to_string};
+use super::summary::EvmTransactionSummary;
+use super::utils::{hash_personal_message, string_to_vec_u8, vec_u8_to_string};
 use candid::{CandidType, Deserialize};
 use enum_dispatch::enum_dispatch;
 use libsecp256k1::{recover, Message, PublicKey, RecoveryId, Signature};
@@ -24,6 +25,7 @@ pub trait EvmSignTrait {
     fn hash(&self) -> Vec<u8>;
     fn unsigned_hash(&self) -> Vec<u8>;
     fn tx_id(&self) -> String;
+    fn summary(&self) -> EvmTransactionSummary;
 }
 
 #[enum_dispatch(EvmSignTrait)]
@@ -58,15 +60,15 @@ pub fn get_evm_transaction(
     let tx_type = get_evm_transaction_type(hex_raw_tx)?;
 
     if tx_type == EvmTransactionType::Legacy {
-        let transaction = EvmTransactionLegacy::from((hex_raw_tx.to_owned(), chain_id));
+        let transaction = EvmTransactionLegacy::try_from((hex_raw_tx.to_owned(), chain_id))?;
 
         Ok(transaction.into())
     } else if tx_type == EvmTransactionType::EIP1559 {
-        let transaction = EvmTransaction1559::from(hex_raw_tx.to_owned());
+        let transaction = EvmTransaction1559::try_from(hex_raw_tx.to_owned())?;
 
         Ok(transaction.into())
     } else if tx_type == EvmTransactionType::EIP2930 {
-        let transaction = EvmTransaction2930::from(hex_raw_tx.to_owned());
+        let transaction = EvmTransaction2930::try_from(hex_raw_tx.to_owned())?;
 
         Ok(transaction.into())
     } else {
@@ -114,6 +116,28 @@ pub fn get_recovery_id(
     ))
 }
 
+/// The 65 bytes `r || s || v` signature that `personal_sign` and `eth_signTypedData` return.
+/// `s` is moved to the lower half of the curve order, as contracts reject malleable signatures,
+/// and `v` is 27 plus the recovery id.
+pub fn get_rsv_signature(
+    message: &[u8],
+    signature: &[u8],
+    public_key: &PublicKey,
+) -> Result<Vec<u8>, EvmError> {
+    let mut signature = Signature::parse_overflowing_slice(signature)
+        .map_err(|err| EvmError::InvalidSignature(err.to_string()))?;
+
+    signature.normalize_s();
+
+    let mut rsv = signature.serialize().to_vec();
+
+    let recovery_id = get_recovery_id(message, &rsv, public_key)?;
+
+    rsv.push(27 + recovery_id.serialize());
+
+    Ok(rsv)
+}
+
 pub fn encode_access_list(access_list: &Vec<(String, Vec<String>)>) -> Vec<u8> {
     let mut stream = rlp::RlpStream::new_list(access_list.len());
 
@@ -163,7 +187,7 @@ impl Ledger {
         let mut evm_tx =
             get_evm_transaction(&hex_raw_tx, chain_id).map_err(LedgerError::EvmError)?;
 
-        let message = evm_tx.unsigned_serialized();
+        let message = evm_tx.unsigned_hash();
 
         if message.len() != 32 {
             return Err(LedgerError::InvalidMessageLength);
@@ -177,6 +201,21 @@ impl Ledger {
 
         Ok(signed_evm_tx)
     }
+
+    /// Sign a 32 bytes hash, returning the `r || s || v` signature `ecrecover` accepts.
+    pub async fn sign_evm_hash(&self, hash: [u8; 32]) -> Result<Vec<u8>, LedgerError> {
+        let public_key = self.public_key()?;
+
+        let signature = self.subaccount.sign_with_ecdsa(hash.to_vec()).await?;
+
+        get_rsv_signature(&hash, &signature, public_key).map_err(LedgerError::EvmError)
+    }
+
+    /// Sign a message the way `personal_sign` does, behind the EIP-191 prefix,
+    /// so it can never be mistaken for a transaction.
+    pub async fn sign_evm_personal_message(&self, message: &[u8]) -> Result<Vec<u8>, LedgerError> {
+        self.sign_evm_hash(hash_personal_message(message)).await
+    }
 }
 
 #[cfg(test)]
@@ -237,6 +276,38 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn get_evm_transaction_with_value_above_u256() {
+        use crate::ledger::evm::utils::u64_to_vec_u8;
+
+        let mut stream = rlp::RlpStream::new_list(12);
+
+        stream.append(&u64_to_vec_u8(&1));
+        stream.append(&u64_to_vec_u8(&0));
+        stream.append(&u64_to_vec_u8(&1_000_000_000));
+        stream.append(&u64_to_vec_u8(&20_000_000_000));
+        stream.append(&u64_to_vec_u8(&21_000));
+        stream.append(&vec![0x11u8; 20]);
+        // 33 bytes do not fit in 256 bits
+        stream.append(&vec![0xffu8; 33]);
+        stream.append(&Vec::<u8>::new());
+        stream.append_raw(&encode_access_list(&vec![]), 1);
+        stream.append(&Vec::<u8>::new());
+        stream.append(&Vec::<u8>::new());
+        stream.append(&Vec::<u8>::new());
+
+        let raw_tx = [&[0x02], &stream.out()[..]].concat();
+
+        assert!(matches!(
+            EvmTransaction1559::try_from(raw_tx.clone()),
+            Err(EvmError::InvalidU256(_))
+        ));
+        assert!(matches!(
+            get_evm_transaction(&raw_tx, 1),
+            Err(EvmError::InvalidU256(_))
+        ));
+    }
+
     #[test]
     fn access_list_encode() {
         let expected = "f872f85994de0b295669a9fd93d5f28d9ec85e40f4cb697baef842a00000000000000000000000000000000000000000000000000000000000000003a00000000000000000000000000000000000000000000000000000000000000007d694bb9bc244d798123fde783fcc1c72d3bb8c189413c0";
@@ -275,4 +346,58 @@ mod tests {
         let decoded = decode_access_list(&access_list_hex);
         assert_eq!(decoded, expected);
     }
+
+    #[test]
+    fn personal_sign_recovers_to_eth_address() {
+        use crate::ledger::{ecdsa::ChainAddress, evm::utils::keccak256};
+        use libsecp256k1::{sign, SecretKey};
+
+        let secret_key = SecretKey::parse(&keccak256(b"b3wallet")).unwrap();
+        let public_key = PublicKey::from_secret_key(&secret_key);
+
+        let mut ledger = Ledger::default();
+        ledger
+            .set_ecdsa_public_key(public_key.serialize_compressed().to_vec())
+            .unwrap();
+
+        let hash = hash_personal_message(b"Sign in to b3wallet");
+
+        // what the canister gets back from sign_with_ecdsa, 64 bytes without v
+        let (signature, _) = sign(&Message::parse(&hash), &secret_key);
+
+        let rsv = get_rsv_signature(&hash, &signature.serialize(), &public_key).unwrap();
+        assert_eq!(rsv.len(), 65);
+        assert!(rsv[64] == 27 || rsv[64] == 28);
+
+        // ecrecover(hash, v, r, s)
+        let recovered = recover(
+            &Message::parse(&hash),
+            &Signature::parse_standard_slice(&rsv[..64]).unwrap(),
+            &RecoveryId::parse_rpc(rsv[64]).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            recovered.eth_address().unwrap(),
+            ledger.eth_address().unwrap()
+        );
+    }
+
+    #[test]
+    fn rsv_signature_has_low_s() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[7u8; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secret_key);
+
+        let hash = hash_personal_message(b"malleable");
+        let (signature, _) = libsecp256k1::sign(&Message::parse(&hash), &secret_key);
+
+        // the same signature with s in the upper half of the curve order
+        let mut high_s = signature;
+        high_s.s = -high_s.s;
+
+        let low = get_rsv_signature(&hash, &signature.serialize(), &public_key).unwrap();
+        let high = get_rsv_signature(&hash, &high_s.serialize(), &public_key).unwrap();
+
+        assert_eq!(low, high);
+    }
 }
@@ -0,0 +1,190 @@
+use super::error::EvmError;
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+/// Gas defaults and fee caps of an EVM chain, kept in the wallet settings.
+/// The defaults are only used when the chain can not be asked for a suggestion,
+/// the caps are the most an operation on this chain is allowed to pay.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct EvmGasConfig {
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    pub gas_limit_cap: Option<u64>,
+    pub max_fee_per_gas_cap: Option<u64>,
+}
+
+/// The fees of a transaction, as signed.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EvmFees {
+    pub gas_limit: u64,
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+}
+
+/// What the chain suggests at the time of execution.
+/// Every value is optional, a failing RPC call leaves it to the defaults.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct EvmFeeSuggestion {
+    pub gas_limit: Option<u64>,
+    pub base_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+}
+
+impl EvmFees {
+    /// The most the transaction can pay in fees, in wei.
+    pub fn max_fee(&self) -> u128 {
+        self.gas_limit as u128 * self.max_fee_per_gas as u128
+    }
+}
+
+impl EvmGasConfig {
+    /// The most an operation on this chain can pay in fees, in wei.
+    pub fn max_fee_cap(&self) -> Option<u128> {
+        let gas_limit = self.gas_limit_cap?;
+        let max_fee_per_gas = self.max_fee_per_gas_cap?;
+
+        Some(gas_limit as u128 * max_fee_per_gas as u128)
+    }
+
+    pub fn check_caps(&self, fees: &EvmFees) -> Result<(), EvmError> {
+        if let Some(cap) = self.gas_limit_cap {
+            if fees.gas_limit > cap {
+                return Err(EvmError::GasLimitAboveCap(fees.gas_limit, cap));
+            }
+        }
+
+        if let Some(cap) = self.max_fee_per_gas_cap {
+            if fees.max_fee_per_gas > cap {
+                return Err(EvmError::MaxFeePerGasAboveCap(fees.max_fee_per_gas, cap));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pick the fees of a transaction: the requested values first, then the suggestion
+    /// of the chain and at last the defaults of this config. The result is checked against the caps.
+    /// The max fee leaves room for the base fee to double before the transaction is mined.
+    pub fn resolve(
+        &self,
+        gas_limit: Option<u64>,
+        max_fee_per_gas: Option<u64>,
+        max_priority_fee_per_gas: Option<u64>,
+        suggestion: &EvmFeeSuggestion,
+    ) -> Result<EvmFees, EvmError> {
+        let gas_limit = gas_limit
+            .or(suggestion.gas_limit)
+            .or(self.gas_limit)
+            .ok_or(EvmError::MissingFee("gas_limit".to_string()))?;
+
+        let max_priority_fee_per_gas = max_priority_fee_per_gas
+            .or(suggestion.max_priority_fee_per_gas)
+            .or(self.max_priority_fee_per_gas)
+            .unwrap_or(0);
+
+        let max_fee_per_gas = max_fee_per_gas
+            .or(suggestion.base_fee_per_gas.map(|base_fee| {
+                base_fee
+                    .saturating_mul(2)
+                    .saturating_add(max_priority_fee_per_gas)
+            }))
+            .or(self.max_fee_per_gas)
+            .ok_or(EvmError::MissingFee("max_fee_per_gas".to_string()))?;
+
+        let fees = EvmFees {
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas: max_priority_fee_per_gas.min(max_fee_per_gas),
+        };
+
+        self.check_caps(&fees)?;
+
+        Ok(fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_fees() {
+        let config = EvmGasConfig {
+            gas_limit: Some(100_000),
+            max_fee_per_gas: Some(50_000_000_000),
+            max_priority_fee_per_gas: Some(2_000_000_000),
+            gas_limit_cap: Some(200_000),
+            max_fee_per_gas_cap: Some(100_000_000_000),
+        };
+
+        assert_eq!(config.max_fee_cap(), Some(20_000_000_000_000_000));
+
+        // nothing from the chain, the defaults are used
+        let fees = config
+            .resolve(None, None, None, &EvmFeeSuggestion::default())
+            .unwrap();
+
+        assert_eq!(
+            fees,
+            EvmFees {
+                gas_limit: 100_000,
+                max_fee_per_gas: 50_000_000_000,
+                max_priority_fee_per_gas: 2_000_000_000,
+            }
+        );
+
+        let suggestion = EvmFeeSuggestion {
+            gas_limit: Some(21_000),
+            base_fee_per_gas: Some(20_000_000_000),
+            max_priority_fee_per_gas: Some(1_000_000_000),
+        };
+
+        // the suggestion of the chain wins over the defaults
+        let fees = config.resolve(None, None, None, &suggestion).unwrap();
+
+        assert_eq!(
+            fees,
+            EvmFees {
+                gas_limit: 21_000,
+                max_fee_per_gas: 41_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+            }
+        );
+        assert_eq!(fees.max_fee(), 861_000_000_000_000);
+
+        // the requested values win over everything
+        let fees = config
+            .resolve(Some(30_000), Some(60_000_000_000), Some(0), &suggestion)
+            .unwrap();
+
+        assert_eq!(fees.gas_limit, 30_000);
+        assert_eq!(fees.max_fee_per_gas, 60_000_000_000);
+        assert_eq!(fees.max_priority_fee_per_gas, 0);
+
+        // the caps are never exceeded
+        assert_eq!(
+            config.resolve(Some(300_000), None, None, &suggestion),
+            Err(EvmError::GasLimitAboveCap(300_000, 200_000))
+        );
+
+        let congested = EvmFeeSuggestion {
+            base_fee_per_gas: Some(80_000_000_000),
+            ..suggestion
+        };
+
+        assert_eq!(
+            config.resolve(None, None, None, &congested),
+            Err(EvmError::MaxFeePerGasAboveCap(
+                161_000_000_000,
+                100_000_000_000
+            ))
+        );
+
+        // without defaults there has to be a suggestion
+        assert_eq!(
+            EvmGasConfig::default().resolve(None, None, None, &EvmFeeSuggestion::default()),
+            Err(EvmError::MissingFee("gas_limit".to_string()))
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use super::error::EvmError;
 use super::evm::{get_recovery_id, EvmSignTrait};
+use super::summary::EvmTransactionSummary;
+use super::u256::U256;
 use super::utils::{
     remove_leading, string_to_vec_u8, u64_to_vec_u8, vec_u8_to_string, vec_u8_to_u64,
 };
@@ -14,15 +16,17 @@ pub struct EvmTransactionLegacy {
     pub gas_price: u64,
     pub gas_limit: u64,
     pub to: String,
-    pub value: u64,
+    pub value: U256,
     pub data: String,
     pub v: String,
     pub r: String,
     pub s: String,
 }
 
-impl From<(Vec<u8>, u64)> for EvmTransactionLegacy {
-    fn from(data: (Vec<u8>, u64)) -> Self {
+impl TryFrom<(Vec<u8>, u64)> for EvmTransactionLegacy {
+    type Error = EvmError;
+
+    fn try_from(data: (Vec<u8>, u64)) -> Result<Self, Self::Error> {
         let rlp = rlp::Rlp::new(&data.0[..]);
 
         let nonce_hex = rlp.at(0).as_val::<Vec<u8>>();
@@ -38,7 +42,7 @@ impl From<(Vec<u8>, u64)> for EvmTransactionLegacy {
         let to = vec_u8_to_string(&to_hex);
 
         let value_hex = rlp.at(4).as_val::<Vec<u8>>();
-        let value = vec_u8_to_u64(&value_hex);
+        let value = U256::from_be_slice(&value_hex)?;
 
         let data_tx_hex = rlp.at(5).as_val::<Vec<u8>>();
         let data_tx = vec_u8_to_string(&data_tx_hex);
@@ -54,7 +58,7 @@ impl From<(Vec<u8>, u64)> for EvmTransactionLegacy {
 
         let chain_id = data.1;
 
-        EvmTransactionLegacy {
+        Ok(EvmTransactionLegacy {
             chain_id,
             nonce,
             gas_price,
@@ -65,7 +69,7 @@ impl From<(Vec<u8>, u64)> for EvmTransactionLegacy {
             v,
             r,
             s,
-        }
+        })
     }
 }
 impl EvmSignTrait for EvmTransactionLegacy {
@@ -79,7 +83,7 @@ impl EvmSignTrait for EvmTransactionLegacy {
 
         let s = vec_u8_to_string(&s_remove_leading_zeros);
 
-        let message = self.unsigned_serialized();
+        let message = self.unsigned_hash();
         let recovery_id = get_recovery_id(&message, &signature, &public_key)?;
 
         let v_number = chain_id * 2 + 35 + u64::from(recovery_id.serialize());
@@ -141,7 +145,7 @@ impl EvmSignTrait for EvmTransactionLegacy {
             u64_to_vec_u8(&self.gas_price),
             u64_to_vec_u8(&self.gas_limit),
             string_to_vec_u8(&self.to),
-            u64_to_vec_u8(&self.value),
+            self.value.to_be_bytes_trimmed(),
             string_to_vec_u8(&self.data),
             u64_to_vec_u8(&self.chain_id),
         ];
@@ -173,7 +177,7 @@ impl EvmSignTrait for EvmTransactionLegacy {
         let to = string_to_vec_u8(&self.to[..]);
         stream.append(&to);
 
-        let value = u64_to_vec_u8(&self.value);
+        let value = self.value.to_be_bytes_trimmed();
         stream.append(&value);
 
         let data = string_to_vec_u8(&self.data[..]);
@@ -229,6 +233,18 @@ impl EvmSignTrait for EvmTransactionLegacy {
 
         tx_id
     }
+
+    fn summary(&self) -> EvmTransactionSummary {
+        EvmTransactionSummary::new(
+            self.chain_id,
+            self.nonce,
+            &self.to,
+            self.value,
+            &self.data,
+            self.gas_limit,
+            self.gas_price,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -243,7 +259,7 @@ mod tests {
             nonce: 0,
             gas_limit: 0,
             to: "0x".to_string(),
-            value: 0,
+            value: U256::ZERO,
             data: "0x".to_string(),
             v: "".to_string(),
             r: "".to_string(),
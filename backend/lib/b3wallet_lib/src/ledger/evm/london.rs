@@ -1,5 +1,7 @@
 use super::error::EvmError;
 use super::evm::{decode_access_list, encode_access_list, get_recovery_id, EvmSignTrait};
+use super::summary::EvmTransactionSummary;
+use super::u256::U256;
 use super::utils::{
     remove_leading, string_to_vec_u8, u64_to_vec_u8, vec_u8_to_string, vec_u8_to_u64,
 };
@@ -15,7 +17,7 @@ pub struct EvmTransaction1559 {
     pub gas_limit: u64,
     pub max_fee_per_gas: u64,
     pub to: String,
-    pub value: u64,
+    pub value: U256,
     pub data: String,
     pub access_list: Vec<(String, Vec<String>)>,
     pub v: String,
@@ -23,8 +25,10 @@ pub struct EvmTransaction1559 {
     pub s: String,
 }
 
-impl From<Vec<u8>> for EvmTransaction1559 {
-    fn from(data: Vec<u8>) -> Self {
+impl TryFrom<Vec<u8>> for EvmTransaction1559 {
+    type Error = EvmError;
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
         let rlp = rlp::Rlp::new(&data[1..]);
 
         let chain_id_hex = rlp.at(0).as_val::<Vec<u8>>();
@@ -47,7 +51,7 @@ impl From<Vec<u8>> for EvmTransaction1559 {
         let to = vec_u8_to_string(&to_hex);
 
         let value_hex = rlp.at(6).as_val::<Vec<u8>>();
-        let value = vec_u8_to_u64(&value_hex);
+        let value = U256::from_be_slice(&value_hex)?;
 
         let data_tx_hex = rlp.at(7).as_val::<Vec<u8>>();
         let data_tx = vec_u8_to_string(&data_tx_hex);
@@ -63,7 +67,7 @@ impl From<Vec<u8>> for EvmTransaction1559 {
         let s_hex = rlp.at(11).as_val::<Vec<u8>>();
         let s = vec_u8_to_string(&s_hex);
 
-        EvmTransaction1559 {
+        Ok(EvmTransaction1559 {
             chain_id,
             nonce,
             max_priority_fee_per_gas,
@@ -76,7 +80,7 @@ impl From<Vec<u8>> for EvmTransaction1559 {
             v,
             r,
             s,
-        }
+        })
     }
 }
 impl EvmSignTrait for EvmTransaction1559 {
@@ -88,7 +92,7 @@ impl EvmSignTrait for EvmTransaction1559 {
 
         let s = vec_u8_to_string(&s_remove_leading_zeros);
 
-        let message = self.unsigned_serialized();
+        let message = self.unsigned_hash();
 
         let recovery_id = get_recovery_id(&message, &signature, &public_key)?;
 
@@ -160,7 +164,7 @@ impl EvmSignTrait for EvmTransaction1559 {
             u64_to_vec_u8(&self.max_fee_per_gas),
             u64_to_vec_u8(&self.gas_limit),
             string_to_vec_u8(&self.to),
-            u64_to_vec_u8(&self.value),
+            self.value.to_be_bytes_trimmed(),
             string_to_vec_u8(&self.data),
         ];
 
@@ -198,7 +202,7 @@ impl EvmSignTrait for EvmTransaction1559 {
         let to = string_to_vec_u8(&self.to[..]);
         stream.append(&to);
 
-        let value = u64_to_vec_u8(&self.value);
+        let value = self.value.to_be_bytes_trimmed();
         stream.append(&value);
 
         let data = string_to_vec_u8(&self.data[..]);
@@ -260,6 +264,22 @@ impl EvmSignTrait for EvmTransaction1559 {
 
         tx_id
     }
+
+    fn summary(&self) -> EvmTransactionSummary {
+        let mut summary = EvmTransactionSummary::new(
+            self.chain_id,
+            self.nonce,
+            &self.to,
+            self.value,
+            &self.data,
+            self.gas_limit,
+            self.max_fee_per_gas,
+        );
+
+        summary.max_priority_fee_per_gas = Some(self.max_priority_fee_per_gas);
+
+        summary
+    }
 }
 
 #[cfg(test)]
@@ -276,7 +296,7 @@ mod tests {
             max_fee_per_gas: 0,
             gas_limit: 0,
             to: "0x".to_string(),
-            value: 0,
+            value: U256::ZERO,
             data: "0x".to_string(),
             access_list: vec![],
             v: "".to_string(),
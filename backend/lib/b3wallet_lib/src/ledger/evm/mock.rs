@@ -0,0 +1,140 @@
+use super::error::EvmError;
+use super::rpc::RpcService;
+use super::utils::{string_to_vec_u8, vec_u8_to_string};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tiny_keccak::{Hasher, Keccak};
+
+/// An in-memory EVM node answering the requests normally sent to the EVM RPC canister.
+#[derive(Default)]
+pub struct MockNode {
+    pub balances: HashMap<String, u128>,
+    pub nonces: HashMap<String, u64>,
+    pub gas_price: u64,
+    pub max_priority_fee_per_gas: u64,
+    pub base_fee_per_gas: u64,
+    /// The gas every call is estimated to use, `None` makes the estimation fail.
+    pub estimated_gas: Option<u64>,
+    /// The output of `eth_call`, by contract address.
+    pub call_results: HashMap<String, Vec<u8>>,
+    pub raw_transactions: Vec<String>,
+    /// The block number and success of the mined transactions, by hash.
+    pub receipts: HashMap<String, (u64, bool)>,
+}
+
+thread_local! {
+    static NODE: RefCell<MockNode> = RefCell::new(MockNode::default());
+}
+
+pub fn with_mock_node<R>(f: impl FnOnce(&mut MockNode) -> R) -> R {
+    NODE.with(|node| f(&mut node.borrow_mut()))
+}
+
+pub fn reset_mock_node() {
+    with_mock_node(|node| *node = MockNode::default());
+}
+
+impl MockNode {
+    fn handle(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+        let address = params[0].as_str().unwrap_or_default().to_lowercase();
+
+        match method {
+            "eth_getBalance" => {
+                let balance = self.balances.get(&address).cloned().unwrap_or(0);
+
+                Ok(json!(format!("0x{:x}", balance)))
+            }
+            "eth_getTransactionCount" => {
+                let nonce = self.nonces.get(&address).cloned().unwrap_or(0);
+
+                Ok(json!(format!("0x{:x}", nonce)))
+            }
+            "eth_gasPrice" => Ok(json!(format!("0x{:x}", self.gas_price))),
+            "eth_maxPriorityFeePerGas" => {
+                Ok(json!(format!("0x{:x}", self.max_priority_fee_per_gas)))
+            }
+            "eth_call" => {
+                let to = params[0]["to"].as_str().unwrap_or_default().to_lowercase();
+
+                match self.call_results.get(&to) {
+                    Some(output) => Ok(json!(format!("0x{}", vec_u8_to_string(output)))),
+                    None => Err("execution reverted".to_string()),
+                }
+            }
+            "eth_estimateGas" => match self.estimated_gas {
+                Some(gas) => Ok(json!(format!("0x{:x}", gas))),
+                None => Err("execution reverted".to_string()),
+            },
+            "eth_feeHistory" => Ok(json!({
+                "oldestBlock": "0x1",
+                "baseFeePerGas": [
+                    format!("0x{:x}", self.base_fee_per_gas),
+                    format!("0x{:x}", self.base_fee_per_gas),
+                ],
+                "reward": [[format!("0x{:x}", self.max_priority_fee_per_gas)]],
+            })),
+            "eth_sendRawTransaction" => {
+                let raw_tx = params[0].as_str().ok_or("missing raw transaction")?;
+
+                self.raw_transactions.push(raw_tx.to_string());
+
+                Ok(json!(tx_hash(raw_tx)))
+            }
+            "eth_getTransactionReceipt" => {
+                let hash = params[0].as_str().ok_or("missing transaction hash")?;
+
+                match self.receipts.get(hash) {
+                    Some((block_number, success)) => Ok(json!({
+                        "transactionHash": hash,
+                        "blockNumber": format!("0x{:x}", block_number),
+                        "status": if *success { "0x1" } else { "0x0" },
+                    })),
+                    None => Ok(Value::Null),
+                }
+            }
+            "eth_getTransactionByHash" => {
+                let hash = params[0].as_str().ok_or("missing transaction hash")?;
+
+                let known = self
+                    .raw_transactions
+                    .iter()
+                    .any(|raw_tx| tx_hash(raw_tx) == hash);
+
+                if known {
+                    Ok(json!({ "hash": hash }))
+                } else {
+                    Ok(Value::Null)
+                }
+            }
+            _ => Err(format!("the method {} does not exist", method)),
+        }
+    }
+}
+
+fn tx_hash(raw_tx: &str) -> String {
+    let mut keccak = Keccak::v256();
+    keccak.update(&string_to_vec_u8(raw_tx));
+    let mut hash = [0u8; 32];
+    keccak.finalize(&mut hash);
+
+    format!("0x{}", vec_u8_to_string(&hash.to_vec()))
+}
+
+pub async fn rpc_request(_service: &RpcService, json: String) -> Result<String, EvmError> {
+    let request: Value =
+        serde_json::from_str(&json).map_err(|err| EvmError::RpcError(err.to_string()))?;
+
+    let method = request["method"].as_str().unwrap_or_default();
+
+    let response = match with_mock_node(|node| node.handle(method, &request["params"])) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32601, "message": message },
+        }),
+    };
+
+    Ok(response.to_string())
+}
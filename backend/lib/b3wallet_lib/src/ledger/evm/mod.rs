@@ -1,8 +1,23 @@
+pub mod abi;
+pub mod address;
 pub mod api;
 pub mod berlin;
+pub mod contract;
+pub mod create2;
+pub mod eip712;
 pub mod error;
 pub mod evm;
+pub mod gas;
 pub mod legacy;
 pub mod london;
+pub mod network;
+pub mod nft;
+pub mod rpc;
+pub mod summary;
 pub mod types;
+pub mod u256;
+pub mod user_op;
 pub mod utils;
+
+#[cfg(test)]
+mod mock;
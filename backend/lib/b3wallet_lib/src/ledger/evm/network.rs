@@ -0,0 +1,160 @@
+use super::{error::EvmError, rpc::RpcService, u256::U256};
+use crate::ledger::types::ChainId;
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What the wallet knows about an EVM chain, kept in the wallet settings by chain id.
+/// An EVM chain can only be added to an account once its network is registered.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EvmNetwork {
+    pub name: String,
+    /// The symbol of the native token, e.g. ETH.
+    pub symbol: String,
+    pub decimals: u8,
+    /// Where the JSON-RPC requests go, None lets the EVM RPC canister
+    /// pick one of its providers for the chain id.
+    pub rpc_service: Option<RpcService>,
+    /// The block explorer, e.g. https://etherscan.io.
+    pub explorer_url: Option<String>,
+    /// The wallet signs EIP-1559 transactions only, they are refused on other networks.
+    pub eip1559: bool,
+}
+
+impl EvmNetwork {
+    pub fn new(name: &str, symbol: &str, explorer_url: &str) -> Self {
+        EvmNetwork {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            decimals: 18,
+            rpc_service: None,
+            explorer_url: Some(explorer_url.to_string()),
+            eip1559: true,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), EvmError> {
+        if self.name.trim().is_empty() {
+            return Err(EvmError::InvalidNetwork("empty name".to_string()));
+        }
+
+        if self.symbol.trim().is_empty() {
+            return Err(EvmError::InvalidNetwork("empty symbol".to_string()));
+        }
+
+        // a uint256 has 78 digits at most
+        if self.decimals > 77 {
+            return Err(EvmError::InvalidNetwork(format!(
+                "{} decimals",
+                self.decimals
+            )));
+        }
+
+        if let Some(url) = &self.explorer_url {
+            if !url.starts_with("https://") {
+                return Err(EvmError::InvalidNetwork(format!("explorer url {}", url)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The amount of native token in `value` wei, e.g. `0.5 ETH`.
+    pub fn format_amount(&self, value: U256) -> String {
+        format!(
+            "{} {}",
+            value.format_units(self.decimals as usize),
+            self.symbol
+        )
+    }
+
+    /// The page of the transaction on the block explorer.
+    pub fn tx_url(&self, tx_hash: &str) -> Option<String> {
+        self.explorer_url
+            .as_ref()
+            .map(|url| format!("{}/tx/{}", url.trim_end_matches('/'), tx_hash))
+    }
+}
+
+/// The networks every wallet starts with, more can be registered in the settings.
+pub fn default_evm_networks() -> HashMap<ChainId, EvmNetwork> {
+    HashMap::from([
+        (
+            1,
+            EvmNetwork::new("Ethereum", "ETH", "https://etherscan.io"),
+        ),
+        (
+            11155111,
+            EvmNetwork::new("Sepolia", "ETH", "https://sepolia.etherscan.io"),
+        ),
+        (
+            10,
+            EvmNetwork::new("OP Mainnet", "ETH", "https://optimistic.etherscan.io"),
+        ),
+        (8453, EvmNetwork::new("Base", "ETH", "https://basescan.org")),
+        (
+            42161,
+            EvmNetwork::new("Arbitrum One", "ETH", "https://arbiscan.io"),
+        ),
+        (
+            137,
+            EvmNetwork::new("Polygon", "POL", "https://polygonscan.com"),
+        ),
+        (
+            56,
+            EvmNetwork::new("BNB Smart Chain", "BNB", "https://bscscan.com"),
+        ),
+        (
+            43114,
+            EvmNetwork::new("Avalanche C-Chain", "AVAX", "https://snowtrace.io"),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evm_network_display() {
+        let networks = default_evm_networks();
+
+        let base = networks.get(&8453).unwrap();
+
+        assert_eq!(
+            base.format_amount(U256::from(500_000_000_000_000_000u64)),
+            "0.5 ETH"
+        );
+        assert_eq!(
+            base.tx_url("0xabc"),
+            Some("https://basescan.org/tx/0xabc".to_string())
+        );
+
+        let polygon = networks.get(&137).unwrap();
+
+        assert_eq!(
+            polygon.format_amount(U256::from(1u64)),
+            "0.000000000000000001 POL"
+        );
+
+        assert!(networks.values().all(|network| network.validate().is_ok()));
+    }
+
+    #[test]
+    fn test_evm_network_validate() {
+        let mut network = EvmNetwork::new("Base", "ETH", "http://basescan.org");
+
+        assert_eq!(
+            network.validate(),
+            Err(EvmError::InvalidNetwork(
+                "explorer url http://basescan.org".to_string()
+            ))
+        );
+
+        network.explorer_url = None;
+        assert_eq!(network.validate(), Ok(()));
+
+        network.symbol = " ".to_string();
+        assert!(network.validate().is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use super::{
+    abi::{decode, encode_function_call, AbiToken, ParamType},
+    address::EvmAddress,
+    error::EvmError,
+    u256::U256,
+};
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(CandidType, Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum EvmNftStandard {
+    ERC721,
+    ERC1155,
+}
+
+impl fmt::Display for EvmNftStandard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvmNftStandard::ERC721 => write!(f, "ERC-721"),
+            EvmNftStandard::ERC1155 => write!(f, "ERC-1155"),
+        }
+    }
+}
+
+/// An NFT the account holds, as far as the wallet knows.
+#[derive(CandidType, Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct EvmNft {
+    pub contract_address: EvmAddress,
+    pub token_id: U256,
+    pub standard: EvmNftStandard,
+    /// Always 1 for ERC-721.
+    pub amount: U256,
+}
+
+impl EvmNft {
+    pub fn new(
+        contract_address: EvmAddress,
+        token_id: U256,
+        standard: EvmNftStandard,
+        amount: U256,
+    ) -> Self {
+        EvmNft {
+            contract_address,
+            token_id,
+            standard,
+            amount,
+        }
+    }
+
+    pub fn is(&self, contract_address: &EvmAddress, token_id: U256) -> bool {
+        self.contract_address == *contract_address && self.token_id == token_id
+    }
+}
+
+/// An approval the account gave on an NFT collection, listed until it is revoked.
+#[derive(CandidType, Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct EvmNftApproval {
+    pub contract_address: EvmAddress,
+    pub operator: EvmAddress,
+    /// The ERC-721 token the operator is approved for,
+    /// None when it is approved for the whole collection with `setApprovalForAll`.
+    pub token_id: Option<U256>,
+    /// The transaction that gave the approval.
+    pub tx_hash: String,
+}
+
+impl EvmNftApproval {
+    pub fn new(
+        contract_address: EvmAddress,
+        operator: EvmAddress,
+        token_id: Option<U256>,
+        tx_hash: String,
+    ) -> Self {
+        EvmNftApproval {
+            contract_address,
+            operator,
+            token_id,
+            tx_hash,
+        }
+    }
+
+    pub fn is(
+        &self,
+        contract_address: &EvmAddress,
+        operator: &EvmAddress,
+        token_id: Option<U256>,
+    ) -> bool {
+        self.contract_address == *contract_address
+            && self.operator == *operator
+            && self.token_id == token_id
+    }
+}
+
+/// The calldata of `safeTransferFrom`, with the ERC-1155 `data` left empty.
+/// The amount is ignored for ERC-721.
+pub fn nft_transfer_data(
+    standard: EvmNftStandard,
+    from: &EvmAddress,
+    to: &EvmAddress,
+    token_id: U256,
+    amount: U256,
+) -> Result<Vec<u8>, EvmError> {
+    match standard {
+        EvmNftStandard::ERC721 => encode_function_call(
+            "safeTransferFrom(address,address,uint256)",
+            &[
+                AbiToken::Address(*from),
+                AbiToken::Address(*to),
+                AbiToken::Uint(token_id),
+            ],
+        ),
+        EvmNftStandard::ERC1155 => encode_function_call(
+            "safeTransferFrom(address,address,uint256,uint256,bytes)",
+            &[
+                AbiToken::Address(*from),
+                AbiToken::Address(*to),
+                AbiToken::Uint(token_id),
+                AbiToken::Uint(amount),
+                AbiToken::Bytes(vec![]),
+            ],
+        ),
+    }
+}
+
+/// The calldata giving, or taking back, an approval.
+/// With a token id the operator gets that ERC-721 token only, and revoking approves the
+/// zero address. Without one it gets the whole collection, the only way ERC-1155 approves.
+pub fn nft_approval_data(
+    operator: &EvmAddress,
+    token_id: Option<U256>,
+    approved: bool,
+) -> Result<Vec<u8>, EvmError> {
+    match token_id {
+        Some(token_id) => {
+            let operator = if approved {
+                *operator
+            } else {
+                EvmAddress::ZERO
+            };
+
+            encode_function_call(
+                "approve(address,uint256)",
+                &[AbiToken::Address(operator), AbiToken::Uint(token_id)],
+            )
+        }
+        None => encode_function_call(
+            "setApprovalForAll(address,bool)",
+            &[AbiToken::Address(*operator), AbiToken::Bool(approved)],
+        ),
+    }
+}
+
+/// The calldata asking how many of the token `owner` holds,
+/// `ownerOf(uint256)` for ERC-721 and `balanceOf(address,uint256)` for ERC-1155.
+pub fn nft_balance_data(
+    standard: EvmNftStandard,
+    owner: &EvmAddress,
+    token_id: U256,
+) -> Result<Vec<u8>, EvmError> {
+    match standard {
+        EvmNftStandard::ERC721 => {
+            encode_function_call("ownerOf(uint256)", &[AbiToken::Uint(token_id)])
+        }
+        EvmNftStandard::ERC1155 => encode_function_call(
+            "balanceOf(address,uint256)",
+            &[AbiToken::Address(*owner), AbiToken::Uint(token_id)],
+        ),
+    }
+}
+
+/// Read the answer to `nft_balance_data`, an ERC-721 token counts as 1 when `owner` owns it.
+pub fn decode_nft_balance(
+    standard: EvmNftStandard,
+    owner: &EvmAddress,
+    output: &[u8],
+) -> Result<U256, EvmError> {
+    match standard {
+        EvmNftStandard::ERC721 => match decode(&[ParamType::Address], output)?.as_slice() {
+            [AbiToken::Address(address)] if address == owner => Ok(U256::from(1u64)),
+            _ => Ok(U256::ZERO),
+        },
+        EvmNftStandard::ERC1155 => match decode(&[ParamType::Uint(256)], output)?.as_slice() {
+            [AbiToken::Uint(amount)] => Ok(*amount),
+            _ => Err(EvmError::InvalidAbiValue("balanceOf output".to_string())),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::evm::abi::encode;
+
+    fn addresses() -> (EvmAddress, EvmAddress) {
+        (
+            EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap(),
+            EvmAddress::parse("0xbb9bc244d798123fde783fcc1c72d3bb8c189413").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_nft_calldata() {
+        let (owner, to) = addresses();
+        let token_id = U256::from(42u64);
+
+        let data =
+            nft_transfer_data(EvmNftStandard::ERC721, &owner, &to, token_id, U256::ZERO).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "42842e0e");
+        assert_eq!(data.len(), 4 + 3 * 32);
+
+        let data = nft_transfer_data(
+            EvmNftStandard::ERC1155,
+            &owner,
+            &to,
+            token_id,
+            U256::from(3u64),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&data[..4]), "f242432a");
+        // 5 head words and the empty bytes length
+        assert_eq!(data.len(), 4 + 6 * 32);
+
+        let data = nft_approval_data(&to, None, true).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "a22cb465");
+        assert_eq!(data[4 + 63], 1);
+
+        let data = nft_approval_data(&to, None, false).unwrap();
+        assert_eq!(data[4 + 63], 0);
+
+        let data = nft_approval_data(&to, Some(token_id), true).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "095ea7b3");
+        assert_eq!(data[16..36], *to.as_bytes());
+
+        // revoking a single token approval approves the zero address
+        let data = nft_approval_data(&to, Some(token_id), false).unwrap();
+        assert_eq!(data[4..36], [0u8; 32]);
+
+        let data = nft_balance_data(EvmNftStandard::ERC721, &owner, token_id).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "6352211e");
+
+        let data = nft_balance_data(EvmNftStandard::ERC1155, &owner, token_id).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "00fdd58e");
+    }
+
+    #[test]
+    fn test_decode_nft_balance() {
+        let (owner, to) = addresses();
+
+        let owned = encode(&[AbiToken::Address(owner)]).unwrap();
+        let not_owned = encode(&[AbiToken::Address(to)]).unwrap();
+
+        assert_eq!(
+            decode_nft_balance(EvmNftStandard::ERC721, &owner, &owned),
+            Ok(U256::from(1u64))
+        );
+        assert_eq!(
+            decode_nft_balance(EvmNftStandard::ERC721, &owner, &not_owned),
+            Ok(U256::ZERO)
+        );
+
+        let balance = encode(&[AbiToken::Uint(U256::from(5u64))]).unwrap();
+
+        assert_eq!(
+            decode_nft_balance(EvmNftStandard::ERC1155, &owner, &balance),
+            Ok(U256::from(5u64))
+        );
+        assert!(decode_nft_balance(EvmNftStandard::ERC1155, &owner, &[]).is_err());
+    }
+}
@@ -0,0 +1,430 @@
+use super::error::EvmError;
+use super::u256::U256;
+use super::utils::vec_u8_to_string;
+use crate::ledger::types::Balance;
+use b3_utils::types::CanisterId;
+use candid::{CandidType, Deserialize, Principal, Reserved};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+#[cfg(not(test))]
+use ic_cdk::api::call::call_with_payment128;
+
+#[cfg(test)]
+use super::mock::rpc_request;
+
+/// The EVM RPC canister (7hfb6-caaaa-aaaar-qadga-cai).
+/// It forwards JSON-RPC requests to the EVM providers through HTTPS outcalls.
+pub const EVM_RPC_CANISTER_ID: CanisterId =
+    Principal::from_slice(&[0, 0, 0, 0, 2, 48, 0, 204, 1, 1]);
+
+/// The cycles attached to every request, the unused part is refunded.
+pub const EVM_RPC_REQUEST_CYCLES: u128 = 10_000_000_000;
+
+/// The maximum size of a JSON-RPC response, in bytes.
+pub const EVM_RPC_MAX_RESPONSE_BYTES: u64 = 8_000;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcApi {
+    pub url: String,
+    pub headers: Option<Vec<HttpHeader>>,
+}
+
+/// The provider the EVM RPC canister sends the request to.
+/// `Chain` lets the canister pick one of its providers for the chain id.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RpcService {
+    Chain(u64),
+    Provider(u64),
+    Custom(RpcApi),
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum RpcError {
+    ProviderError(Reserved),
+    HttpOutcallError(Reserved),
+    JsonRpcError(JsonRpcError),
+    ValidationError(Reserved),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::ProviderError(_) => write!(f, "Provider error"),
+            RpcError::HttpOutcallError(_) => write!(f, "HTTP outcall error"),
+            RpcError::JsonRpcError(err) => write!(f, "{} ({})", err.message, err.code),
+            RpcError::ValidationError(_) => write!(f, "Validation error"),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum RequestResult {
+    Ok(String),
+    Err(RpcError),
+}
+
+#[cfg(not(test))]
+async fn rpc_request(service: &RpcService, json: String) -> Result<String, EvmError> {
+    let (result,): (RequestResult,) = call_with_payment128(
+        EVM_RPC_CANISTER_ID,
+        "request",
+        (service, json, EVM_RPC_MAX_RESPONSE_BYTES),
+        EVM_RPC_REQUEST_CYCLES,
+    )
+    .await
+    .map_err(|err| EvmError::RpcError(err.1))?;
+
+    request_response(result)
+}
+
+fn request_response(result: RequestResult) -> Result<String, EvmError> {
+    match result {
+        RequestResult::Ok(response) => Ok(response),
+        RequestResult::Err(err) => Err(EvmError::RpcError(err.to_string())),
+    }
+}
+
+/// The state of a broadcast transaction, read from its receipt.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum EvmTransactionStatus {
+    Pending,
+    Success(u64),
+    Reverted(u64),
+    Dropped,
+}
+
+/// The fee market of the recent blocks.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EvmFeeHistory {
+    pub base_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+}
+
+/// A JSON-RPC client for a single EVM chain.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EvmRpc {
+    pub service: RpcService,
+}
+
+impl EvmRpc {
+    pub fn new(service: RpcService) -> Self {
+        EvmRpc { service }
+    }
+
+    /// Send a JSON-RPC request and return the `result` of the response.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, EvmError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response = rpc_request(&self.service, request.to_string()).await?;
+
+        let mut response: Value = serde_json::from_str(&response)
+            .map_err(|err| EvmError::InvalidRpcResponse(err.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(EvmError::RpcError(error.to_string()));
+        }
+
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(EvmError::InvalidRpcResponse(response.to_string())),
+        }
+    }
+
+    /// Returns the balance of the address in wei.
+    pub async fn get_balance(&self, address: &str) -> Result<Balance, EvmError> {
+        let result = self
+            .call("eth_getBalance", json!([address, "latest"]))
+            .await?;
+
+        quantity_to_nat(&result)
+    }
+
+    /// Returns the number of transactions sent from the address.
+    /// With the `pending` block tag, transactions still in the mempool are counted too.
+    pub async fn get_transaction_count(
+        &self,
+        address: &str,
+        block_tag: &str,
+    ) -> Result<u64, EvmError> {
+        let result = self
+            .call("eth_getTransactionCount", json!([address, block_tag]))
+            .await?;
+
+        quantity_to_u64(&result)
+    }
+
+    pub async fn gas_price(&self) -> Result<u64, EvmError> {
+        let result = self.call("eth_gasPrice", json!([])).await?;
+
+        quantity_to_u64(&result)
+    }
+
+    pub async fn max_priority_fee_per_gas(&self) -> Result<u64, EvmError> {
+        let result = self.call("eth_maxPriorityFeePerGas", json!([])).await?;
+
+        quantity_to_u64(&result)
+    }
+
+    /// Execute a read-only call against the latest block and return its raw output.
+    pub async fn eth_call(&self, to: &str, data: &[u8]) -> Result<Vec<u8>, EvmError> {
+        let call = json!({
+            "to": to,
+            "data": format!("0x{}", hex::encode(data)),
+        });
+
+        let result = self.call("eth_call", json!([call, "latest"])).await?;
+
+        let output = result
+            .as_str()
+            .ok_or(EvmError::InvalidRpcResponse(result.to_string()))?;
+
+        hex::decode(output.trim_start_matches("0x"))
+            .map_err(|err| EvmError::InvalidRpcResponse(err.to_string()))
+    }
+
+    /// Returns the gas the call would use if it was mined now.
+    /// An empty `to` ("0x") estimates a contract creation.
+    pub async fn estimate_gas(
+        &self,
+        from: &str,
+        to: &str,
+        value: U256,
+        data: &str,
+    ) -> Result<u64, EvmError> {
+        let mut call = json!({
+            "from": from,
+            "value": format!("0x{:x}", value),
+            "data": data,
+        });
+
+        if !to.trim_start_matches("0x").is_empty() {
+            call["to"] = json!(to);
+        }
+
+        let result = self.call("eth_estimateGas", json!([call])).await?;
+
+        quantity_to_u64(&result)
+    }
+
+    /// Returns the base fee of the next block and the median priority fee paid
+    /// in the last `block_count` blocks.
+    pub async fn fee_history(&self, block_count: u64) -> Result<EvmFeeHistory, EvmError> {
+        let result = self
+            .call(
+                "eth_feeHistory",
+                json!([format!("0x{:x}", block_count), "latest", [50]]),
+            )
+            .await?;
+
+        let base_fees = result["baseFeePerGas"]
+            .as_array()
+            .ok_or(EvmError::InvalidRpcResponse(result.to_string()))?;
+
+        // the last base fee is the one of the next block
+        let base_fee_per_gas = match base_fees.last() {
+            Some(base_fee) => quantity_to_u64(base_fee)?,
+            None => return Err(EvmError::InvalidRpcResponse(result.to_string())),
+        };
+
+        let mut rewards = result["reward"]
+            .as_array()
+            .map(|rewards| {
+                rewards
+                    .iter()
+                    .map(|reward| quantity_to_u64(&reward[0]))
+                    .collect::<Result<Vec<u64>, EvmError>>()
+            })
+            .unwrap_or(Ok(vec![]))?;
+
+        rewards.sort();
+
+        let max_priority_fee_per_gas = rewards.get(rewards.len() / 2).cloned().unwrap_or(0);
+
+        Ok(EvmFeeHistory {
+            base_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+
+    /// Broadcast a signed transaction and return its hash.
+    pub async fn send_raw_transaction(&self, signed_tx: &Vec<u8>) -> Result<String, EvmError> {
+        let raw_tx = format!("0x{}", vec_u8_to_string(signed_tx));
+
+        let result = self.call("eth_sendRawTransaction", json!([raw_tx])).await?;
+
+        result
+            .as_str()
+            .map(|tx_hash| tx_hash.to_string())
+            .ok_or(EvmError::InvalidRpcResponse(result.to_string()))
+    }
+
+    /// Look up the receipt of the transaction.
+    /// A transaction without receipt that the node does not know anymore has been dropped.
+    pub async fn transaction_status(
+        &self,
+        tx_hash: &str,
+    ) -> Result<EvmTransactionStatus, EvmError> {
+        let receipt = self
+            .call("eth_getTransactionReceipt", json!([tx_hash]))
+            .await?;
+
+        if receipt.is_null() {
+            let transaction = self
+                .call("eth_getTransactionByHash", json!([tx_hash]))
+                .await?;
+
+            if transaction.is_null() {
+                return Ok(EvmTransactionStatus::Dropped);
+            }
+
+            return Ok(EvmTransactionStatus::Pending);
+        }
+
+        let block_number = quantity_to_u64(&receipt["blockNumber"])?;
+
+        match receipt["status"].as_str() {
+            Some("0x1") => Ok(EvmTransactionStatus::Success(block_number)),
+            Some("0x0") => Ok(EvmTransactionStatus::Reverted(block_number)),
+            _ => Err(EvmError::InvalidRpcResponse(receipt.to_string())),
+        }
+    }
+}
+
+fn quantity_digits(value: &Value) -> Result<&str, EvmError> {
+    let quantity = value
+        .as_str()
+        .ok_or(EvmError::InvalidRpcResponse(value.to_string()))?;
+
+    let digits = quantity.strip_prefix("0x").unwrap_or(quantity);
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(EvmError::InvalidRpcResponse(quantity.to_string()));
+    }
+
+    Ok(digits)
+}
+
+/// Parse a hex encoded JSON-RPC quantity, e.g. "0x1bc16d674ec80000".
+pub fn quantity_to_u64(value: &Value) -> Result<u64, EvmError> {
+    let digits = quantity_digits(value)?;
+
+    u64::from_str_radix(digits, 16).map_err(|err| EvmError::InvalidRpcResponse(err.to_string()))
+}
+
+/// Parse a hex encoded JSON-RPC quantity of any size.
+pub fn quantity_to_nat(value: &Value) -> Result<Balance, EvmError> {
+    let digits = quantity_digits(value)?;
+
+    let nat = digits.chars().fold(Balance::from(0u8), |nat, c| {
+        nat * Balance::from(16u8) + Balance::from(c.to_digit(16).unwrap())
+    });
+
+    Ok(nat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantity_parsing() {
+        assert_eq!(quantity_to_u64(&json!("0x0")).unwrap(), 0);
+        assert_eq!(quantity_to_u64(&json!("0x5208")).unwrap(), 21_000);
+
+        // 100 ETH does not fit in a u64
+        let balance = quantity_to_nat(&json!("0x56bc75e2d63100000")).unwrap();
+        assert_eq!(
+            balance.to_string().replace('_', ""),
+            "100000000000000000000"
+        );
+
+        assert!(quantity_to_u64(&json!("0x")).is_err());
+        assert!(quantity_to_u64(&json!("0xzz")).is_err());
+        assert!(quantity_to_nat(&json!(12)).is_err());
+    }
+
+    // the errors of the EVM RPC canister carry records that are not decoded
+    #[derive(CandidType)]
+    struct HttpOutcallError {
+        code: u32,
+        message: String,
+    }
+
+    #[derive(CandidType)]
+    enum CanisterRpcError {
+        HttpOutcallError(HttpOutcallError),
+        JsonRpcError(JsonRpcError),
+    }
+
+    #[derive(CandidType)]
+    enum CanisterRequestResult {
+        Ok(String),
+        Err(CanisterRpcError),
+    }
+
+    fn canister_reply(result: CanisterRequestResult) -> Result<String, EvmError> {
+        let bytes = candid::encode_one(result).unwrap();
+
+        request_response(candid::decode_one::<RequestResult>(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_request_args_and_reply() {
+        let json = r#"{"jsonrpc":"2.0","id":1,"method":"eth_gasPrice","params":[]}"#;
+
+        let bytes =
+            candid::encode_args((RpcService::Chain(1), json, EVM_RPC_MAX_RESPONSE_BYTES)).unwrap();
+        let (service, request, max_response_bytes): (RpcService, String, u64) =
+            candid::decode_args(&bytes).unwrap();
+
+        assert_eq!(service, RpcService::Chain(1));
+        assert_eq!(request, json);
+        assert_eq!(max_response_bytes, EVM_RPC_MAX_RESPONSE_BYTES);
+
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"0x3b9aca00"}"#;
+
+        assert_eq!(
+            canister_reply(CanisterRequestResult::Ok(response.to_string())),
+            Ok(response.to_string())
+        );
+
+        assert_eq!(
+            canister_reply(CanisterRequestResult::Err(CanisterRpcError::JsonRpcError(
+                JsonRpcError {
+                    code: 3,
+                    message: "execution reverted".to_string(),
+                }
+            ))),
+            Err(EvmError::RpcError("execution reverted (3)".to_string()))
+        );
+
+        assert_eq!(
+            canister_reply(CanisterRequestResult::Err(
+                CanisterRpcError::HttpOutcallError(HttpOutcallError {
+                    code: 2,
+                    message: "timeout".to_string(),
+                })
+            )),
+            Err(EvmError::RpcError("HTTP outcall error".to_string()))
+        );
+    }
+}
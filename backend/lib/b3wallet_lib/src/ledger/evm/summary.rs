@@ -0,0 +1,448 @@
+use super::{
+    abi::AbiToken,
+    address::EvmAddress,
+    contract::{AbiFunction, ContractAbi},
+    u256::U256,
+};
+use crate::store::with_setting;
+use candid::{CandidType, Deserialize};
+use std::fmt;
+
+/// The token and NFT calls approvers see most, to decode calldata without the contract ABI.
+const KNOWN_FUNCTIONS: &str = r#"[
+    { "name": "transfer", "inputs": [
+        { "name": "to", "type": "address" },
+        { "name": "amount", "type": "uint256" }
+    ] },
+    { "name": "approve", "inputs": [
+        { "name": "spender", "type": "address" },
+        { "name": "amount", "type": "uint256" }
+    ] },
+    { "name": "increaseAllowance", "inputs": [
+        { "name": "spender", "type": "address" },
+        { "name": "addedValue", "type": "uint256" }
+    ] },
+    { "name": "transferFrom", "inputs": [
+        { "name": "from", "type": "address" },
+        { "name": "to", "type": "address" },
+        { "name": "amount", "type": "uint256" }
+    ] },
+    { "name": "safeTransferFrom", "inputs": [
+        { "name": "from", "type": "address" },
+        { "name": "to", "type": "address" },
+        { "name": "tokenId", "type": "uint256" }
+    ] },
+    { "name": "safeTransferFrom", "inputs": [
+        { "name": "from", "type": "address" },
+        { "name": "to", "type": "address" },
+        { "name": "tokenId", "type": "uint256" },
+        { "name": "data", "type": "bytes" }
+    ] },
+    { "name": "setApprovalForAll", "inputs": [
+        { "name": "operator", "type": "address" },
+        { "name": "approved", "type": "bool" }
+    ] }
+]"#;
+
+/// Allowances past 2^128 are far beyond any real supply, this catches `type(uint256).max`
+/// and the other "infinite" sentinels dApps use.
+const UNLIMITED_ALLOWANCE_BITS: usize = 128;
+
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmDecodedCall {
+    /// The canonical signature, e.g. `approve(address,uint256)`.
+    pub function: String,
+    /// The call with its arguments, e.g. `approve(address spender = 0x…, uint256 amount = 1000)`.
+    pub description: String,
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub enum EvmTransactionWarning {
+    /// An allowance that never runs out, the spender can take every token of the contract.
+    UnlimitedApproval { token: String, spender: String },
+    /// `setApprovalForAll(operator, true)`, the operator can take every NFT of the collection.
+    ApprovalForAll {
+        collection: String,
+        operator: String,
+    },
+}
+
+/// What a transaction does, decoded for the people approving it.
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmTransactionSummary {
+    pub chain_id: u64,
+    /// The name of the chain in the network registry, None when it is not registered.
+    pub network: Option<String>,
+    /// The native token of the chain, ETH with 18 decimals when it is not registered.
+    pub symbol: String,
+    pub decimals: u8,
+    pub nonce: u64,
+    /// None when the transaction deploys a contract, checksummed when it is a valid address.
+    pub to: Option<String>,
+    pub value: U256,
+    pub gas_limit: u64,
+    /// The gas price of legacy and EIP-2930 transactions.
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: Option<u64>,
+    pub data_size: u64,
+    /// None for plain transfers, deployments and unknown selectors.
+    pub call: Option<EvmDecodedCall>,
+    pub selector: Option<String>,
+    pub warnings: Vec<EvmTransactionWarning>,
+}
+
+impl EvmTransactionSummary {
+    /// `to` and `data` are hex, with or without the `0x` prefix as the decoders leave them.
+    /// The priority fee is left for EIP-1559 transactions to fill in.
+    pub fn new(
+        chain_id: u64,
+        nonce: u64,
+        to: &str,
+        value: U256,
+        data: &str,
+        gas_limit: u64,
+        max_fee_per_gas: u64,
+    ) -> Self {
+        // the checksum of a raw transaction is meaningless, only its bytes are signed
+        let to = match to.trim_start_matches("0x").to_lowercase() {
+            digits if digits.is_empty() => None,
+            digits => {
+                let to = format!("0x{}", digits);
+
+                Some(EvmAddress::parse(&to).map_or(to, |address| address.to_string()))
+            }
+        };
+
+        let data = hex::decode(data.trim_start_matches("0x")).unwrap_or_default();
+
+        let network = with_setting(|setting| setting.evm_network(chain_id)).ok();
+
+        let mut summary = EvmTransactionSummary {
+            chain_id,
+            symbol: network
+                .as_ref()
+                .map_or("ETH".to_string(), |network| network.symbol.clone()),
+            decimals: network.as_ref().map_or(18, |network| network.decimals),
+            network: network.map(|network| network.name),
+            nonce,
+            to,
+            value,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas: None,
+            data_size: data.len() as u64,
+            call: None,
+            selector: None,
+            warnings: vec![],
+        };
+
+        if summary.to.is_some() && data.len() >= 4 {
+            summary.selector = Some(format!("0x{}", hex::encode(&data[..4])));
+            summary.decode_call(&data);
+        }
+
+        summary
+    }
+
+    pub fn is_deployment(&self) -> bool {
+        self.to.is_none()
+    }
+
+    /// The most the transaction can pay in fees, in wei.
+    pub fn max_fee(&self) -> u128 {
+        self.gas_limit as u128 * self.max_fee_per_gas as u128
+    }
+
+    /// The value in the native token, e.g. `1.5 ETH`.
+    pub fn native_value(&self) -> String {
+        self.native_amount(self.value)
+    }
+
+    /// What the transaction does, without its chain, nonce and fees.
+    pub fn action(&self) -> String {
+        match (&self.to, &self.call, &self.selector) {
+            (None, _, _) => format!(
+                "Deploy a contract of {} bytes with {}",
+                self.data_size,
+                self.native_value()
+            ),
+            (Some(to), Some(call), _) => format!(
+                "Call {} on {} with {}",
+                call.description,
+                to,
+                self.native_value()
+            ),
+            (Some(to), None, Some(selector)) => format!(
+                "Call unknown function {} on {} with {} bytes of calldata and {}",
+                selector,
+                to,
+                self.data_size,
+                self.native_value()
+            ),
+            (Some(to), None, None) => format!("Send {} to {}", self.native_value(), to),
+        }
+    }
+
+    fn native_amount(&self, amount: U256) -> String {
+        format!(
+            "{} {}",
+            amount.format_units(self.decimals as usize),
+            self.symbol
+        )
+    }
+
+    fn decode_call(&mut self, data: &[u8]) {
+        let known = match ContractAbi::from_json(KNOWN_FUNCTIONS) {
+            Ok(known) => known,
+            Err(_) => return,
+        };
+
+        let decoded = known.functions.iter().find_map(|function| {
+            function
+                .decode_input(data)
+                .ok()
+                .map(|tokens| (function, tokens))
+        });
+
+        if let Some((function, tokens)) = decoded {
+            self.warnings = self.warnings_of(function, &tokens);
+
+            self.call = Some(EvmDecodedCall {
+                function: function.signature(),
+                description: function.describe(&tokens),
+            });
+        }
+    }
+
+    fn warnings_of(
+        &self,
+        function: &AbiFunction,
+        tokens: &[AbiToken],
+    ) -> Vec<EvmTransactionWarning> {
+        let contract = self.to.clone().unwrap_or_default();
+
+        match (function.name.as_str(), tokens) {
+            (
+                "approve" | "increaseAllowance",
+                [AbiToken::Address(spender), AbiToken::Uint(amount)],
+            ) if amount.bits() > UNLIMITED_ALLOWANCE_BITS => {
+                vec![EvmTransactionWarning::UnlimitedApproval {
+                    token: contract,
+                    spender: spender.to_string(),
+                }]
+            }
+            ("setApprovalForAll", [AbiToken::Address(operator), AbiToken::Bool(true)]) => {
+                vec![EvmTransactionWarning::ApprovalForAll {
+                    collection: contract,
+                    operator: operator.to_string(),
+                }]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl fmt::Display for EvmTransactionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvmTransactionWarning::UnlimitedApproval { token, spender } => write!(
+                f,
+                "unlimited approval, {} can spend every token of {} held by this account",
+                spender, token
+            ),
+            EvmTransactionWarning::ApprovalForAll {
+                collection,
+                operator,
+            } => write!(
+                f,
+                "approval for all, {} can transfer every NFT of {} held by this account",
+                operator, collection
+            ),
+        }
+    }
+}
+
+impl fmt::Display for EvmTransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.action())?;
+
+        match &self.network {
+            Some(network) => write!(f, " on {}", network)?,
+            None => write!(f, " on EVM chain {}", self.chain_id)?,
+        }
+
+        write!(
+            f,
+            " with nonce {}, paying at most {} in fees ({} gas at {} wei",
+            self.nonce,
+            self.native_amount(U256::from(self.max_fee())),
+            self.gas_limit,
+            self.max_fee_per_gas
+        )?;
+
+        if let Some(priority_fee) = self.max_priority_fee_per_gas {
+            write!(f, ", {} wei priority fee", priority_fee)?;
+        }
+
+        write!(f, ")")?;
+
+        for warning in &self.warnings {
+            write!(f, ". WARNING: {}", warning)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::evm::{
+        abi::encode_function_call,
+        evm::{EvmSignTrait, EvmTransaction},
+        legacy::EvmTransactionLegacy,
+        london::EvmTransaction1559,
+    };
+
+    const TOKEN: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const SPENDER: &str = "0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6";
+
+    fn spender() -> EvmAddress {
+        EvmAddress::parse(SPENDER).unwrap()
+    }
+
+    fn london(to: &str, value: U256, data: Vec<u8>) -> EvmTransaction {
+        EvmTransaction1559 {
+            chain_id: 1,
+            nonce: 7,
+            max_priority_fee_per_gas: 1_000_000_000,
+            gas_limit: 60_000,
+            max_fee_per_gas: 30_000_000_000,
+            to: to.to_string(),
+            value,
+            data: format!("0x{}", hex::encode(data)),
+            access_list: vec![],
+            v: "0x00".to_string(),
+            r: "0x00".to_string(),
+            s: "0x00".to_string(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_summary_of_plain_transfer() {
+        // as decoded from a raw legacy transaction, lowercase without 0x prefixes
+        let transaction: EvmTransaction = EvmTransactionLegacy {
+            chain_id: 1,
+            nonce: 3,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: SPENDER[2..].to_lowercase(),
+            value: U256::parse("1500000000000000000").unwrap(),
+            data: "".to_string(),
+            v: "".to_string(),
+            r: "".to_string(),
+            s: "".to_string(),
+        }
+        .into();
+
+        let summary = transaction.summary();
+
+        assert_eq!(summary.to, Some(SPENDER.to_string()));
+        assert_eq!(summary.native_value(), "1.5 ETH");
+        assert_eq!(summary.max_fee(), 420_000_000_000_000);
+        assert_eq!(summary.call, None);
+        assert!(summary.warnings.is_empty());
+        assert_eq!(
+            summary.to_string(),
+            "Send 1.5 ETH to 0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6 on Ethereum with nonce 3, \
+             paying at most 0.00042 ETH in fees (21000 gas at 20000000000 wei)"
+        );
+    }
+
+    #[test]
+    fn test_summary_decodes_known_calls() {
+        let amount = U256::parse("1000000").unwrap();
+
+        let data = encode_function_call(
+            "transfer(address,uint256)",
+            &[AbiToken::Address(spender()), AbiToken::Uint(amount)],
+        )
+        .unwrap();
+
+        let summary = london(TOKEN, U256::ZERO, data).summary();
+
+        assert_eq!(
+            summary.call.unwrap().description,
+            format!(
+                "transfer(address to = {}, uint256 amount = 1000000)",
+                SPENDER
+            )
+        );
+        assert_eq!(summary.max_priority_fee_per_gas, Some(1_000_000_000));
+        assert!(summary.warnings.is_empty());
+
+        // a bounded approval is fine
+        let data = encode_function_call(
+            "approve(address,uint256)",
+            &[AbiToken::Address(spender()), AbiToken::Uint(amount)],
+        )
+        .unwrap();
+
+        assert!(london(TOKEN, U256::ZERO, data)
+            .summary()
+            .warnings
+            .is_empty());
+
+        // unknown selectors are shown as such
+        let summary = london(TOKEN, U256::ZERO, vec![0xde, 0xad, 0xbe, 0xef, 0]).summary();
+
+        assert_eq!(summary.call, None);
+        assert_eq!(summary.selector, Some("0xdeadbeef".to_string()));
+        assert!(summary
+            .to_string()
+            .starts_with("Call unknown function 0xdeadbeef"));
+
+        // no recipient is a deployment
+        let summary = london("", U256::ZERO, vec![0x60, 0x80, 0x60, 0x40]).summary();
+
+        assert!(summary.is_deployment());
+        assert_eq!(summary.selector, None);
+    }
+
+    #[test]
+    fn test_summary_warns_on_unlimited_approvals() {
+        let data = encode_function_call(
+            "approve(address,uint256)",
+            &[AbiToken::Address(spender()), AbiToken::Uint(U256::MAX)],
+        )
+        .unwrap();
+
+        let summary = london(TOKEN, U256::ZERO, data).summary();
+
+        assert_eq!(
+            summary.warnings,
+            vec![EvmTransactionWarning::UnlimitedApproval {
+                token: TOKEN.to_string(),
+                spender: SPENDER.to_string(),
+            }]
+        );
+        assert!(summary.to_string().contains(
+            "WARNING: unlimited approval, 0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6 can spend"
+        ));
+
+        let data = encode_function_call(
+            "setApprovalForAll(address,bool)",
+            &[AbiToken::Address(spender()), AbiToken::Bool(true)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            london(TOKEN, U256::ZERO, data).summary().warnings,
+            vec![EvmTransactionWarning::ApprovalForAll {
+                collection: TOKEN.to_string(),
+                operator: SPENDER.to_string(),
+            }]
+        );
+    }
+}
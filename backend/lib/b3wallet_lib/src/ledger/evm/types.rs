@@ -1,25 +1,17 @@
-use b3_utils::vec_to_hex_string_with_0x;
+use super::address::EvmAddress;
+use candid::{CandidType, Deserialize};
 use libsecp256k1::PublicKey;
+use serde::Serialize;
 use tiny_keccak::{Hasher, Keccak};
 
 pub trait PublicKeyTrait {
-    fn to_address(&self) -> String;
+    fn to_address(&self) -> EvmAddress;
     fn to_evm_key(&self) -> Vec<u8>; // Return owned Vec<u8> instead of reference
 }
 
 impl PublicKeyTrait for PublicKey {
-    fn to_address(&self) -> String {
-        let pub_key = self.serialize();
-
-        let mut keccak = Keccak::v256();
-        keccak.update(&pub_key[1..]);
-        let mut output = [0u8; 32];
-        keccak.finalize(&mut output);
-
-        // Convert the last 20 bytes of hash to hex string
-        let address = vec_to_hex_string_with_0x(&output[12..]);
-
-        address
+    fn to_address(&self) -> EvmAddress {
+        EvmAddress::from_public_key(self)
     }
 
     fn to_evm_key(&self) -> Vec<u8> {
@@ -34,3 +26,36 @@ impl PublicKeyTrait for PublicKey {
         output[12..].to_vec()
     }
 }
+
+/// The nonce kept by the wallet compared with the transaction count of the node.
+/// `gaps` are the nonces the wallet has used but the node has never seen,
+/// any transaction with a higher nonce stays stuck until they are filled.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EvmNonceStatus {
+    pub local: u64,
+    pub latest: u64,
+    pub pending: u64,
+    pub gaps: Vec<u64>,
+}
+
+impl EvmNonceStatus {
+    pub fn new(local: u64, latest: u64, pending: u64) -> Self {
+        let gaps = (pending..local).collect();
+
+        EvmNonceStatus {
+            local,
+            latest,
+            pending,
+            gaps,
+        }
+    }
+
+    pub fn has_gaps(&self) -> bool {
+        !self.gaps.is_empty()
+    }
+
+    /// The nonce the next transaction should use.
+    pub fn next_nonce(&self) -> u64 {
+        self.local.max(self.pending)
+    }
+}
@@ -0,0 +1,365 @@
+use super::error::EvmError;
+use candid::{
+    types::{Serializer, Type},
+    CandidType, Nat,
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{fmt, str::FromStr};
+
+/// An unsigned 256 bit integer, the size of every EVM word.
+/// Token amounts routinely go past `u64` (18 tokens of 18 decimals), so values and
+/// ABI integers use this type. It travels as a candid `nat` and is checked to fit on the way in.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U256([u8; 32]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 32]);
+    pub const MAX: U256 = U256([0xff; 32]);
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        U256(bytes)
+    }
+
+    /// Read a big endian integer of at most 32 bytes, e.g. an RLP encoded value.
+    pub fn from_be_slice(bytes: &[u8]) -> Result<Self, EvmError> {
+        if bytes.len() > 32 {
+            return Err(EvmError::InvalidU256(vec_to_hex(bytes)));
+        }
+
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+
+        Ok(U256(word))
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// The big endian bytes without leading zeros, empty for zero as RLP wants it.
+    pub fn to_be_bytes_trimmed(&self) -> Vec<u8> {
+        self.0.iter().skip_while(|&&b| b == 0).cloned().collect()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 32]
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        let bytes = self.to_be_bytes_trimmed();
+
+        if bytes.len() > 8 {
+            return None;
+        }
+
+        Some(bytes.iter().fold(0u64, |n, &b| n << 8 | b as u64))
+    }
+
+    /// The number of significant bits, 0 for zero.
+    pub fn bits(&self) -> usize {
+        match self.0.iter().position(|&b| b != 0) {
+            Some(i) => (32 - i) * 8 - self.0[i].leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// The two's complement negation, how `int256` words store negative numbers.
+    pub fn wrapping_neg(&self) -> U256 {
+        let mut inverted = [0u8; 32];
+
+        for (i, b) in self.0.iter().enumerate() {
+            inverted[i] = !b;
+        }
+
+        let mut result = U256(inverted);
+
+        for i in (0..32).rev() {
+            let (sum, overflow) = result.0[i].overflowing_add(1);
+            result.0[i] = sum;
+
+            if !overflow {
+                break;
+            }
+        }
+
+        result
+    }
+
+    pub fn checked_add(&self, other: &U256) -> Option<U256> {
+        let mut result = [0u8; 32];
+        let mut carry = 0u16;
+
+        for i in (0..32).rev() {
+            let sum = self.0[i] as u16 + other.0[i] as u16 + carry;
+            result[i] = sum as u8;
+            carry = sum >> 8;
+        }
+
+        if carry > 0 {
+            return None;
+        }
+
+        Some(U256(result))
+    }
+
+    pub fn checked_sub(&self, other: &U256) -> Option<U256> {
+        if self < other {
+            return None;
+        }
+
+        let mut result = [0u8; 32];
+        let mut borrow = 0i16;
+
+        for i in (0..32).rev() {
+            let diff = self.0[i] as i16 - other.0[i] as i16 - borrow;
+            result[i] = diff.rem_euclid(256) as u8;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+
+        Some(U256(result))
+    }
+
+    pub fn checked_mul_u64(&self, mul: u64) -> Option<U256> {
+        let mut result = [0u8; 32];
+        let mut carry = 0u128;
+
+        for i in (0..32).rev() {
+            let product = self.0[i] as u128 * mul as u128 + carry;
+            result[i] = product as u8;
+            carry = product >> 8;
+        }
+
+        if carry > 0 {
+            return None;
+        }
+
+        Some(U256(result))
+    }
+
+    /// The decimal number with the point `decimals` digits from the right,
+    /// without trailing zeros, e.g. `1.5` for 1.5e18 wei with 18 decimals.
+    pub fn format_units(&self, decimals: usize) -> String {
+        let digits = format!("{:0>width$}", self.to_string(), width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            integer.to_string()
+        } else {
+            format!("{}.{}", integer, fraction)
+        }
+    }
+
+    /// Parse a decimal number, or a hex one with the `0x` prefix.
+    pub fn parse(number: &str) -> Result<Self, EvmError> {
+        let invalid = || EvmError::InvalidU256(number.to_string());
+
+        if let Some(digits) = number.strip_prefix("0x") {
+            if digits.is_empty() || digits.len() > 64 {
+                return Err(invalid());
+            }
+
+            let padded = format!("{:0>64}", digits);
+            let bytes = hex::decode(padded).map_err(|_| invalid())?;
+
+            return U256::from_be_slice(&bytes);
+        }
+
+        if number.is_empty() {
+            return Err(invalid());
+        }
+
+        number.chars().try_fold(U256::ZERO, |n, c| {
+            let digit = c.to_digit(10).ok_or_else(invalid)?;
+
+            n.checked_mul_u64(10)
+                .and_then(|n| n.checked_add(&U256::from(digit as u64)))
+                .ok_or_else(invalid)
+        })
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(n: u64) -> Self {
+        U256::from(n as u128)
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(n: u128) -> Self {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&n.to_be_bytes());
+
+        U256(word)
+    }
+}
+
+impl From<U256> for Nat {
+    fn from(n: U256) -> Self {
+        n.0.iter().fold(Nat::from(0u8), |nat, &b| {
+            nat * Nat::from(256u16) + Nat::from(b)
+        })
+    }
+}
+
+impl TryFrom<Nat> for U256 {
+    type Error = EvmError;
+
+    fn try_from(nat: Nat) -> Result<Self, Self::Error> {
+        U256::from_be_slice(&nat.0.to_bytes_be())
+            .map_err(|_| EvmError::InvalidU256(nat.0.to_string()))
+    }
+}
+
+impl FromStr for U256 {
+    type Err = EvmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        U256::parse(s)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Nat::from(*self).0)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = hex::encode(self.to_be_bytes_trimmed());
+        let digits = hex.trim_start_matches('0');
+
+        if digits.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", digits)
+        }
+    }
+}
+
+impl CandidType for U256 {
+    fn _ty() -> Type {
+        Nat::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        Nat::from(*self).idl_serialize(serializer)
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Nat::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let nat = Nat::deserialize(deserializer)?;
+
+        U256::try_from(nat).map_err(de::Error::custom)
+    }
+}
+
+fn vec_to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u256_conversions() {
+        // 1000 tokens of 18 decimals
+        let amount = U256::parse("1000000000000000000000").unwrap();
+
+        assert_eq!(amount, U256::parse("0x3635c9adc5dea00000").unwrap());
+        assert_eq!(format!("{:x}", amount), "3635c9adc5dea00000");
+        assert_eq!(amount.to_string(), "1000000000000000000000");
+        assert_eq!(amount.to_u64(), None);
+        assert_eq!(
+            amount.to_be_bytes_trimmed(),
+            vec![0x36, 0x35, 0xc9, 0xad, 0xc5, 0xde, 0xa0, 0x00, 0x00]
+        );
+
+        let nat = Nat::from(amount);
+        assert_eq!(U256::try_from(nat).unwrap(), amount);
+
+        assert_eq!(U256::from(21_000u64).to_u64(), Some(21_000));
+        assert_eq!(format!("{:x}", U256::ZERO), "0");
+        assert!(U256::ZERO.to_be_bytes_trimmed().is_empty());
+
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(U256::parse(&U256::MAX.to_string()).unwrap(), U256::MAX);
+
+        // 2^256 does not fit
+        let too_big = Nat::from(U256::MAX) + Nat::from(1u8);
+        assert!(U256::try_from(too_big).is_err());
+        assert!(U256::parse(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        )
+        .is_err());
+        assert_eq!(U256::MAX.checked_add(&U256::from(1u64)), None);
+        assert_eq!(
+            amount.checked_sub(&U256::from(1u64)),
+            Some(U256::parse("999999999999999999999").unwrap())
+        );
+        assert_eq!(U256::MAX.checked_sub(&U256::MAX), Some(U256::ZERO));
+        assert_eq!(U256::ZERO.checked_sub(&U256::from(1u64)), None);
+
+        assert_eq!(U256::ZERO.bits(), 0);
+        assert_eq!(U256::from(255u64).bits(), 8);
+        assert_eq!(U256::from(256u64).bits(), 9);
+        assert_eq!(U256::MAX.bits(), 256);
+
+        // -1 is all ones
+        assert_eq!(U256::from(1u64).wrapping_neg(), U256::MAX);
+        assert_eq!(U256::MAX.wrapping_neg(), U256::from(1u64));
+        assert_eq!(U256::ZERO.wrapping_neg(), U256::ZERO);
+
+        assert_eq!(amount.format_units(18), "1000");
+        assert_eq!(U256::from(1_500u64).format_units(3), "1.5");
+        assert_eq!(U256::from(42u64).format_units(18), "0.000000000000000042");
+        assert_eq!(U256::ZERO.format_units(18), "0");
+        assert_eq!(U256::from(42u64).format_units(0), "42");
+
+        assert!(U256::parse("").is_err());
+        assert!(U256::parse("12a").is_err());
+        assert!(U256::parse("0x").is_err());
+        assert!(U256::from_be_slice(&[1; 33]).is_err());
+    }
+
+    #[test]
+    fn test_u256_candid() {
+        let amount = U256::parse("1000000000000000000000").unwrap();
+
+        let bytes = candid::encode_one(amount).unwrap();
+
+        // on the wire it is a plain nat
+        assert_eq!(bytes, candid::encode_one(Nat::from(amount)).unwrap());
+        assert_eq!(candid::decode_one::<U256>(&bytes).unwrap(), amount);
+
+        let too_big = candid::encode_one(Nat::from(U256::MAX) + Nat::from(1u8)).unwrap();
+        assert!(candid::decode_one::<U256>(&too_big).is_err());
+    }
+}
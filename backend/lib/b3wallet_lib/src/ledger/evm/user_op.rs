@@ -0,0 +1,599 @@
+use super::{
+    abi::{decode, encode, function_selector, AbiToken, ParamType},
+    address::EvmAddress,
+    error::EvmError,
+    summary::EvmTransactionSummary,
+    u256::U256,
+    utils::keccak256,
+};
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// The ERC-4337 EntryPoint v0.6, 0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789 on every chain.
+pub const ENTRY_POINT_V06: EvmAddress = EvmAddress::from_bytes([
+    0x5f, 0xf1, 0x37, 0xd4, 0xb0, 0xfd, 0xcd, 0x49, 0xdc, 0xa3, 0x0c, 0x7c, 0xf5, 0x7e, 0x57, 0x8a,
+    0x02, 0x6d, 0x27, 0x89,
+]);
+
+/// The ERC-4337 EntryPoint v0.7, 0x0000000071727De22E5E9d8BAf0edAc6f37da032 on every chain.
+pub const ENTRY_POINT_V07: EvmAddress = EvmAddress::from_bytes([
+    0x00, 0x00, 0x00, 0x00, 0x71, 0x72, 0x7d, 0xe2, 0x2e, 0x5e, 0x9d, 0x8b, 0xaf, 0x0e, 0xda, 0xc6,
+    0xf3, 0x7d, 0xa0, 0x32,
+]);
+
+/// The v0.7 paymaster data starts with the paymaster and its two 16 bytes gas limits.
+const V07_PAYMASTER_PREFIX: usize = 20 + 16 + 16;
+
+#[derive(CandidType, Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum EntryPointVersion {
+    V06,
+    V07,
+}
+
+impl EntryPointVersion {
+    pub fn entry_point(&self) -> EvmAddress {
+        match self {
+            EntryPointVersion::V06 => ENTRY_POINT_V06,
+            EntryPointVersion::V07 => ENTRY_POINT_V07,
+        }
+    }
+}
+
+impl fmt::Display for EntryPointVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryPointVersion::V06 => write!(f, "v0.6"),
+            EntryPointVersion::V07 => write!(f, "v0.7"),
+        }
+    }
+}
+
+/// A call the smart account makes, decoded from the call data of the user operation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserOperationCall {
+    pub to: EvmAddress,
+    pub value: U256,
+    pub data: Vec<u8>,
+}
+
+/// An ERC-4337 user operation, with the fields unpacked the way v0.6 has them.
+/// For v0.7 the init code is the factory followed by its data, and the paymaster data
+/// is the paymaster followed by its verification and post-op gas limits, 16 bytes each,
+/// and its data. They are packed when the operation is hashed.
+#[derive(CandidType, Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct UserOperation {
+    pub sender: EvmAddress,
+    pub nonce: U256,
+    pub init_code: Vec<u8>,
+    pub call_data: Vec<u8>,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl UserOperation {
+    pub fn validate(&self, version: EntryPointVersion) -> Result<(), EvmError> {
+        let invalid = |msg: &str| Err(EvmError::InvalidUserOperation(msg.to_string()));
+
+        if !self.init_code.is_empty() && self.init_code.len() < 20 {
+            return invalid("init code shorter than the factory address");
+        }
+
+        match version {
+            EntryPointVersion::V06 => {
+                if !self.paymaster_and_data.is_empty() && self.paymaster_and_data.len() < 20 {
+                    return invalid("paymaster data shorter than the paymaster address");
+                }
+            }
+            EntryPointVersion::V07 => {
+                if !self.paymaster_and_data.is_empty()
+                    && self.paymaster_and_data.len() < V07_PAYMASTER_PREFIX
+                {
+                    return invalid("paymaster data shorter than the paymaster and its gas limits");
+                }
+
+                // packed two by two in a word
+                for (name, value) in [
+                    ("callGasLimit", &self.call_gas_limit),
+                    ("verificationGasLimit", &self.verification_gas_limit),
+                    ("maxFeePerGas", &self.max_fee_per_gas),
+                    ("maxPriorityFeePerGas", &self.max_priority_fee_per_gas),
+                ] {
+                    if value.bits() > 128 {
+                        return Err(EvmError::InvalidUserOperation(format!(
+                            "{} does not fit in 128 bits",
+                            name
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The fields of the operation the hash covers, the dynamic ones hashed, without the signature.
+    pub fn pack(&self, version: EntryPointVersion) -> Result<Vec<u8>, EvmError> {
+        self.validate(version)?;
+
+        let hashed = |bytes: &[u8]| AbiToken::FixedBytes(keccak256(bytes).to_vec());
+
+        match version {
+            EntryPointVersion::V06 => encode(&[
+                AbiToken::Address(self.sender),
+                AbiToken::Uint(self.nonce),
+                hashed(&self.init_code),
+                hashed(&self.call_data),
+                AbiToken::Uint(self.call_gas_limit),
+                AbiToken::Uint(self.verification_gas_limit),
+                AbiToken::Uint(self.pre_verification_gas),
+                AbiToken::Uint(self.max_fee_per_gas),
+                AbiToken::Uint(self.max_priority_fee_per_gas),
+                hashed(&self.paymaster_and_data),
+            ]),
+            EntryPointVersion::V07 => encode(&[
+                AbiToken::Address(self.sender),
+                AbiToken::Uint(self.nonce),
+                hashed(&self.init_code),
+                hashed(&self.call_data),
+                AbiToken::FixedBytes(pack_u128(
+                    &self.verification_gas_limit,
+                    &self.call_gas_limit,
+                )),
+                AbiToken::Uint(self.pre_verification_gas),
+                AbiToken::FixedBytes(pack_u128(
+                    &self.max_priority_fee_per_gas,
+                    &self.max_fee_per_gas,
+                )),
+                hashed(&self.paymaster_and_data),
+            ]),
+        }
+    }
+
+    /// The userOpHash the smart account checks the signature against:
+    /// `keccak256(abi.encode(keccak256(pack(userOp)), entryPoint, chainId))`.
+    pub fn hash(
+        &self,
+        version: EntryPointVersion,
+        entry_point: &EvmAddress,
+        chain_id: u64,
+    ) -> Result<[u8; 32], EvmError> {
+        let packed = self.pack(version)?;
+
+        let encoded = encode(&[
+            AbiToken::FixedBytes(keccak256(&packed).to_vec()),
+            AbiToken::Address(*entry_point),
+            AbiToken::Uint(U256::from(chain_id)),
+        ])?;
+
+        Ok(keccak256(&encoded))
+    }
+
+    /// The paymaster sponsoring the gas, None when the smart account pays it.
+    pub fn paymaster(&self) -> Option<EvmAddress> {
+        let address: [u8; 20] = self.paymaster_and_data.get(..20)?.try_into().ok()?;
+
+        Some(EvmAddress::from_bytes(address))
+    }
+
+    /// The most the smart account pays for gas, in wei, when there is no paymaster.
+    pub fn max_fee(&self) -> Option<U256> {
+        let gas = self
+            .call_gas_limit
+            .checked_add(&self.verification_gas_limit)?
+            .checked_add(&self.pre_verification_gas)?
+            .to_u64()?;
+
+        self.max_fee_per_gas.checked_mul_u64(gas)
+    }
+
+    /// The calls of `execute(address,uint256,bytes)` and of the `executeBatch` of the
+    /// SimpleAccount of both versions. None for the call data of other smart accounts.
+    pub fn calls(&self) -> Option<Vec<UserOperationCall>> {
+        let selector = self.call_data.get(..4)?;
+        let args = &self.call_data[4..];
+
+        let addresses = || ParamType::Array(Box::new(ParamType::Address));
+        let uints = || ParamType::Array(Box::new(ParamType::Uint(256)));
+        let bytes = || ParamType::Array(Box::new(ParamType::Bytes));
+
+        if selector == function_selector("execute(address,uint256,bytes)") {
+            let kinds = [ParamType::Address, ParamType::Uint(256), ParamType::Bytes];
+
+            return match decode(&kinds, args).ok()?.as_slice() {
+                [AbiToken::Address(to), AbiToken::Uint(value), AbiToken::Bytes(data)] => {
+                    Some(vec![UserOperationCall {
+                        to: *to,
+                        value: *value,
+                        data: data.clone(),
+                    }])
+                }
+                _ => None,
+            };
+        }
+
+        let (targets, values, datas) =
+            if selector == function_selector("executeBatch(address[],bytes[])") {
+                match decode(&[addresses(), bytes()], args).ok()?.as_slice() {
+                    [AbiToken::Array(targets), AbiToken::Array(datas)] => {
+                        let values = vec![AbiToken::Uint(U256::ZERO); targets.len()];
+
+                        (targets.clone(), values, datas.clone())
+                    }
+                    _ => return None,
+                }
+            } else if selector == function_selector("executeBatch(address[],uint256[],bytes[])") {
+                match decode(&[addresses(), uints(), bytes()], args)
+                    .ok()?
+                    .as_slice()
+                {
+                    [AbiToken::Array(targets), AbiToken::Array(values), AbiToken::Array(datas)] => {
+                        // the v0.7 account takes no values at all for a batch without ether
+                        let values = if values.is_empty() {
+                            vec![AbiToken::Uint(U256::ZERO); targets.len()]
+                        } else {
+                            values.clone()
+                        };
+
+                        (targets.clone(), values, datas.clone())
+                    }
+                    _ => return None,
+                }
+            } else {
+                return None;
+            };
+
+        if targets.len() != values.len() || targets.len() != datas.len() {
+            return None;
+        }
+
+        targets
+            .iter()
+            .zip(values.iter())
+            .zip(datas.iter())
+            .map(|((to, value), data)| match (to, value, data) {
+                (AbiToken::Address(to), AbiToken::Uint(value), AbiToken::Bytes(data)) => {
+                    Some(UserOperationCall {
+                        to: *to,
+                        value: *value,
+                        data: data.clone(),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// What the smart account does, for the approvers. The calls are decoded like the
+    /// calls of a raw transaction, with their warnings.
+    pub fn describe(&self, chain_id: u64) -> String {
+        let calls = match self.calls() {
+            Some(calls) => calls,
+            None => {
+                return match self.call_data.get(..4) {
+                    Some(selector) => format!(
+                        "call unknown function 0x{} of the smart account with {} bytes of calldata",
+                        hex::encode(selector),
+                        self.call_data.len()
+                    ),
+                    None => "call nothing".to_string(),
+                }
+            }
+        };
+
+        let mut actions = vec![];
+        let mut warnings = vec![];
+
+        for (index, call) in calls.iter().enumerate() {
+            let summary = EvmTransactionSummary::new(
+                chain_id,
+                0,
+                &call.to.to_string(),
+                call.value,
+                &hex::encode(&call.data),
+                0,
+                0,
+            );
+
+            actions.push(format!("{}) {}", index + 1, summary.action()));
+            warnings.extend(summary.warnings);
+        }
+
+        let mut description = actions.join("; ");
+
+        for warning in warnings {
+            description.push_str(&format!(". WARNING: {}", warning));
+        }
+
+        description
+    }
+
+    /// The operation as `eth_sendUserOperation` takes it, in the format of the EntryPoint version.
+    pub fn to_json(&self, version: EntryPointVersion) -> Result<Value, EvmError> {
+        self.validate(version)?;
+
+        let quantity = |value: &U256| Value::String(format!("0x{:x}", value));
+        let bytes = |bytes: &[u8]| Value::String(format!("0x{}", hex::encode(bytes)));
+
+        let mut json = Map::new();
+
+        json.insert("sender".to_string(), Value::String(self.sender.to_string()));
+        json.insert("nonce".to_string(), quantity(&self.nonce));
+
+        match version {
+            EntryPointVersion::V06 => {
+                json.insert("initCode".to_string(), bytes(&self.init_code));
+            }
+            EntryPointVersion::V07 => {
+                if !self.init_code.is_empty() {
+                    let (factory, data) = self.init_code.split_at(20);
+
+                    json.insert("factory".to_string(), bytes(factory));
+                    json.insert("factoryData".to_string(), bytes(data));
+                }
+            }
+        }
+
+        json.insert("callData".to_string(), bytes(&self.call_data));
+        json.insert("callGasLimit".to_string(), quantity(&self.call_gas_limit));
+        json.insert(
+            "verificationGasLimit".to_string(),
+            quantity(&self.verification_gas_limit),
+        );
+        json.insert(
+            "preVerificationGas".to_string(),
+            quantity(&self.pre_verification_gas),
+        );
+        json.insert("maxFeePerGas".to_string(), quantity(&self.max_fee_per_gas));
+        json.insert(
+            "maxPriorityFeePerGas".to_string(),
+            quantity(&self.max_priority_fee_per_gas),
+        );
+
+        match version {
+            EntryPointVersion::V06 => {
+                json.insert(
+                    "paymasterAndData".to_string(),
+                    bytes(&self.paymaster_and_data),
+                );
+            }
+            EntryPointVersion::V07 => {
+                if !self.paymaster_and_data.is_empty() {
+                    let data = &self.paymaster_and_data;
+
+                    let gas_limit = |range: std::ops::Range<usize>| {
+                        U256::from_be_slice(&data[range]).map(|value| quantity(&value))
+                    };
+
+                    json.insert("paymaster".to_string(), bytes(&data[..20]));
+                    json.insert(
+                        "paymasterVerificationGasLimit".to_string(),
+                        gas_limit(20..36)?,
+                    );
+                    json.insert("paymasterPostOpGasLimit".to_string(), gas_limit(36..52)?);
+                    json.insert(
+                        "paymasterData".to_string(),
+                        bytes(&data[V07_PAYMASTER_PREFIX..]),
+                    );
+                }
+            }
+        }
+
+        json.insert("signature".to_string(), bytes(&self.signature));
+
+        Ok(Value::Object(json))
+    }
+}
+
+/// Two values of at most 128 bits in a word, `high` first.
+fn pack_u128(high: &U256, low: &U256) -> Vec<u8> {
+    let mut word = high.to_be_bytes()[16..].to_vec();
+    word.extend_from_slice(&low.to_be_bytes()[16..]);
+
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::evm::abi::encode_function_call;
+
+    const SENDER: &str = "0x7a9d2f53fea15E31F0a89D7f5d9E0E82B0B88AD6";
+    const TARGET: &str = "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413";
+
+    fn user_operation(call_data: Vec<u8>) -> UserOperation {
+        UserOperation {
+            sender: EvmAddress::parse(SENDER).unwrap(),
+            nonce: U256::from(1u64),
+            init_code: vec![],
+            call_data,
+            call_gas_limit: U256::from(100_000u64),
+            verification_gas_limit: U256::from(200_000u64),
+            pre_verification_gas: U256::from(50_000u64),
+            max_fee_per_gas: U256::from(30_000_000_000u64),
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            paymaster_and_data: vec![],
+            signature: vec![],
+        }
+    }
+
+    fn execute(value: U256, data: Vec<u8>) -> Vec<u8> {
+        encode_function_call(
+            "execute(address,uint256,bytes)",
+            &[
+                AbiToken::Address(EvmAddress::parse(TARGET).unwrap()),
+                AbiToken::Uint(value),
+                AbiToken::Bytes(data),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_entry_points() {
+        assert_eq!(
+            ENTRY_POINT_V06.to_string(),
+            "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789"
+        );
+        assert_eq!(
+            ENTRY_POINT_V07.to_string(),
+            "0x0000000071727De22E5E9d8BAf0edAc6f37da032"
+        );
+    }
+
+    #[test]
+    fn test_user_operation_hash() {
+        let user_op = user_operation(execute(U256::from(100_000_000_000_000_000u64), vec![]));
+
+        assert_eq!(hex::encode(&user_op.call_data[..4]), "b61d27f6");
+
+        let hash = user_op
+            .hash(EntryPointVersion::V06, &ENTRY_POINT_V06, 1)
+            .unwrap();
+        assert_eq!(
+            hex::encode(hash),
+            "9af12473def3d70aa9645ae95126aec5b07aa6cbb4b78074baf4d877fd655706"
+        );
+
+        let hash = user_op
+            .hash(EntryPointVersion::V07, &ENTRY_POINT_V07, 1)
+            .unwrap();
+        assert_eq!(
+            hex::encode(hash),
+            "16484328ccc2a993a3767e474ac801501c3ce65dde39f196cf41f702d5bb87a1"
+        );
+
+        // the chain and the EntryPoint are part of the hash, so it can not be replayed elsewhere
+        assert_ne!(
+            user_op.hash(EntryPointVersion::V07, &ENTRY_POINT_V07, 8453),
+            Ok(hash)
+        );
+
+        // the signature is not
+        let mut signed = user_op.clone();
+        signed.signature = vec![1; 65];
+        assert_eq!(
+            signed.hash(EntryPointVersion::V07, &ENTRY_POINT_V07, 1),
+            Ok(hash)
+        );
+
+        // v0.7 packs the gas limits in 128 bits
+        let mut too_much_gas = user_op;
+        too_much_gas.call_gas_limit = U256::from(u128::MAX)
+            .checked_add(&U256::from(1u64))
+            .unwrap();
+        assert!(too_much_gas
+            .hash(EntryPointVersion::V06, &ENTRY_POINT_V06, 1)
+            .is_ok());
+        assert_eq!(
+            too_much_gas.hash(EntryPointVersion::V07, &ENTRY_POINT_V07, 1),
+            Err(EvmError::InvalidUserOperation(
+                "callGasLimit does not fit in 128 bits".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_user_operation_calls() {
+        let user_op = user_operation(execute(U256::from(100_000_000_000_000_000u64), vec![]));
+
+        assert_eq!(
+            user_op.describe(1),
+            format!("1) Send 0.1 ETH to {}", TARGET)
+        );
+        assert_eq!(
+            user_op.max_fee(),
+            Some(U256::from(350_000u64 * 30_000_000_000))
+        );
+
+        let approve = encode_function_call(
+            "approve(address,uint256)",
+            &[
+                AbiToken::Address(EvmAddress::parse(SENDER).unwrap()),
+                AbiToken::Uint(U256::from_be_bytes([0xff; 32])),
+            ],
+        )
+        .unwrap();
+
+        let batch = encode_function_call(
+            "executeBatch(address[],uint256[],bytes[])",
+            &[
+                AbiToken::Array(vec![
+                    AbiToken::Address(EvmAddress::parse(TARGET).unwrap()),
+                    AbiToken::Address(EvmAddress::parse(SENDER).unwrap()),
+                ]),
+                AbiToken::Array(vec![]),
+                AbiToken::Array(vec![AbiToken::Bytes(approve), AbiToken::Bytes(vec![])]),
+            ],
+        )
+        .unwrap();
+
+        let user_op = user_operation(batch);
+        let calls = user_op.calls().unwrap();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].value, U256::ZERO);
+
+        let description = user_op.describe(1);
+
+        assert!(description.starts_with(&format!("1) Call approve(address spender = {}", SENDER)));
+        assert!(description.contains(&format!("; 2) Send 0 ETH to {}", SENDER)));
+        assert!(description.contains(". WARNING: unlimited approval"));
+
+        let unknown = user_operation(vec![0x12, 0x34, 0x56, 0x78, 0x00]);
+
+        assert_eq!(unknown.calls(), None);
+        assert_eq!(
+            unknown.describe(1),
+            "call unknown function 0x12345678 of the smart account with 5 bytes of calldata"
+        );
+    }
+
+    #[test]
+    fn test_user_operation_json() {
+        let mut user_op = user_operation(execute(U256::ZERO, vec![]));
+        user_op.init_code = [vec![0xfa; 20], vec![0x01, 0x02]].concat();
+        user_op.signature = vec![0xaa; 65];
+
+        let json = user_op.to_json(EntryPointVersion::V06).unwrap();
+
+        assert_eq!(json["sender"], SENDER);
+        assert_eq!(json["nonce"], "0x1");
+        assert_eq!(json["callGasLimit"], "0x186a0");
+        assert_eq!(json["initCode"], format!("0x{}0102", "fa".repeat(20)));
+        assert_eq!(json["paymasterAndData"], "0x");
+        assert_eq!(json["signature"], format!("0x{}", "aa".repeat(65)));
+
+        let mut paymaster = vec![0xee; 20];
+        paymaster.extend_from_slice(&U256::from(100_000u64).to_be_bytes()[16..]);
+        paymaster.extend_from_slice(&U256::from(50_000u64).to_be_bytes()[16..]);
+        paymaster.push(0xab);
+        user_op.paymaster_and_data = paymaster;
+
+        assert_eq!(
+            user_op.paymaster(),
+            Some(EvmAddress::from_bytes([0xee; 20]))
+        );
+
+        let json = user_op.to_json(EntryPointVersion::V07).unwrap();
+
+        assert_eq!(json.get("initCode"), None);
+        assert_eq!(json["factory"], format!("0x{}", "fa".repeat(20)));
+        assert_eq!(json["factoryData"], "0x0102");
+        assert_eq!(json["paymaster"], format!("0x{}", "ee".repeat(20)));
+        assert_eq!(json["paymasterVerificationGasLimit"], "0x186a0");
+        assert_eq!(json["paymasterPostOpGasLimit"], "0xc350");
+        assert_eq!(json["paymasterData"], "0xab");
+
+        // too short for the gas limits of the paymaster
+        user_op.paymaster_and_data.truncate(30);
+        assert!(user_op.to_json(EntryPointVersion::V07).is_err());
+        assert!(user_op.to_json(EntryPointVersion::V06).is_ok());
+    }
+}
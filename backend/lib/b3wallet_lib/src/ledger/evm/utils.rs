@@ -1,8 +1,13 @@
-use b3_utils::vec_to_hex_string_with_0x;
 use libsecp256k1::PublicKey;
 use tiny_keccak::{Hasher, Keccak};
 
-use super::{error::EvmError, types::PublicKeyTrait};
+use super::{
+    abi::{encode_function_call, AbiToken},
+    address::EvmAddress,
+    error::EvmError,
+    types::PublicKeyTrait,
+    u256::U256,
+};
 
 pub fn get_method_id(method_sig: &str) -> String {
     // Keccak-256 hashing using tiny-keccak
@@ -17,23 +22,35 @@ pub fn get_method_id(method_sig: &str) -> String {
     hex_string
 }
 
-pub fn get_transfer_data(address: &str, amount: u64) -> Result<String, EvmError> {
-    if address.len() != 42 {
-        return Err(EvmError::InvalidAddress(address.to_string()));
-    }
-    let method_sig = "transfer(address,uint256)";
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+
+    output
+}
 
-    let method_id = get_method_id(method_sig);
+/// The EIP-191 hash `personal_sign` signs:
+/// `keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)`.
+pub fn hash_personal_message(message: &[u8]) -> [u8; 32] {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
 
-    let address_64 = format!("{:0>64}", &address[2..]);
+    keccak256(&prefixed)
+}
 
-    let amount_hex = format!("{:02x}", amount);
-    let amount_64 = format!("{:0>64}", amount_hex);
+/// The calldata of an ERC-20 `transfer(address,uint256)`, hex encoded without prefix.
+pub fn get_transfer_data(address: &EvmAddress, amount: U256) -> Result<String, EvmError> {
+    let data = encode_function_call(
+        "transfer(address,uint256)",
+        &[AbiToken::Address(*address), AbiToken::Uint(amount)],
+    )?;
 
-    Ok(method_id + &address_64 + &amount_64)
+    Ok(hex::encode(data))
 }
 
-pub fn create_address_from(public_key: &PublicKey, nonce: u64) -> String {
+pub fn create_address_from(public_key: &PublicKey, nonce: u64) -> EvmAddress {
     let sender = public_key.to_evm_key();
 
     let mut stream = rlp::RlpStream::new_list(2);
@@ -48,10 +65,11 @@ pub fn create_address_from(public_key: &PublicKey, nonce: u64) -> String {
     let mut output = [0u8; 32];
     keccak.finalize(&mut output);
 
-    // Convert the last 20 bytes of hash to hex string
-    let address = vec_to_hex_string_with_0x(&output[12..]);
+    // The address is the last 20 bytes of the hash
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&output[12..]);
 
-    address
+    EvmAddress::from_bytes(address)
 }
 
 /// TODO: Remove this function and use b3_utils::hex_string_to_vec instead
@@ -98,7 +116,7 @@ pub fn vec_u8_to_u64(vec: &Vec<u8>) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use b3_utils::hex_string_to_vec;
+    use b3_utils::{hex_string_to_vec, vec_to_hex_string_with_0x};
 
     use super::*;
 
@@ -113,14 +131,28 @@ mod tests {
         assert_eq!(result, expected_result);
     }
 
+    #[test]
+    fn test_hash_personal_message() {
+        assert_eq!(
+            hex::encode(hash_personal_message(b"Hello World")),
+            "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+        );
+
+        // the length is the byte length in decimal, not a single byte
+        let long = [0xabu8; 300];
+        let mut prefixed = b"\x19Ethereum Signed Message:\n300".to_vec();
+        prefixed.extend_from_slice(&long);
+        assert_eq!(hash_personal_message(&long), keccak256(&prefixed));
+    }
+
     #[test]
     fn test_get_transfer_data() {
-        let address = "0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6";
-        let amount = 12345;
+        let address = EvmAddress::parse("0x7a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad6").unwrap();
+        let amount = U256::from(12345u64);
 
         let expected_result = "a9059cbb0000000000000000000000007a9d2f53fea15e31f0a89d7f5d9e0e82b0b88ad60000000000000000000000000000000000000000000000000000000000003039";
 
-        let result = get_transfer_data(address, amount).unwrap();
+        let result = get_transfer_data(&address, amount).unwrap();
 
         assert_eq!(result, expected_result);
     }
@@ -156,18 +188,18 @@ mod tests {
 
         let nonce = 0;
 
-        let expected_result = "0x0407316cb70d5a7d4642b592e9cb37fa70c56cd1";
+        let expected_result = "0x0407316cB70D5a7D4642B592e9CB37Fa70c56CD1";
 
         let result = create_address_from(&public_key, nonce);
 
-        assert_eq!(result, expected_result);
+        assert_eq!(result.to_string(), expected_result);
 
         let nonce = 1;
 
-        let expected_result = "0xa871c4b1dc678be80af6b5cc8aa4910ad62b11cb";
+        let expected_result = "0xa871C4B1DC678bE80AF6b5cc8AA4910Ad62b11Cb";
 
         let result = create_address_from(&public_key, nonce);
 
-        assert_eq!(result, expected_result);
+        assert_eq!(result.to_string(), expected_result);
     }
 }
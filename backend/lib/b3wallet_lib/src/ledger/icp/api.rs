@@ -14,7 +14,7 @@ use super::{error::IcpError, icp::IcpChain};
 use crate::ledger::{
     chain::ChainTrait,
     error::LedgerError,
-    types::{Balance, IcpPending, PendingEnum, SendResult},
+    types::{Balance, CachedBalance, IcpPending, PendingEnum, SendResult},
 };
 
 #[async_trait]
@@ -104,4 +104,16 @@ impl ChainTrait for IcpChain {
     fn clear_pending(&mut self) {
         self.pendings.clear();
     }
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
 }
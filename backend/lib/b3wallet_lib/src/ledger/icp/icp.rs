@@ -1,4 +1,4 @@
-use crate::ledger::types::IcpPending;
+use crate::ledger::types::{CachedBalance, IcpPending};
 
 use super::error::IcpError;
 use b3_utils::{
@@ -22,6 +22,8 @@ pub struct IcpChain {
     pub fee: ICPToken,
     pub created_at_time: Option<ICPTransferTimestamp>,
     pub pendings: Vec<IcpPending>,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
 }
 
 impl IcpChain {
@@ -32,6 +34,7 @@ impl IcpChain {
             fee: IC_TRANSACTION_FEE_ICP,
             created_at_time: None,
             pendings: Vec::new(),
+            cached_balance: None,
         }
     }
 }
@@ -5,7 +5,7 @@ use super::{
 use crate::ledger::{
     chain::ChainTrait,
     error::LedgerError,
-    types::{Balance, PendingEnum, SendResult},
+    types::{Balance, CachedBalance, PendingEnum, SendResult},
 };
 use async_trait::async_trait;
 use b3_utils::ledger::{currency::TokenAmount, ICRCAccount};
@@ -79,4 +79,16 @@ impl ChainTrait for IcrcChain {
     fn clear_pending(&mut self) {
         self.pendings.clear();
     }
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
 }
@@ -1,12 +1,13 @@
-use crate::ledger::types::IcrcPending;
+use crate::ledger::types::{CachedBalance, IcrcPending};
 use b3_utils::{types::CanisterId, Subaccount};
 use candid::CandidType;
+use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
 use super::{
     error::IcrcError,
     icrc1::ICRC1,
-    types::{ICRCMemo, ICRCMetadata, ICRCTimestamp, ICRCTokens},
+    types::{ICRC1MetadataValue, ICRCMemo, ICRCMetadata, ICRCTimestamp, ICRCTokens},
 };
 
 #[derive(CandidType, Serialize, Clone, Deserialize, PartialEq, Debug)]
@@ -18,6 +19,8 @@ pub struct IcrcChain {
     pub memo: Option<ICRCMemo>,
     pub pendings: Vec<IcrcPending>,
     pub created_at_time: Option<ICRCTimestamp>,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
 }
 
 impl IcrcChain {
@@ -36,6 +39,25 @@ impl IcrcChain {
             fee: Some(fee),
             created_at_time: None,
             pendings: Vec::new(),
+            cached_balance: None,
+        })
+    }
+}
+
+impl IcrcChain {
+    /// Returns the token symbol from the cached `icrc1:symbol` metadata entry.
+    pub fn symbol(&self) -> Option<String> {
+        self.metadata.iter().find_map(|(key, value)| match value {
+            ICRC1MetadataValue::Text(symbol) if key == "icrc1:symbol" => Some(symbol.clone()),
+            _ => None,
+        })
+    }
+
+    /// Returns the token decimals from the cached `icrc1:decimals` metadata entry.
+    pub fn decimals(&self) -> Option<u8> {
+        self.metadata.iter().find_map(|(key, value)| match value {
+            ICRC1MetadataValue::Nat(decimals) if key == "icrc1:decimals" => decimals.0.to_u8(),
+            _ => None,
         })
     }
 }
@@ -1,7 +1,7 @@
 use super::{
     chain::Chain,
     error::LedgerError,
-    types::{AddressMap, Balance, ChainEnum, ChainMap, PendingEnum, SendResult},
+    types::{AddressMap, Balance, CachedBalanceMap, ChainEnum, ChainMap, PendingEnum, SendResult},
 };
 use crate::ledger::chain::ChainTrait;
 use crate::ledger::ecdsa::ChainAddress;
@@ -14,6 +14,8 @@ pub struct Ledger {
     pub public_key: Option<PublicKey>,
     pub subaccount: Subaccount,
     pub chains: ChainMap,
+    #[serde(default)]
+    pub watch_only: bool,
 }
 
 impl Default for Ledger {
@@ -22,6 +24,7 @@ impl Default for Ledger {
             public_key: None,
             chains: ChainMap::default(),
             subaccount: Subaccount::new(Environment::Production, 0),
+            watch_only: false,
         }
     }
 }
@@ -38,21 +41,47 @@ impl From<Subaccount> for Ledger {
             chains,
             subaccount,
             public_key: None,
+            watch_only: false,
         }
     }
 }
 
 impl Ledger {
+    /// A watch-only ledger holding only externally supplied addresses.
+    pub fn new_watch_only(subaccount: Subaccount) -> Self {
+        Ledger {
+            chains: ChainMap::new(),
+            subaccount,
+            public_key: None,
+            watch_only: true,
+        }
+    }
+
     pub fn is_public_key_set(&self) -> bool {
         self.public_key.is_some()
     }
 
+    pub fn is_watch_only(&self) -> bool {
+        self.watch_only
+    }
+
+    /// Reject anything that would need the canister to sign for this ledger.
+    pub fn check_signable(&self) -> Result<(), LedgerError> {
+        if self.watch_only {
+            return Err(LedgerError::WatchOnlyAccount);
+        }
+
+        Ok(())
+    }
+
     pub async fn send(
         &self,
         chain_type: &ChainEnum,
         to: String,
         amount: TokenAmount,
     ) -> Result<SendResult, LedgerError> {
+        self.check_signable()?;
+
         let chain = self.chain(chain_type)?;
 
         chain.send(to, amount).await
@@ -77,6 +106,36 @@ impl Ledger {
         addresses
     }
 
+    pub fn cached_balances(&self) -> CachedBalanceMap {
+        let mut balances = CachedBalanceMap::new();
+
+        for (chain_type, chain) in &self.chains {
+            if let Some(cached) = chain.cached_balance() {
+                balances.insert(chain_type.clone(), cached);
+            }
+        }
+
+        balances
+    }
+
+    /// Drops the cached balances, they may be stale after funds moved.
+    pub fn clear_cached_balances(&mut self) {
+        for chain in self.chains.values_mut() {
+            chain.clear_cached_balance();
+        }
+    }
+
+    /// Returns the cached balance of the chain if it has not expired yet.
+    pub fn fresh_balance(&self, chain_type: &ChainEnum, now: u64) -> Option<Balance> {
+        let cached = self.chains.get(chain_type)?.cached_balance()?;
+
+        if cached.is_expired(now) {
+            None
+        } else {
+            Some(cached.balance)
+        }
+    }
+
     pub fn pendings(&self) -> Vec<PendingEnum> {
         self.chains
             .iter()
@@ -85,6 +144,8 @@ impl Ledger {
     }
 
     pub fn public_key(&self) -> Result<&PublicKey, LedgerError> {
+        self.check_signable()?;
+
         match &self.public_key {
             Some(public_key) => Ok(public_key),
             None => Err(LedgerError::MissingEcdsaPublicKey),
@@ -111,6 +172,8 @@ impl Ledger {
     }
 
     pub async fn new_chain(&self, chain_type: ChainEnum) -> Result<Chain, LedgerError> {
+        self.check_signable()?;
+
         let subaccount = self.subaccount.to_owned();
 
         match chain_type {
@@ -132,9 +195,9 @@ impl Ledger {
                 Ok(btc_chain)
             }
             ChainEnum::EVM(chain_id) => {
-                let eth_address = self.eth_address()?;
+                let ecdsa = self.public_key()?;
 
-                let eth_chain = Chain::new_evm_chain(chain_id, eth_address);
+                let eth_chain = Chain::new_evm_chain(chain_id, subaccount, ecdsa.clone())?;
 
                 Ok(eth_chain)
             }
@@ -147,6 +210,8 @@ impl Ledger {
     }
 
     pub fn set_ecdsa_public_key(&mut self, ecdsa: Vec<u8>) -> Result<(), LedgerError> {
+        self.check_signable()?;
+
         if self.is_public_key_set() {
             return Err(LedgerError::EcdsaPublicKeyAlreadySet);
         }
@@ -159,10 +224,41 @@ impl Ledger {
         Ok(())
     }
 
+    /// Fills in what chains stored by an earlier version of the wallet are missing.
+    pub fn migrate(&mut self) {
+        for chain in self.chains.values_mut() {
+            if let Chain::EvmChain(evm) = chain {
+                if evm.subaccount.is_none() {
+                    evm.subaccount = Some(self.subaccount.clone());
+                }
+
+                if evm.ecdsa_public_key.is_none() {
+                    evm.ecdsa_public_key = self.public_key;
+                }
+            }
+        }
+    }
+
     pub fn insert_chain(&mut self, chain_type: ChainEnum, chain: Chain) {
         self.chains.insert(chain_type, chain);
     }
 
+    pub fn add_watch_address(
+        &mut self,
+        chain_type: ChainEnum,
+        address: String,
+    ) -> Result<(), LedgerError> {
+        if !self.watch_only {
+            return Err(LedgerError::InvalidChain);
+        }
+
+        let chain = Chain::new_watch_only_chain(chain_type.clone(), address)?;
+
+        self.chains.insert(chain_type, chain);
+
+        Ok(())
+    }
+
     pub fn remove_address(&mut self, chain_type: ChainEnum) -> Result<(), LedgerError> {
         if self.chains.remove(&chain_type).is_none() {
             return Err(LedgerError::MissingAddress);
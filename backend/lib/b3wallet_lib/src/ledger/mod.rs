@@ -9,6 +9,7 @@ pub mod icrc;
 pub mod ledger;
 pub mod subaccount;
 pub mod types;
+pub mod watch;
 
 pub mod ecdsa;
 #[cfg(test)]
@@ -5,7 +5,7 @@ use super::types::{
 use super::{config::EcdsaConfig, types::EcdsaKeyId};
 use async_trait::async_trait;
 use b3_utils::constants::MANAGMENT_CANISTER_ID;
-use b3_utils::Subaccount;
+use b3_utils::{types::CanisterId, Subaccount};
 use ic_cdk::api::call::{call, call_with_payment};
 
 #[async_trait]
@@ -14,6 +14,7 @@ pub trait SubaccountEcdsaTrait {
     fn key_id(&self) -> EcdsaKeyId;
     fn key_id_with_cycles_and_path(&self) -> (EcdsaKeyId, u64, Vec<Vec<u8>>);
     async fn ecdsa_public_key(&self) -> Result<Vec<u8>, LedgerError>;
+    async fn ecdsa_public_key_of(&self, canister_id: CanisterId) -> Result<Vec<u8>, LedgerError>;
     async fn sign_with_ecdsa(&self, message_hash: Vec<u8>) -> Result<Vec<u8>, LedgerError>;
 }
 
@@ -56,6 +57,26 @@ impl SubaccountEcdsaTrait for Subaccount {
         Ok(res.public_key)
     }
 
+    /// The public key of the subaccount on another canister.
+    async fn ecdsa_public_key_of(&self, canister_id: CanisterId) -> Result<Vec<u8>, LedgerError> {
+        let key_id = self.key_id();
+
+        let derivation_path = self.derivation_path();
+
+        let request = ECDSAPublicKeyArgs {
+            canister_id: Some(canister_id),
+            derivation_path,
+            key_id,
+        };
+
+        let (res,): (ECDSAPublicKeyResponse,) =
+            call(MANAGMENT_CANISTER_ID, "ecdsa_public_key", (request,))
+                .await
+                .map_err(|e| LedgerError::CallError(e.1))?;
+
+        Ok(res.public_key)
+    }
+
     async fn sign_with_ecdsa(&self, message_hash: Vec<u8>) -> Result<Vec<u8>, LedgerError> {
         let (key_id, cycles, derivation_path) = self.key_id_with_cycles_and_path();
 
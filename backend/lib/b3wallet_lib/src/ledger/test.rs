@@ -8,8 +8,10 @@ mod tests {
             network::BitcoinNetwork,
         },
         chain::{Chain, ChainTrait},
+        error::LedgerError,
+        evm::error::EvmError,
         ledger::Ledger,
-        types::{ChainEnum, ChainMap},
+        types::{Balance, CachedBalance, ChainEnum, ChainMap},
     };
     use b3_utils::{ledger::AccountIdentifier, mocks::id_mock, types::CanisterId, Subaccount};
     use libsecp256k1::PublicKey;
@@ -35,6 +37,7 @@ mod tests {
             public_key: None,
             subaccount: subaccount.clone(),
             chains,
+            watch_only: false,
         };
 
         let ecdsa = [
@@ -51,20 +54,27 @@ mod tests {
 
         println!("identifier: {}", identifier);
 
-        let address = ledger.eth_address().unwrap();
+        let public_key = PublicKey::parse_compressed(&ecdsa).unwrap();
+
+        let eth = Chain::new_evm_chain(1, subaccount.clone(), public_key).unwrap();
 
-        let eth = Chain::new_evm_chain(1, address);
+        assert_eq!(eth.symbol(), "ETH");
+
+        // only the chains of the network registry can be added
+        assert_eq!(
+            Chain::new_evm_chain(424242, subaccount.clone(), public_key).err(),
+            Some(LedgerError::EvmError(EvmError::UnknownNetwork(424242)))
+        );
 
         ledger.insert_chain(ChainEnum::EVM(1), eth);
 
         let eth_address = ledger.chain(&ChainEnum::EVM(1)).unwrap().address();
 
-        assert_eq!(eth_address, "0x7e87f653ec3e9c6cde261e0e2e3e9c14bbe86802");
+        assert_eq!(eth_address, "0x7e87F653eC3e9C6CdE261E0e2E3e9C14BBe86802");
 
         println!("eth_address: {}", eth_address);
 
         assert_eq!(eth_address.len(), 42);
-        let public_key = PublicKey::parse_compressed(&ecdsa).unwrap();
 
         let chain =
             Chain::new_btc_chain(BitcoinNetwork::Regtest, subaccount.clone(), public_key).unwrap();
@@ -175,6 +185,7 @@ mod tests {
             public_key: None,
             subaccount: subaccount.clone(),
             chains,
+            watch_only: false,
         };
 
         let ecdsa = [
@@ -195,7 +206,7 @@ mod tests {
 
         let eth_address = ledger.eth_address().unwrap();
 
-        assert_eq!(eth_address, "0xd0406029f0703f6c04176c16451ce3a324f723c0");
+        assert_eq!(eth_address, "0xd0406029f0703f6c04176C16451Ce3A324f723C0");
 
         println!("eth_address: {}", eth_address);
 
@@ -277,6 +288,7 @@ mod tests {
             public_key: None,
             subaccount: subaccount.clone(),
             chains,
+            watch_only: false,
         };
 
         let ecdsa = [
@@ -297,7 +309,7 @@ mod tests {
 
         let eth_address = ledger.eth_address().unwrap();
 
-        assert_eq!(eth_address, "0x82f3031c7bd2cd7e5c6d4d83584656b873304502");
+        assert_eq!(eth_address, "0x82F3031C7BD2cD7e5c6D4D83584656b873304502");
 
         println!("eth_address: {}", eth_address);
 
@@ -333,4 +345,301 @@ mod tests {
         assert_eq!(btc_p2pkh_add.len(), 34);
         assert_eq!(btc_address.len(), 42);
     }
+
+    #[test]
+    fn test_cached_balance_expiry() {
+        let subaccount = Subaccount::new(b3_utils::Environment::Production, 0);
+
+        let mut ledger = Ledger::from(subaccount);
+
+        assert_eq!(ledger.fresh_balance(&ChainEnum::ICP, 0), None);
+        assert!(ledger.cached_balances().is_empty());
+
+        let cached = CachedBalance::new(Balance::from(100u64), 1_000, 500);
+
+        ledger
+            .chain_mut(ChainEnum::ICP)
+            .unwrap()
+            .set_cached_balance(cached.clone());
+
+        assert_eq!(
+            ledger.fresh_balance(&ChainEnum::ICP, 1_499),
+            Some(Balance::from(100u64))
+        );
+        assert_eq!(ledger.fresh_balance(&ChainEnum::ICP, 1_500), None);
+        assert_eq!(ledger.cached_balances()[&ChainEnum::ICP], cached);
+
+        // a send makes the cached balance stale
+        ledger.clear_cached_balances();
+
+        assert_eq!(ledger.fresh_balance(&ChainEnum::ICP, 1_000), None);
+        assert!(ledger.cached_balances().is_empty());
+    }
+
+    #[test]
+    fn test_watch_only_ledger() {
+        let subaccount = Subaccount::new(b3_utils::Environment::Production, 1);
+
+        let mut ledger = Ledger::new_watch_only(subaccount);
+
+        let btc = ChainEnum::BTC(BitcoinNetwork::Mainnet);
+
+        ledger
+            .add_watch_address(
+                btc.clone(),
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+            )
+            .unwrap();
+
+        ledger
+            .add_watch_address(
+                ChainEnum::EVM(1),
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            ledger.add_watch_address(ChainEnum::EVM(1), "0x1234".to_string()),
+            Err(LedgerError::InvalidWatchAddress("0x1234".to_string()))
+        );
+        // a mixed case address with a wrong EIP-55 checksum
+        assert!(ledger
+            .add_watch_address(
+                ChainEnum::EVM(1),
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".to_string(),
+            )
+            .is_err());
+        assert!(ledger
+            .add_watch_address(btc.clone(), "tb1qinvalid".to_string())
+            .is_err());
+
+        assert_eq!(ledger.address_map().len(), 2);
+        assert_eq!(
+            ledger.chain(&btc).unwrap().address(),
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+        );
+
+        assert_eq!(ledger.public_key(), Err(LedgerError::WatchOnlyAccount));
+        assert_eq!(
+            ledger.set_ecdsa_public_key(vec![2; 33]),
+            Err(LedgerError::WatchOnlyAccount)
+        );
+        assert_eq!(
+            ledger.chain(&btc).unwrap().btc().err(),
+            Some(LedgerError::WatchOnlyAccount)
+        );
+
+        let mut ledger = Ledger::from(Subaccount::new(b3_utils::Environment::Production, 0));
+
+        assert_eq!(
+            ledger.add_watch_address(
+                btc,
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string()
+            ),
+            Err(LedgerError::InvalidChain)
+        );
+    }
+
+    #[test]
+    fn test_migrate_stored_evm_chain() {
+        use crate::ledger::evm::api::EvmChain;
+        use serde::Serialize;
+
+        // the shape an EVM chain was stored with before it kept its signing key
+        // and broadcast its transactions
+        #[derive(Serialize)]
+        struct StoredEvmPending {
+            block_index: u64,
+        }
+
+        #[derive(Serialize)]
+        struct StoredEvmChain {
+            chain_id: u64,
+            address: String,
+            pendings: Vec<StoredEvmPending>,
+        }
+
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(
+            &StoredEvmChain {
+                chain_id: 1,
+                address: "0x7e87f653ec3e9c6cde261e0e2e3e9c14bbe86802".to_string(),
+                pendings: vec![StoredEvmPending { block_index: 7 }],
+            },
+            &mut bytes,
+        )
+        .unwrap();
+
+        let evm: EvmChain = ciborium::de::from_reader(&bytes[..]).unwrap();
+
+        assert_eq!(evm.subaccount, None);
+        assert_eq!(evm.ecdsa_public_key, None);
+        assert_eq!(evm.nonce, 0);
+        assert_eq!(evm.pendings[0].tx_hash, "");
+
+        let subaccount = Subaccount::new(b3_utils::Environment::Production, 1);
+
+        let ecdsa = [
+            3, 94, 114, 171, 76, 217, 209, 126, 120, 169, 209, 205, 226, 55, 21, 238, 204, 199,
+            153, 192, 65, 30, 59, 177, 153, 39, 80, 76, 185, 200, 51, 255, 218,
+        ];
+
+        let mut ledger = Ledger::from(subaccount.clone());
+        ledger.set_ecdsa_public_key(ecdsa.to_vec()).unwrap();
+        ledger.insert_chain(ChainEnum::EVM(1), Chain::EvmChain(evm));
+
+        ledger.migrate();
+
+        let evm = ledger.chain(&ChainEnum::EVM(1)).unwrap().evm().unwrap();
+
+        assert_eq!(evm.subaccount, Some(subaccount));
+        // a pending without a transaction hash stays visible, but can not be checked
+        assert_eq!(evm.pendings.len(), 1);
+        assert_eq!(
+            futures::executor::block_on(evm.check_pending(0)),
+            Err(LedgerError::EvmError(EvmError::MissingTransactionHash(0)))
+        );
+        assert_eq!(
+            evm.ecdsa_public_key,
+            Some(PublicKey::parse_compressed(&ecdsa).unwrap())
+        );
+        assert_eq!(
+            evm.address.to_string(),
+            "0x7e87F653eC3e9C6CdE261E0e2E3e9C14BBe86802"
+        );
+    }
+
+    #[test]
+    fn test_child_of_incoming_transaction() {
+        use crate::ledger::btc::{
+            error::BitcoinError,
+            tx::parse_txid,
+            types::{OutPoint, Utxo},
+        };
+        use ic_cdk::api::management_canister::bitcoin::{
+            GetUtxosResponse, Outpoint, Utxo as CanisterUtxo,
+        };
+
+        let ecdsa = [
+            3, 94, 114, 171, 76, 217, 209, 126, 120, 169, 209, 205, 226, 55, 21, 238, 204, 199,
+            153, 192, 65, 30, 59, 177, 153, 39, 80, 76, 185, 200, 51, 255, 218,
+        ];
+
+        let public_key = PublicKey::parse_compressed(&ecdsa).unwrap();
+        let subaccount = Subaccount::new(b3_utils::Environment::Production, 0);
+
+        let mut btc = Chain::new_btc_chain(BitcoinNetwork::Regtest, subaccount, public_key)
+            .unwrap()
+            .btc()
+            .unwrap();
+
+        let txid = "ab".repeat(32);
+        let outpoint = OutPoint::new(parse_txid(&txid).unwrap().to_vec(), 1);
+
+        // the Bitcoin canister does not return the outputs of transactions in the mempool
+        let mut utxos = GetUtxosResponse {
+            utxos: vec![],
+            tip_block_hash: vec![],
+            tip_height: 0,
+            next_page: None,
+        };
+
+        assert_eq!(
+            btc.parent_output(&txid, 1, &utxos, None),
+            Err(BitcoinError::OutputNotFound(txid.clone(), 1))
+        );
+        assert_eq!(
+            btc.parent_output(&txid, 1, &utxos, Some(50_000)),
+            Ok(Utxo {
+                outpoint: outpoint.clone(),
+                value: 50_000,
+                height: 0,
+            })
+        );
+
+        let (tx, child) = btc
+            .build_child(&txid, 1, &utxos, Some(50_000), 200, 200, 20_000, 0)
+            .unwrap();
+
+        assert_eq!(tx.inputs[0].previous_output, outpoint);
+        assert_eq!(tx.inputs[0].value, 50_000);
+        assert_eq!(child.change, 50_000 - child.fee);
+        assert!(btc.is_locked(&outpoint, 0));
+
+        // the value seen by the Bitcoin canister wins over the given one
+        utxos.utxos.push(CanisterUtxo {
+            outpoint: Outpoint {
+                txid: outpoint.txid.clone(),
+                vout: 1,
+            },
+            value: 40_000,
+            height: 7,
+        });
+
+        assert_eq!(
+            btc.parent_output(&txid, 1, &utxos, Some(50_000))
+                .map(|utxo| utxo.value),
+            Ok(40_000)
+        );
+    }
+
+    #[test]
+    fn test_sent_transaction_confirms_by_its_outputs() {
+        use crate::ledger::btc::{
+            tx::parse_txid,
+            types::{BtcSentTransaction, OutPoint, Utxo},
+            utils::has_output,
+        };
+        use ic_cdk::api::management_canister::bitcoin::{
+            GetUtxosResponse, Outpoint, Utxo as CanisterUtxo,
+        };
+
+        let parent = parse_txid(&"ab".repeat(32)).unwrap();
+        let txid = parse_txid(&"cd".repeat(32)).unwrap();
+
+        // a child spending the unconfirmed output of its parent, paying all to its change
+        let child = BtcSentTransaction {
+            inputs: vec![Utxo {
+                outpoint: OutPoint::new(parent.to_vec(), 1),
+                value: 50_000,
+                height: 0,
+            }],
+            outputs: vec![],
+            change: 46_000,
+            fee: 4_000,
+            vsize: 110,
+        };
+
+        assert_eq!(child.outputs_to("own"), vec![("own".to_string(), 0)]);
+
+        // the parent output is not listed either, which does not make the child confirmed
+        let mut utxos = GetUtxosResponse {
+            utxos: vec![],
+            tip_block_hash: vec![],
+            tip_height: 0,
+            next_page: None,
+        };
+
+        assert!(!has_output(&utxos, &txid, 0));
+
+        utxos.utxos.push(CanisterUtxo {
+            outpoint: Outpoint {
+                txid: txid.to_vec(),
+                vout: 0,
+            },
+            value: 46_000,
+            height: 7,
+        });
+
+        assert!(has_output(&utxos, &txid, 0));
+        assert!(!has_output(&utxos, &parent, 0));
+
+        let transfer = BtcSentTransaction {
+            outputs: vec![("to".to_string(), 20_000)],
+            change: 0,
+            ..child
+        };
+
+        assert_eq!(transfer.outputs_to("own"), vec![("to".to_string(), 0)]);
+    }
 }
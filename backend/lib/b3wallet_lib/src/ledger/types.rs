@@ -1,5 +1,8 @@
 use super::{
-    btc::network::BitcoinNetwork, btc::types::BtcTxId, chain::Chain, icrc::types::TxIndex,
+    btc::network::BitcoinNetwork,
+    btc::types::{BtcSentTransaction, BtcTxId},
+    chain::Chain,
+    icrc::types::TxIndex,
 };
 use b3_utils::types::CanisterId;
 use candid::{CandidType, Nat};
@@ -16,10 +19,43 @@ pub type ChainMap = BTreeMap<ChainEnum, Chain>;
 
 pub type AddressMap = BTreeMap<ChainEnum, String>;
 
+pub type CachedBalanceMap = BTreeMap<ChainEnum, CachedBalance>;
+
+/// The default time a cached balance is considered fresh, in nanoseconds (5 minutes).
+pub const DEFAULT_BALANCE_TTL: u64 = 5 * 60 * 1_000_000_000;
+
+/// A balance fetched from the chain, kept to avoid paying for repeated calls.
+/// `last_updated` and `ttl` are in nanoseconds.
+#[derive(CandidType, PartialEq, Serialize, Deserialize, Clone, Debug)]
+pub struct CachedBalance {
+    pub balance: Balance,
+    pub last_updated: u64,
+    pub ttl: u64,
+}
+
+impl CachedBalance {
+    pub fn new(balance: Balance, last_updated: u64, ttl: u64) -> Self {
+        CachedBalance {
+            balance,
+            last_updated,
+            ttl,
+        }
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_updated) >= self.ttl
+    }
+}
+
 #[derive(CandidType, PartialEq, Serialize, Eq, PartialOrd, Ord, Deserialize, Clone, Debug)]
 pub struct BtcPending {
     pub txid: BtcTxId,
+    /// The ICRC account credited by the ckBTC minter for swaps,
+    /// the sending address for transfers.
     pub account: String,
+    /// The sent transaction, none for the swaps to ckBTC kept before it was.
+    #[serde(default)]
+    pub transaction: Option<BtcSentTransaction>,
 }
 
 #[derive(CandidType, PartialEq, Serialize, Eq, PartialOrd, Ord, Deserialize, Clone, Debug)]
@@ -30,7 +66,10 @@ pub struct IcpPending {
 
 #[derive(CandidType, PartialEq, Serialize, Eq, PartialOrd, Ord, Deserialize, Clone, Debug)]
 pub struct EvmPending {
-    pub block_index: u64,
+    /// Empty for the pendings stored before EVM transactions were broadcast, which only
+    /// had a block index. They are kept so they show up, but can only be removed.
+    #[serde(default)]
+    pub tx_hash: String,
 }
 
 #[derive(CandidType, PartialEq, Serialize, Eq, PartialOrd, Ord, Deserialize, Clone, Debug)]
@@ -70,11 +109,15 @@ impl PendingEnum {
     }
 
     pub fn new_btc(txid: BtcTxId, account: String) -> Self {
-        PendingEnum::BTC(BtcPending { txid, account })
+        PendingEnum::BTC(BtcPending {
+            txid,
+            account,
+            transaction: None,
+        })
     }
 
-    pub fn new_evm(block_index: u64) -> Self {
-        PendingEnum::EVM(EvmPending { block_index })
+    pub fn new_evm(tx_hash: String) -> Self {
+        PendingEnum::EVM(EvmPending { tx_hash })
     }
 
     pub fn new_icp(block_index: u64, canister_id: String) -> Self {
@@ -137,7 +180,7 @@ pub enum SendResult {
     CKBTC(TxIndex),
     ICRC(TxIndex),
     BTC(BtcTxId),
-    EVM,
+    EVM(String),
 }
 
 impl fmt::Display for SendResult {
@@ -147,7 +190,7 @@ impl fmt::Display for SendResult {
             SendResult::CKBTC(tx_index) => write!(f, "CKBTC({})", tx_index),
             SendResult::ICRC(tx_index) => write!(f, "ICRC({})", tx_index),
             SendResult::BTC(txid) => write!(f, "BTC({})", txid),
-            SendResult::EVM => write!(f, "EVM"),
+            SendResult::EVM(tx_hash) => write!(f, "EVM({})", tx_hash),
         }
     }
 }
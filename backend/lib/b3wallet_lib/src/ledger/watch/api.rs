@@ -0,0 +1,108 @@
+use crate::ledger::{
+    btc::network::BitcoinNetwork,
+    chain::ChainTrait,
+    error::LedgerError,
+    evm::rpc::{EvmRpc, RpcService},
+    icp::error::IcpError,
+    icrc::icrc1::ICRC1,
+    types::{Balance, CachedBalance, ChainEnum, PendingEnum, SendResult},
+};
+use async_trait::async_trait;
+use b3_utils::{
+    constants::{CKBTC_LEDGER_CANISTER_MAINNET, CKBTC_LEDGER_CANISTER_TESTNET, LEDGER_CANISTER_ID},
+    ledger::{
+        currency::{ICPToken, TokenAmount},
+        AccountIdentifier, ICPAccountBalanceArgs, ICRCAccount,
+    },
+};
+use std::str::FromStr;
+
+use super::watch::WatchOnlyChain;
+
+#[async_trait]
+impl ChainTrait for WatchOnlyChain {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn balance(&self) -> Result<Balance, LedgerError> {
+        match &self.chain {
+            ChainEnum::ICP => {
+                let account = AccountIdentifier::from_str(&self.address)
+                    .map_err(|e| LedgerError::InvalidWatchAddress(e.to_string()))?;
+
+                let args = ICPAccountBalanceArgs { account };
+
+                let (res,): (ICPToken,) =
+                    ic_cdk::call(LEDGER_CANISTER_ID, "account_balance", (args,))
+                        .await
+                        .map_err(|e| LedgerError::IcpError(IcpError::CallError(e.1)))?;
+
+                Ok(res.e8s().into())
+            }
+            ChainEnum::ICRC(canister_id) => {
+                let account =
+                    ICRCAccount::from_str(&self.address).map_err(LedgerError::ICRCAccountError)?;
+
+                ICRC1(*canister_id)
+                    .balance_of(account)
+                    .await
+                    .map_err(LedgerError::IcrcError)
+            }
+            ChainEnum::CKBTC(btc_network) => {
+                let account =
+                    ICRCAccount::from_str(&self.address).map_err(LedgerError::ICRCAccountError)?;
+
+                let ledger = match btc_network {
+                    BitcoinNetwork::Testnet => ICRC1(CKBTC_LEDGER_CANISTER_TESTNET),
+                    BitcoinNetwork::Mainnet => ICRC1(CKBTC_LEDGER_CANISTER_MAINNET),
+                    BitcoinNetwork::Regtest => ICRC1(CKBTC_LEDGER_CANISTER_MAINNET),
+                };
+
+                ledger
+                    .balance_of(account)
+                    .await
+                    .map_err(LedgerError::IcrcError)
+            }
+            ChainEnum::BTC(btc_network) => btc_network
+                .get_balance(self.address.clone(), None)
+                .await
+                .map_err(LedgerError::BitcoinError),
+            ChainEnum::EVM(chain_id) => EvmRpc::new(RpcService::Chain(*chain_id))
+                .get_balance(&self.address)
+                .await
+                .map_err(LedgerError::EvmError),
+        }
+    }
+
+    async fn send(&self, _to: String, _amount: TokenAmount) -> Result<SendResult, LedgerError> {
+        Err(LedgerError::WatchOnlyAccount)
+    }
+
+    /// A watched address has no pendings, see [WatchOnlyChain].
+    async fn check_pending(&self, pending_index: usize) -> Result<(), LedgerError> {
+        Err(LedgerError::PendingIndexError(pending_index))
+    }
+
+    fn pendings(&self) -> Vec<PendingEnum> {
+        Vec::new()
+    }
+
+    fn add_pending(&mut self, _pending: PendingEnum) {}
+
+    fn remove_pending(&mut self, _pending_index: usize) {}
+
+    fn clear_pending(&mut self) {}
+
+    fn cached_balance(&self) -> Option<CachedBalance> {
+        self.cached_balance.clone()
+    }
+
+    fn set_cached_balance(&mut self, cached_balance: CachedBalance) {
+        self.cached_balance = Some(cached_balance);
+    }
+
+    fn clear_cached_balance(&mut self) {
+        self.cached_balance = None;
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ledger::{
+    btc::address::BitcoinAddress,
+    error::LedgerError,
+    evm::address::EvmAddress,
+    types::{CachedBalance, ChainEnum},
+};
+use b3_utils::ledger::{AccountIdentifier, ICRCAccount};
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A chain holding an externally supplied address.
+/// The canister holds no key for it, so anything that would sign is rejected.
+///
+/// Only the balance is followed, the wallet sends nothing from a watched address so it
+/// has no pendings. Transaction history is not followed either:
+/// - ICP and ICRC (ckBTC included): the index canisters of the ledgers answer
+///   `get_account_identifier_transactions` and `get_account_transactions`, the wallet
+///   does not query them yet.
+/// - BTC: the Bitcoin canister only returns the UTXOs and the balance of an address.
+/// - EVM: JSON-RPC has no history query by account, it takes an indexer.
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct WatchOnlyChain {
+    pub chain: ChainEnum,
+    pub address: String,
+    #[serde(default)]
+    pub cached_balance: Option<CachedBalance>,
+}
+
+impl WatchOnlyChain {
+    /// Check the address format for the given chain before it is stored.
+    pub fn new(chain: ChainEnum, address: String) -> Result<Self, LedgerError> {
+        match chain {
+            ChainEnum::ICP => {
+                AccountIdentifier::from_str(&address)
+                    .map_err(|e| LedgerError::InvalidWatchAddress(e.to_string()))?;
+            }
+            ChainEnum::ICRC(_) | ChainEnum::CKBTC(_) => {
+                ICRCAccount::from_str(&address)
+                    .map_err(|e| LedgerError::InvalidWatchAddress(e.to_string()))?;
+            }
+            ChainEnum::BTC(btc_network) => {
+                BitcoinAddress::parse(&address, btc_network)
+                    .map_err(|e| LedgerError::InvalidWatchAddress(e.to_string()))?;
+            }
+            ChainEnum::EVM(_) => {
+                EvmAddress::parse(&address)
+                    .map_err(|_| LedgerError::InvalidWatchAddress(address.clone()))?;
+            }
+        }
+
+        Ok(WatchOnlyChain {
+            chain,
+            address,
+            cached_balance: None,
+        })
+    }
+}
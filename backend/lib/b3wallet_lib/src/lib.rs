@@ -1,7 +1,13 @@
 pub mod account;
+pub mod balance;
+pub mod btc_transfer;
+pub mod bundle;
 pub mod error;
+pub mod evm_transfer;
 pub mod ledger;
 pub mod nonces;
+pub mod portfolio;
+pub mod send;
 pub mod setting;
 pub mod state;
 pub mod store;
@@ -0,0 +1,191 @@
+use crate::{
+    ledger::{
+        chain::{Chain, ChainTrait},
+        types::{Balance, ChainEnum},
+    },
+    types::{AccountId, WalletAccountMap},
+};
+use candid::CandidType;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+use b3_utils::mocks::time_mock as ic_timestamp;
+#[cfg(not(test))]
+use ic_cdk::api::time as ic_timestamp;
+
+pub type ChainTotals = BTreeMap<ChainEnum, Balance>;
+
+pub type TokenTotals = BTreeMap<String, TokenTotal>;
+
+/// The balance of a single chain of a single account.
+/// If the balance could not be fetched, `balance` is `None` and `error` holds the reason.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChainBalance {
+    pub account_id: AccountId,
+    pub chain: ChainEnum,
+    pub symbol: String,
+    pub decimals: u8,
+    pub balance: Option<Balance>,
+    pub error: Option<String>,
+}
+
+/// The sum of all balances holding the same token symbol.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TokenTotal {
+    pub symbol: String,
+    pub decimals: u8,
+    pub amount: Balance,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Portfolio {
+    pub balances: Vec<ChainBalance>,
+    pub chains: ChainTotals,
+    pub tokens: TokenTotals,
+    pub updated_at: u64,
+}
+
+impl ChainBalance {
+    /// Fetch the balance of the given chain.
+    /// A failing call is recorded in the result instead of being returned as an error.
+    pub async fn fetch(account_id: AccountId, chain_enum: ChainEnum, chain: &Chain) -> Self {
+        let result = chain.balance().await;
+
+        let (balance, error) = match result {
+            Ok(balance) => (Some(balance), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+        ChainBalance {
+            account_id,
+            chain: chain_enum,
+            symbol: chain.symbol(),
+            decimals: chain.decimals(),
+            balance,
+            error,
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+impl Portfolio {
+    /// Fetch the balances of every chain of every account concurrently.
+    pub async fn fetch(accounts: &WalletAccountMap) -> Self {
+        let calls = accounts.iter().flat_map(|(account_id, account)| {
+            account
+                .ledger()
+                .chains
+                .iter()
+                .map(move |(chain_enum, chain)| {
+                    ChainBalance::fetch(account_id.clone(), chain_enum.clone(), chain)
+                })
+        });
+
+        let balances = join_all(calls).await;
+
+        Portfolio::new(balances, ic_timestamp())
+    }
+
+    /// Aggregates the balances per chain and per token symbol.
+    /// Failed balances are kept in the list but left out of the totals.
+    pub fn new(balances: Vec<ChainBalance>, updated_at: u64) -> Self {
+        let mut chains = ChainTotals::new();
+        let mut tokens = TokenTotals::new();
+
+        for chain_balance in balances.iter() {
+            let balance = match &chain_balance.balance {
+                Some(balance) => balance.clone(),
+                None => continue,
+            };
+
+            *chains
+                .entry(chain_balance.chain.clone())
+                .or_insert_with(|| Balance::from(0u8)) += balance.clone();
+
+            tokens
+                .entry(chain_balance.symbol.clone())
+                .or_insert_with(|| TokenTotal {
+                    symbol: chain_balance.symbol.clone(),
+                    decimals: chain_balance.decimals,
+                    amount: Balance::from(0u8),
+                })
+                .amount += balance;
+        }
+
+        Portfolio {
+            balances,
+            chains,
+            tokens,
+            updated_at,
+        }
+    }
+
+    pub fn failed(&self) -> Vec<&ChainBalance> {
+        self.balances.iter().filter(|b| b.is_failed()).collect()
+    }
+
+    pub fn is_partial(&self) -> bool {
+        self.balances.iter().any(|b| b.is_failed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::btc::network::BitcoinNetwork;
+    use candid::Principal;
+
+    fn chain_balance(
+        account_id: &str,
+        chain: ChainEnum,
+        symbol: &str,
+        balance: Option<u64>,
+    ) -> ChainBalance {
+        ChainBalance {
+            account_id: account_id.to_string(),
+            chain,
+            symbol: symbol.to_string(),
+            decimals: 8,
+            error: balance.map_or(Some("call failed".to_string()), |_| None),
+            balance: balance.map(Balance::from),
+        }
+    }
+
+    #[test]
+    fn test_portfolio_totals() {
+        let ckbtc = ChainEnum::CKBTC(BitcoinNetwork::Mainnet);
+        let icrc = ChainEnum::ICRC(Principal::from_slice(&[1; 10]));
+
+        let balances = vec![
+            chain_balance("-default", ChainEnum::ICP, "ICP", Some(100)),
+            chain_balance("account_1", ChainEnum::ICP, "ICP", Some(50)),
+            chain_balance("-default", ckbtc.clone(), "ckBTC", Some(10)),
+            chain_balance("account_1", icrc.clone(), "ckBTC", Some(5)),
+            chain_balance("account_1", ChainEnum::BTC(BitcoinNetwork::Mainnet), "BTC", None),
+        ];
+
+        let portfolio = Portfolio::new(balances, 1);
+
+        assert_eq!(portfolio.balances.len(), 5);
+        assert_eq!(portfolio.updated_at, 1);
+
+        assert_eq!(portfolio.chains[&ChainEnum::ICP], Balance::from(150u64));
+        assert_eq!(portfolio.chains[&ckbtc], Balance::from(10u64));
+        assert_eq!(portfolio.chains[&icrc], Balance::from(5u64));
+        assert!(!portfolio
+            .chains
+            .contains_key(&ChainEnum::BTC(BitcoinNetwork::Mainnet)));
+
+        assert_eq!(portfolio.tokens["ICP"].amount, Balance::from(150u64));
+        assert_eq!(portfolio.tokens["ckBTC"].amount, Balance::from(15u64));
+        assert!(!portfolio.tokens.contains_key("BTC"));
+
+        assert!(portfolio.is_partial());
+        assert_eq!(portfolio.failed().len(), 1);
+    }
+}
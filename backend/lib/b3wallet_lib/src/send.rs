@@ -0,0 +1,60 @@
+use crate::{
+    btc_transfer::btc_transfer,
+    error::WalletError,
+    evm_transfer::evm_transfer,
+    ledger::{
+        btc::coin_selection::CoinSelectionStrategy,
+        error::LedgerError,
+        evm::{gas::EvmGasConfig, u256::U256},
+        types::{ChainEnum, SendResult},
+    },
+    store::with_ledger,
+    types::AccountId,
+};
+use b3_utils::ledger::currency::TokenAmount;
+
+/// Sends `amount` from the chain of the account to `to`.
+/// Bitcoin goes through [btc_transfer], which locks the inputs while the transaction
+/// is in flight, and EVM tokens through [evm_transfer], which reserves the nonce.
+/// The other chains send from a copy of the ledger.
+/// The EVM fees are checked against `gas_config`, the one of the chain when none is given.
+pub async fn send(
+    account_id: &AccountId,
+    chain_type: &ChainEnum,
+    to: String,
+    amount: TokenAmount,
+    gas_config: Option<&EvmGasConfig>,
+) -> Result<SendResult, WalletError> {
+    let ledger = with_ledger(account_id, |ledger| ledger.clone())?;
+
+    match chain_type {
+        ChainEnum::BTC(network) => {
+            ledger.check_signable()?;
+
+            let amount = amount
+                .to_satoshi()
+                .map_err(|err| LedgerError::InvalidAmountError(err.to_string()))?;
+
+            let (txid, _) = btc_transfer(
+                account_id,
+                *network,
+                vec![(to, amount)],
+                &CoinSelectionStrategy::default(),
+            )
+            .await?;
+
+            Ok(SendResult::BTC(txid))
+        }
+        ChainEnum::EVM(chain_id) => {
+            ledger.check_signable()?;
+
+            let value = U256::try_from(amount.to_nat())
+                .map_err(|_| LedgerError::InvalidAmountError(amount.to_string()))?;
+
+            let tx_hash = evm_transfer(account_id, *chain_id, to, value, gas_config).await?;
+
+            Ok(SendResult::EVM(tx_hash))
+        }
+        _ => Ok(ledger.send(chain_type, to, amount).await?),
+    }
+}
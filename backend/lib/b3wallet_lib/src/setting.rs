@@ -1,6 +1,18 @@
 use std::collections::HashMap;
 
-use crate::error::WalletError;
+use crate::{
+    error::WalletError,
+    ledger::{
+        evm::{
+            address::EvmAddress,
+            create2::DETERMINISTIC_DEPLOYMENT_PROXY,
+            error::EvmError,
+            gas::EvmGasConfig,
+            network::{default_evm_networks, EvmNetwork},
+        },
+        types::{ChainId, DEFAULT_BALANCE_TTL},
+    },
+};
 use b3_utils::{
     api::Management,
     ledger::{Metadata, Value},
@@ -21,6 +33,16 @@ pub struct WalletSettings {
     pub freezing_threshold: Option<Nat>,
     pub reserved_cycles_limit: Option<Nat>,
     pub initialised: bool,
+    #[serde(default)]
+    pub balance_ttl: Option<u64>,
+    #[serde(default)]
+    pub balance_refresh_interval: Option<u64>,
+    #[serde(default)]
+    pub evm_gas: HashMap<ChainId, EvmGasConfig>,
+    #[serde(default)]
+    pub evm_create2_factories: HashMap<ChainId, EvmAddress>,
+    #[serde(default = "default_evm_networks")]
+    pub evm_networks: HashMap<ChainId, EvmNetwork>,
 }
 
 impl Default for WalletSettings {
@@ -33,6 +55,11 @@ impl Default for WalletSettings {
             freezing_threshold: None,
             reserved_cycles_limit: None,
             initialised: false,
+            balance_ttl: None,
+            balance_refresh_interval: None,
+            evm_gas: HashMap::new(),
+            evm_create2_factories: HashMap::new(),
+            evm_networks: default_evm_networks(),
         }
     }
 }
@@ -50,6 +77,75 @@ impl WalletSettings {
         &self.metadata
     }
 
+    /// How long a fetched balance is served from the cache, in nanoseconds.
+    pub fn balance_ttl(&self) -> u64 {
+        self.balance_ttl.unwrap_or(DEFAULT_BALANCE_TTL)
+    }
+
+    pub fn set_balance_ttl(&mut self, ttl: Option<u64>) {
+        self.balance_ttl = ttl;
+    }
+
+    /// The interval of the watched balances refresh timer, in seconds.
+    /// `None` means the timer is stopped.
+    pub fn balance_refresh_interval(&self) -> Option<u64> {
+        self.balance_refresh_interval
+    }
+
+    pub fn set_balance_refresh_interval(&mut self, interval: Option<u64>) {
+        self.balance_refresh_interval = interval;
+    }
+
+    /// The gas defaults and fee caps of the EVM chain, empty if never configured.
+    pub fn evm_gas_config(&self, chain_id: ChainId) -> EvmGasConfig {
+        self.evm_gas.get(&chain_id).cloned().unwrap_or_default()
+    }
+
+    /// `None` removes the configuration of the chain.
+    pub fn set_evm_gas_config(&mut self, chain_id: ChainId, config: Option<EvmGasConfig>) {
+        match config {
+            Some(config) => self.evm_gas.insert(chain_id, config),
+            None => self.evm_gas.remove(&chain_id),
+        };
+    }
+
+    /// The factory CREATE2 deployments on the EVM chain go through,
+    /// the deterministic deployment proxy if never configured.
+    pub fn evm_create2_factory(&self, chain_id: ChainId) -> EvmAddress {
+        self.evm_create2_factories
+            .get(&chain_id)
+            .cloned()
+            .unwrap_or(DETERMINISTIC_DEPLOYMENT_PROXY)
+    }
+
+    /// `None` goes back to the deterministic deployment proxy.
+    pub fn set_evm_create2_factory(&mut self, chain_id: ChainId, factory: Option<EvmAddress>) {
+        match factory {
+            Some(factory) => self.evm_create2_factories.insert(chain_id, factory),
+            None => self.evm_create2_factories.remove(&chain_id),
+        };
+    }
+
+    pub fn evm_network(&self, chain_id: ChainId) -> Result<EvmNetwork, EvmError> {
+        self.evm_networks
+            .get(&chain_id)
+            .cloned()
+            .ok_or(EvmError::UnknownNetwork(chain_id))
+    }
+
+    pub fn evm_networks(&self) -> &HashMap<ChainId, EvmNetwork> {
+        &self.evm_networks
+    }
+
+    /// `None` removes the network from the registry, the chains already added keep working
+    /// with the defaults: the providers of the EVM RPC canister and no explorer.
+    pub fn set_evm_network(&mut self, chain_id: ChainId, network: Option<EvmNetwork>) {
+        match network {
+            Some(network) => self.evm_networks.insert(chain_id, network),
+            None => self.evm_networks.remove(&chain_id),
+        };
+    }
+
     pub fn controllers(&self) -> &AppControllerMap {
         &self.controllers
     }
@@ -1,12 +1,15 @@
+use crate::bundle::{AccountBundlePayload, ImportReport};
 use crate::error::WalletError;
 use crate::ledger::ledger::Ledger;
 use crate::nonces::NonceTrait;
+use crate::portfolio::Portfolio;
 use crate::setting::WalletSettings;
 use crate::types::{WalletAccountMap, WalletAccountView};
 use crate::{account::WalletAccount, types::AccountId};
 use b3_utils::api::AppAccountsNonce;
 use b3_utils::memory::types::{Bound, Storable};
 use b3_utils::nonce::Nonce;
+use b3_utils::types::CanisterId;
 use b3_utils::Environment;
 use b3_utils::Subaccount;
 use ciborium::de::from_reader;
@@ -21,6 +24,8 @@ pub struct WalletState {
     pub nonces: AppAccountsNonce,
     pub settings: WalletSettings,
     pub accounts: WalletAccountMap,
+    #[serde(default)]
+    pub portfolio: Option<Portfolio>,
 }
 
 impl Storable for WalletState {
@@ -33,7 +38,11 @@ impl Storable for WalletState {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        from_reader(&mut Cursor::new(&bytes)).unwrap()
+        let mut state: WalletState = from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+        state.migrate();
+
+        state
     }
 }
 
@@ -51,6 +60,14 @@ impl WalletState {
             nonces: AppAccountsNonce::new(),
             settings: WalletSettings::default(),
             accounts,
+            portfolio: None,
+        }
+    }
+
+    /// Brings the accounts stored by an earlier version of the wallet up to date.
+    pub fn migrate(&mut self) {
+        for account in self.accounts.values_mut() {
+            account.ledger_mut().migrate();
         }
     }
 
@@ -189,9 +206,82 @@ impl WalletState {
         Ok(())
     }
 
+    // Bundle Functions
+
+    pub fn export_accounts(
+        &self,
+        canister_id: CanisterId,
+        exported_at: u64,
+    ) -> AccountBundlePayload {
+        AccountBundlePayload::new(
+            canister_id,
+            exported_at,
+            self.nonces.clone(),
+            self.accounts.clone(),
+        )
+    }
+
+    /// Import the accounts of a bundle, skipping the ones that already exist.
+    /// The account counters are raised to the exported ones so new accounts don't collide.
+    pub fn import_accounts(&mut self, payload: AccountBundlePayload) -> ImportReport {
+        let mut imported = Vec::new();
+        let mut skipped = Vec::new();
+        let mut address_changes = Vec::new();
+
+        for (id, account) in payload.accounts.iter() {
+            if self.accounts.contains_key(id) {
+                skipped.push(id.clone());
+                continue;
+            }
+
+            let account = payload.rebuild_account(account);
+
+            address_changes.extend(payload.address_changes(&account));
+
+            self.accounts.insert(id.clone(), account);
+
+            imported.push(id.clone());
+        }
+
+        let nonces = &payload.nonces;
+
+        if nonces.development.get() > self.nonces.development.get() {
+            self.nonces.development = nonces.development;
+        }
+
+        if nonces.production.get() > self.nonces.production.get() {
+            self.nonces.production = nonces.production;
+        }
+
+        if nonces.staging.get() > self.nonces.staging.get() {
+            self.nonces.staging = nonces.staging;
+        }
+
+        self.portfolio = None;
+
+        ImportReport {
+            source_canister_id: payload.canister_id,
+            exported_at: payload.exported_at,
+            imported,
+            skipped,
+            address_changes,
+        }
+    }
+
+    // Portfolio Functions
+
+    pub fn portfolio(&self) -> Option<&Portfolio> {
+        self.portfolio.as_ref()
+    }
+
+    pub fn set_portfolio(&mut self, portfolio: Portfolio) {
+        self.portfolio = Some(portfolio);
+    }
+
     pub fn reset_accounts(&mut self) {
         self.accounts.clear();
         self.nonces.reset();
+        self.portfolio = None;
 
         self.init_accounts();
     }
@@ -2,7 +2,18 @@
 mod test {
     use b3_utils::{nonce::Nonce, Environment};
 
-    use crate::{account::WalletAccount, nonces::NonceTrait, state::WalletState};
+    use crate::{
+        account::WalletAccount,
+        error::WalletError,
+        ledger::{
+            btc::network::BitcoinNetwork,
+            chain::ChainTrait,
+            types::{ChainEnum, PendingEnum},
+        },
+        nonces::NonceTrait,
+        state::WalletState,
+    };
+    use futures::executor::block_on;
 
     #[test]
     fn test_init_wallet() {
@@ -162,4 +173,75 @@ mod test {
 
         assert_eq!(nonce, Nonce(1));
     }
+
+    #[test]
+    fn test_account_lifecycle() {
+        let mut state = WalletState::new();
+
+        let subaccount = state.new_subaccount(None);
+
+        state.insert_account(WalletAccount::from(subaccount), None);
+
+        let account = state.account_mut(&"account_1".to_owned()).unwrap();
+
+        account.hide();
+        account.archive();
+
+        assert!(account.is_hidden());
+        assert!(account.is_archived());
+
+        account.unhide();
+
+        assert!(!account.is_hidden());
+        assert!(account.is_archived());
+        assert!(account.view().archived);
+
+        account.unarchive();
+
+        assert!(!account.is_archived());
+
+        account
+            .ledger_mut()
+            .chain_mut(ChainEnum::ICP)
+            .unwrap()
+            .add_pending(PendingEnum::new_icp(
+                1,
+                "bkyz2-fmaaa-aaaaa-qaaaq-cai".to_owned(),
+            ));
+
+        let result = block_on(account.check_removable());
+
+        assert_eq!(result, Err(WalletError::AccountHasPendings(ChainEnum::ICP)));
+    }
+
+    #[test]
+    fn test_watch_only_account_is_removable() {
+        let state = WalletState::new();
+
+        let subaccount = state.new_subaccount(None);
+
+        let mut account = WalletAccount::new_watch_only(subaccount, "Cold Storage".to_owned());
+
+        let btc = ChainEnum::BTC(BitcoinNetwork::Mainnet);
+
+        account
+            .ledger_mut()
+            .add_watch_address(
+                btc.clone(),
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_owned(),
+            )
+            .unwrap();
+
+        // the funds of a watched address are not held by the wallet
+        account
+            .ledger_mut()
+            .chain_mut(btc)
+            .unwrap()
+            .add_pending(PendingEnum::new_btc(
+                "txid".to_owned(),
+                "address".to_owned(),
+            ));
+
+        assert_eq!(block_on(account.check_removable()), Ok(()));
+    }
 }
@@ -17,6 +17,9 @@ pub struct WalletAccountView {
     pub id: String,
     pub name: String,
     pub hidden: bool,
+    pub archived: bool,
+    pub watched: bool,
+    pub watch_only: bool,
     pub metadata: Metadata,
     pub addresses: AddressMap,
     pub environment: Environment,
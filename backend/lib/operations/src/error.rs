@@ -32,6 +32,8 @@ pub enum OperationError {
     InvalidThreshold,
     AccountIsHidden,
     AccountIsNotHidden,
+    AccountIsArchived,
+    AccountIsNotArchived,
     AccountDoesNotExist,
     WasmNotSet,
     InvalidChainId(u64, u64),
@@ -55,6 +57,8 @@ impl fmt::Display for OperationError {
             OperationError::ExecutionError(ref msg) => write!(f, "Execution Error::{}", msg),
             OperationError::AccountIsHidden => write!(f, "Account is hidden!"),
             OperationError::AccountIsNotHidden => write!(f, "Account is not hidden!"),
+            OperationError::AccountIsArchived => write!(f, "Account is archived!"),
+            OperationError::AccountIsNotArchived => write!(f, "Account is not archived!"),
             OperationError::AccountDoesNotExist => write!(f, "Account does not exist!"),
             OperationError::InvalidThreshold => write!(f, "Invalid threshold!"),
             OperationError::AmountIsZero => write!(f, "Amount is zero!"),
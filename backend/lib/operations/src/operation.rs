@@ -34,8 +34,19 @@ pub enum OperationEnum {
     EvmDeployContract,
     EvmSignTranscation,
     EvmSignRawTransaction,
+    EvmContractCall,
+    EvmSignTypedData,
+    EvmPersonalSign,
+    EvmSignUserOperation,
+    EvmTransferErc721,
+    EvmTransferErc1155,
+    EvmApproveNft,
+    EvmRevokeNftApproval,
     // BTC
     BtcTransfer,
+    BtcBatchTransfer,
+    BtcBumpFee,
+    BtcChildPaysForParent,
     // ICP
     IcpTransfer,
     TopUpTransfer,
@@ -47,8 +58,13 @@ pub enum OperationEnum {
     RenameAccount,
     HideAccount,
     UnhideAccount,
+    ArchiveAccount,
+    UnarchiveAccount,
     UpgradeCanister,
     UpdateCanisterSettings,
+    UpdateEvmNetwork,
+    UpdateEvmGasConfig,
+    UpdateEvmCreate2Factory,
 }
 
 impl Operation {
@@ -62,8 +78,19 @@ impl Operation {
             Operation::EvmDeployContract(_) => OperationEnum::EvmDeployContract,
             Operation::EvmSignTranscation(_) => OperationEnum::EvmSignTranscation,
             Operation::EvmSignRawTransaction(_) => OperationEnum::EvmSignRawTransaction,
+            Operation::EvmContractCall(_) => OperationEnum::EvmContractCall,
+            Operation::EvmSignTypedData(_) => OperationEnum::EvmSignTypedData,
+            Operation::EvmPersonalSign(_) => OperationEnum::EvmPersonalSign,
+            Operation::EvmSignUserOperation(_) => OperationEnum::EvmSignUserOperation,
+            Operation::EvmTransferErc721(_) => OperationEnum::EvmTransferErc721,
+            Operation::EvmTransferErc1155(_) => OperationEnum::EvmTransferErc1155,
+            Operation::EvmApproveNft(_) => OperationEnum::EvmApproveNft,
+            Operation::EvmRevokeNftApproval(_) => OperationEnum::EvmRevokeNftApproval,
             // BTC
             Operation::BtcTransfer(_) => OperationEnum::BtcTransfer,
+            Operation::BtcBatchTransfer(_) => OperationEnum::BtcBatchTransfer,
+            Operation::BtcBumpFee(_) => OperationEnum::BtcBumpFee,
+            Operation::BtcChildPaysForParent(_) => OperationEnum::BtcChildPaysForParent,
             // ICP
             Operation::IcpTransfer(_) => OperationEnum::IcpTransfer,
             Operation::TopUpTransfer(_) => OperationEnum::TopUpTransfer,
@@ -75,8 +102,13 @@ impl Operation {
             Operation::RenameAccount(_) => OperationEnum::RenameAccount,
             Operation::HideAccount(_) => OperationEnum::HideAccount,
             Operation::UnhideAccount(_) => OperationEnum::UnhideAccount,
+            Operation::ArchiveAccount(_) => OperationEnum::ArchiveAccount,
+            Operation::UnarchiveAccount(_) => OperationEnum::UnarchiveAccount,
             Operation::UpgradeCanister(_) => OperationEnum::UpgradeCanister,
             Operation::UpdateCanisterSettings(_) => OperationEnum::UpdateCanisterSettings,
+            Operation::UpdateEvmNetwork(_) => OperationEnum::UpdateEvmNetwork,
+            Operation::UpdateEvmGasConfig(_) => OperationEnum::UpdateEvmGasConfig,
+            Operation::UpdateEvmCreate2Factory(_) => OperationEnum::UpdateEvmCreate2Factory,
         }
     }
 }
@@ -88,6 +120,9 @@ pub trait OperationTrait {
     fn message(&self) -> String;
     fn method_name(&self) -> String;
     fn validate_request(&self) -> Result<(), OperationError>;
+    /// Fix what the operation reads from the settings when it is requested,
+    /// so it executes as the approvers were told.
+    fn freeze(&mut self) {}
     async fn execute(self) -> Result<OperationResult, WalletError>;
 }
 
@@ -102,8 +137,19 @@ pub enum Operation {
     EvmDeployContract,
     EvmSignTranscation,
     EvmSignRawTransaction,
+    EvmContractCall,
+    EvmSignTypedData,
+    EvmPersonalSign,
+    EvmSignUserOperation,
+    EvmTransferErc721,
+    EvmTransferErc1155,
+    EvmApproveNft,
+    EvmRevokeNftApproval,
     // BTC
     BtcTransfer,
+    BtcBatchTransfer,
+    BtcBumpFee,
+    BtcChildPaysForParent,
     // ICP
     IcpTransfer,
     TopUpTransfer,
@@ -115,8 +161,13 @@ pub enum Operation {
     RenameAccount,
     HideAccount,
     UnhideAccount,
+    ArchiveAccount,
+    UnarchiveAccount,
     UpgradeCanister,
     UpdateCanisterSettings,
+    UpdateEvmNetwork,
+    UpdateEvmGasConfig,
+    UpdateEvmCreate2Factory,
 }
 
 impl fmt::Display for Operation {
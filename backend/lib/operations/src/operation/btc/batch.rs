@@ -0,0 +1,146 @@
+use crate::{
+    error::OperationError,
+    operation::result::OperationResult,
+    operation::{result::BtcBatchTransfered, OperationTrait},
+};
+use async_trait::async_trait;
+use b3_utils::ledger::currency::TokenAmount;
+use b3wallet_lib::btc_transfer::btc_transfer;
+use b3wallet_lib::ledger::btc::coin_selection::{CoinSelectionStrategy, DUST_THRESHOLD};
+use b3wallet_lib::ledger::btc::error::BitcoinError;
+use b3wallet_lib::ledger::btc::tx::DisplayAmount;
+use b3wallet_lib::ledger::btc::types::Satoshi;
+use b3wallet_lib::ledger::types::ChainEnum;
+use b3wallet_lib::{error::WalletError, ledger::btc::network::BitcoinNetwork, store::with_chain};
+use candid::{CandidType, Deserialize};
+
+/// Pays several addresses in one transaction, with a single change output.
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct BtcBatchTransfer {
+    pub account_id: String,
+    /// The (address, amount) of every payment, in the order of the transaction outputs.
+    pub outputs: Vec<(String, TokenAmount)>,
+    pub network: BitcoinNetwork,
+    /// How the inputs are picked, largest first if not set.
+    #[serde(default)]
+    pub coin_selection: Option<CoinSelectionStrategy>,
+}
+
+impl BtcBatchTransfer {
+    pub fn coin_selection(&self) -> CoinSelectionStrategy {
+        self.coin_selection.unwrap_or_default()
+    }
+
+    pub fn outputs_in_satoshi(&self) -> Result<Vec<(String, Satoshi)>, OperationError> {
+        self.outputs
+            .iter()
+            .map(|(to, amount)| {
+                amount
+                    .to_satoshi()
+                    .map(|amount| (to.clone(), amount))
+                    .map_err(|_| OperationError::InvalidAmount)
+            })
+            .collect()
+    }
+
+    /// The sum of the payments, an error if it does not fit in a satoshi amount.
+    pub fn total_in_satoshi(&self) -> Result<Satoshi, OperationError> {
+        self.outputs_in_satoshi()?
+            .iter()
+            .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+            .ok_or(OperationError::InvalidAmount)
+    }
+
+    fn total(&self) -> String {
+        match self.total_in_satoshi() {
+            Ok(total) => format!("{} BTC", DisplayAmount(total)),
+            Err(_) => "an invalid amount of BTC".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl OperationTrait for BtcBatchTransfer {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let outputs = self
+            .outputs_in_satoshi()
+            .map_err(|err| WalletError::ExecutionError(err.to_string()))?;
+
+        let result = btc_transfer(
+            &self.account_id,
+            self.network,
+            outputs,
+            &self.coin_selection(),
+        )
+        .await;
+
+        match result {
+            Ok((txid, _)) => Ok(BtcBatchTransfered(self, txid).into()),
+            Err(err) => return Err(WalletError::ExecutionError(err.to_string())),
+        }
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        if self.outputs.is_empty() {
+            return Err(OperationError::InvalidRequest);
+        }
+
+        if self
+            .outputs
+            .iter()
+            .any(|(_, amount)| *amount <= TokenAmount::from(0))
+        {
+            return Err(OperationError::InvalidAmount);
+        }
+
+        self.total_in_satoshi()?;
+
+        let btc = with_chain(&self.account_id, &ChainEnum::BTC(self.network), |chain| {
+            chain.btc()
+        })??;
+
+        for (to, amount) in self.outputs_in_satoshi()? {
+            if amount < DUST_THRESHOLD {
+                return Err(WalletError::from(BitcoinError::DustOutput {
+                    address: to,
+                    amount,
+                })
+                .into());
+            }
+
+            btc.tx_out(&to, 0).map_err(WalletError::from)?;
+        }
+
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "btc_batch_transfer".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Send {} on {} to {} addresses",
+            self.total(),
+            self.network,
+            self.outputs.len()
+        )
+    }
+
+    fn message(&self) -> String {
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(to, amount)| format!("{} BTC to {}", amount, to))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!(
+            "Send {} on {} in one transaction: {} ({} coin selection)",
+            self.total(),
+            self.network,
+            outputs,
+            self.coin_selection()
+        )
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    error::OperationError,
+    operation::result::OperationResult,
+    operation::{result::BtcFeeBumped, OperationTrait},
+};
+use async_trait::async_trait;
+use b3wallet_lib::btc_transfer::btc_bump_fee;
+use b3wallet_lib::ledger::btc::types::BtcSentTransaction;
+use b3wallet_lib::ledger::types::ChainEnum;
+use b3wallet_lib::{error::WalletError, ledger::btc::network::BitcoinNetwork, store::with_chain};
+use candid::{CandidType, Deserialize};
+
+/// Replaces a transfer stuck in the mempool by one paying the same outputs with a higher fee.
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct BtcBumpFee {
+    pub account_id: String,
+    pub network: BitcoinNetwork,
+    /// The txid of the pending transfer.
+    pub txid: String,
+    /// The new fee rate in millisatoshi per vbyte, the median fee rate if not set.
+    #[serde(default)]
+    pub fee_per_vbyte: Option<u64>,
+}
+
+impl BtcBumpFee {
+    fn sent_transaction(&self) -> Result<BtcSentTransaction, WalletError> {
+        let btc = with_chain(&self.account_id, &ChainEnum::BTC(self.network), |chain| {
+            chain.btc()
+        })??;
+
+        btc.sent_transaction(&self.txid).map_err(WalletError::from)
+    }
+
+    fn fee_rate(&self) -> String {
+        match self.fee_per_vbyte {
+            Some(fee_per_vbyte) => format!("{} sat/vB", fee_per_vbyte as f64 / 1000.0),
+            None => "the median fee rate".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl OperationTrait for BtcBumpFee {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let result = btc_bump_fee(
+            &self.account_id,
+            self.network,
+            &self.txid,
+            self.fee_per_vbyte,
+        )
+        .await;
+
+        match result {
+            Ok((txid, _)) => Ok(BtcFeeBumped(self, txid).into()),
+            Err(err) => return Err(WalletError::ExecutionError(err.to_string())),
+        }
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        let sent = self.sent_transaction()?;
+
+        if let Some(fee_per_vbyte) = self.fee_per_vbyte {
+            if fee_per_vbyte <= sent.fee_per_vbyte() {
+                return Err(OperationError::InvalidRequest);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "btc_bump_fee".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Bump the fee of {} on {} to {}",
+            self.txid,
+            self.network,
+            self.fee_rate()
+        )
+    }
+
+    fn message(&self) -> String {
+        let current = match self.sent_transaction() {
+            Ok(sent) => format!("{} sat/vB", sent.fee_per_vbyte() as f64 / 1000.0),
+            Err(_) => "an unknown fee rate".to_string(),
+        };
+
+        format!(
+            "Replace the transaction {} on {}, paying {}, by one paying {}. The payments stay the same",
+            self.txid,
+            self.network,
+            current,
+            self.fee_rate()
+        )
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    error::OperationError,
+    operation::result::OperationResult,
+    operation::{result::BtcParentAccelerated, OperationTrait},
+};
+use async_trait::async_trait;
+use b3wallet_lib::btc_transfer::btc_child_pays_for_parent;
+use b3wallet_lib::ledger::btc::tx::parse_txid;
+use b3wallet_lib::ledger::btc::types::Satoshi;
+use b3wallet_lib::ledger::types::ChainEnum;
+use b3wallet_lib::{error::WalletError, ledger::btc::network::BitcoinNetwork, store::with_chain};
+use candid::{CandidType, Deserialize};
+
+/// Speeds up a transaction stuck in the mempool, incoming or outgoing, by spending
+/// one of its outputs paying to the account in a child with a high fee.
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct BtcChildPaysForParent {
+    pub account_id: String,
+    pub network: BitcoinNetwork,
+    /// The txid of the stuck transaction.
+    pub txid: String,
+    /// The index of its output paying to the account.
+    pub vout: u32,
+    /// The virtual size of the stuck transaction, not needed for a pending transfer.
+    #[serde(default)]
+    pub parent_vsize: Option<u64>,
+    /// The fee paid by the stuck transaction in satoshi, not needed for a pending transfer.
+    #[serde(default)]
+    pub parent_fee: Option<Satoshi>,
+    /// The value of the output in satoshi, not needed for a pending transfer.
+    /// The Bitcoin canister does not return the outputs of transactions in the mempool,
+    /// so it is needed for an incoming transaction.
+    #[serde(default)]
+    pub value: Option<Satoshi>,
+    /// The fee rate of both transactions together in millisatoshi per vbyte,
+    /// the median fee rate if not set.
+    #[serde(default)]
+    pub fee_per_vbyte: Option<u64>,
+}
+
+impl BtcChildPaysForParent {
+    pub fn parent(&self) -> Option<(u64, Satoshi)> {
+        self.parent_vsize.zip(self.parent_fee)
+    }
+
+    fn fee_rate(&self) -> String {
+        match self.fee_per_vbyte {
+            Some(fee_per_vbyte) => format!("{} sat/vB", fee_per_vbyte as f64 / 1000.0),
+            None => "the median fee rate".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl OperationTrait for BtcChildPaysForParent {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let result = btc_child_pays_for_parent(
+            &self.account_id,
+            self.network,
+            &self.txid,
+            self.vout,
+            self.parent(),
+            self.value,
+            self.fee_per_vbyte,
+        )
+        .await;
+
+        match result {
+            Ok((txid, _)) => Ok(BtcParentAccelerated(self, txid).into()),
+            Err(err) => return Err(WalletError::ExecutionError(err.to_string())),
+        }
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        parse_txid(&self.txid).map_err(WalletError::from)?;
+
+        if self.parent_vsize == Some(0) || self.value == Some(0) {
+            return Err(OperationError::InvalidRequest);
+        }
+
+        // the parent is either described in full, with the value of the output
+        // the Bitcoin canister can not see yet, or one of the pending transfers
+        match (self.parent_vsize, self.parent_fee, self.value) {
+            (Some(_), Some(_), Some(_)) => Ok(()),
+            (Some(_), Some(_), None) | (None, None, None) => {
+                let btc = with_chain(&self.account_id, &ChainEnum::BTC(self.network), |chain| {
+                    chain.btc()
+                })??;
+
+                btc.sent_transaction(&self.txid)
+                    .map_err(WalletError::from)?;
+
+                Ok(())
+            }
+            _ => Err(OperationError::InvalidRequest),
+        }
+    }
+
+    fn method_name(&self) -> String {
+        "btc_child_pays_for_parent".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Speed up {} on {} to {}",
+            self.txid,
+            self.network,
+            self.fee_rate()
+        )
+    }
+
+    fn message(&self) -> String {
+        let value = match self.value {
+            Some(value) => format!(" of {} satoshi", value),
+            None => String::new(),
+        };
+
+        format!(
+            "Spend the output {}:{}{} on {} in a child transaction, so that both pay {}",
+            self.txid,
+            self.vout,
+            value,
+            self.network,
+            self.fee_rate()
+        )
+    }
+}
@@ -1,3 +1,9 @@
+pub mod batch;
+pub mod bump;
+pub mod cpfp;
 pub mod transfer;
 
+pub use batch::*;
+pub use bump::*;
+pub use cpfp::*;
 pub use transfer::*;
@@ -5,8 +5,9 @@ use crate::{
 };
 use async_trait::async_trait;
 use b3_utils::ledger::currency::TokenAmount;
+use b3wallet_lib::btc_transfer::btc_transfer;
+use b3wallet_lib::ledger::btc::coin_selection::CoinSelectionStrategy;
 use b3wallet_lib::ledger::types::ChainEnum;
-use b3wallet_lib::ledger::{chain::ChainTrait, types::SendResult};
 use b3wallet_lib::{error::WalletError, ledger::btc::network::BitcoinNetwork, store::with_chain};
 use candid::{CandidType, Deserialize};
 
@@ -16,21 +17,36 @@ pub struct BtcTransfer {
     pub amount: TokenAmount,
     pub to: String,
     pub network: BitcoinNetwork,
+    /// How the inputs are picked, largest first if not set.
+    #[serde(default)]
+    pub coin_selection: Option<CoinSelectionStrategy>,
+}
+
+impl BtcTransfer {
+    pub fn coin_selection(&self) -> CoinSelectionStrategy {
+        self.coin_selection.unwrap_or_default()
+    }
 }
 
 #[async_trait]
 impl OperationTrait for BtcTransfer {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let chain = with_chain(&self.account_id, &ChainEnum::BTC(self.network), |chain| {
-            chain.clone()
-        })?;
+        let amount = self
+            .amount
+            .to_satoshi()
+            .map_err(|err| WalletError::ExecutionError(err.to_string()))?;
 
-        let result = chain.send(self.to.clone(), self.amount).await;
+        let result = btc_transfer(
+            &self.account_id,
+            self.network,
+            vec![(self.to.clone(), amount)],
+            &self.coin_selection(),
+        )
+        .await;
 
         match result {
-            Ok(SendResult::BTC(txid)) => Ok(BtcTransfered(self, txid).into()),
+            Ok((txid, _)) => Ok(BtcTransfered(self, txid).into()),
             Err(err) => return Err(WalletError::ExecutionError(err.to_string())),
-            _ => return Err(WalletError::UnknownError),
         }
     }
 
@@ -51,6 +67,12 @@ impl OperationTrait for BtcTransfer {
     }
 
     fn message(&self) -> String {
-        format!("Send {} {}", self.amount, self.network)
+        format!(
+            "Send {} {} to {} ({} coin selection)",
+            self.amount,
+            self.network,
+            self.to,
+            self.coin_selection()
+        )
     }
 }
@@ -0,0 +1,137 @@
+use super::{execute_transaction, fee_cap_message, native_amount, network_name, FeeRequest};
+use crate::{
+    error::OperationError,
+    operation::{
+        result::{EvmContractCalled, OperationResult},
+        OperationTrait,
+    },
+};
+use async_trait::async_trait;
+use b3wallet_lib::{
+    error::WalletError,
+    ledger::{
+        evm::{
+            abi::AbiToken,
+            address::EvmAddress,
+            contract::{AbiFunction, ContractAbi},
+            error::EvmError,
+            gas::EvmGasConfig,
+            u256::U256,
+            utils::vec_u8_to_string,
+        },
+        types::ChainEnum,
+    },
+    store::{with_chain, with_setting},
+};
+use candid::{CandidType, Deserialize};
+
+// CONTRACT CALL
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmContractCall {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub nonce: Option<u64>,
+    pub contract_address: EvmAddress,
+    /// The JSON ABI of the contract, only the called function is needed.
+    pub abi: String,
+    /// The function name, or its full signature when it is overloaded.
+    pub function: String,
+    /// The arguments as a JSON array, in the order of the function inputs.
+    pub args: String,
+    pub value: U256,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+impl EvmContractCall {
+    /// The called function and its arguments, checked against the ABI.
+    pub fn decoded(&self) -> Result<(AbiFunction, Vec<AbiToken>), EvmError> {
+        let abi = ContractAbi::from_json(&self.abi)?;
+
+        let function = abi.function(&self.function)?.clone();
+
+        let tokens = function.tokenize_json(&self.args)?;
+
+        Ok((function, tokens))
+    }
+
+    pub fn calldata(&self) -> Result<Vec<u8>, EvmError> {
+        let (function, tokens) = self.decoded()?;
+
+        function.encode_input(&tokens)
+    }
+}
+
+#[async_trait]
+impl OperationTrait for EvmContractCall {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let data = "0x".to_owned() + &vec_u8_to_string(&self.calldata()?);
+
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: self.value,
+                data: &data,
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            self.broadcast.unwrap_or(false),
+        )
+        .await?;
+
+        Ok(EvmContractCalled(self, tx_hash, Some(signed)).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        self.calldata()?;
+
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
+    fn method_name(&self) -> String {
+        "evm_contract_call".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Call {} on {}", self.function, self.contract_address)
+    }
+
+    fn message(&self) -> String {
+        let call = match self.decoded() {
+            Ok((function, tokens)) => function.describe(&tokens),
+            Err(err) => format!("{} with undecodable arguments ({})", self.function, err),
+        };
+
+        format!(
+            "Call {} on contract {} of {}, sending {}, {}",
+            call,
+            self.contract_address,
+            network_name(self.chain_id),
+            native_amount(self.chain_id, self.value),
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
+    }
+}
@@ -1,7 +1,190 @@
+pub mod contract;
+pub mod nft;
 pub mod other;
 pub mod sign;
 pub mod transfer;
 
+pub use contract::*;
+pub use nft::*;
 pub use other::*;
 pub use sign::*;
 pub use transfer::*;
+
+use b3wallet_lib::{
+    error::WalletError,
+    evm_transfer::{broadcast_transaction, release_nonce, reserve_nonce},
+    ledger::{
+        evm::{
+            evm::EvmSignTrait,
+            gas::{EvmFees, EvmGasConfig},
+            london::EvmTransaction1559,
+            u256::U256,
+        },
+        subaccount::SubaccountEcdsaTrait,
+        types::ChainEnum,
+    },
+    store::{with_chain, with_ledger, with_setting},
+};
+
+/// The fees requested by an operation, missing values are picked at execution time.
+pub struct FeeRequest<'a> {
+    pub to: &'a str,
+    pub value: U256,
+    pub data: &'a str,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    pub gas_config: Option<&'a EvmGasConfig>,
+}
+
+/// The gas defaults and fee caps frozen in the operation when it was requested,
+/// the current ones of the chain for operations requested before they were frozen.
+pub fn gas_config(chain_id: u64, frozen: Option<&EvmGasConfig>) -> EvmGasConfig {
+    match frozen {
+        Some(config) => config.clone(),
+        None => with_setting(|setting| setting.evm_gas_config(chain_id)),
+    }
+}
+
+/// Pick the fees of a transaction executed now.
+/// What the request leaves open is estimated on the chain, falling back to the
+/// gas defaults of the request, and the result is checked against its fee caps.
+pub async fn resolve_fees(
+    account_id: &String,
+    chain_id: u64,
+    request: FeeRequest<'_>,
+) -> Result<EvmFees, WalletError> {
+    let chain = with_chain(account_id, &ChainEnum::EVM(chain_id), |chain| chain.evm())??;
+
+    chain.check_eip1559()?;
+
+    let config = gas_config(chain_id, request.gas_config);
+
+    let suggestion = if request.max_fee_per_gas.is_some() && request.gas_limit.is_some() {
+        Default::default()
+    } else {
+        chain
+            .suggest_fees(request.to, request.value, request.data, request.gas_limit)
+            .await
+    };
+
+    let fees = config.resolve(
+        request.gas_limit,
+        request.max_fee_per_gas,
+        request.max_priority_fee_per_gas,
+        &suggestion,
+    )?;
+
+    Ok(fees)
+}
+
+/// The name of the EVM chain in the network registry, e.g. Base.
+pub fn network_name(chain_id: u64) -> String {
+    with_setting(|setting| setting.evm_network(chain_id))
+        .map(|network| network.name)
+        .unwrap_or_else(|_| format!("EVM chain {}", chain_id))
+}
+
+/// An amount of the native token of the EVM chain, e.g. 0.5 ETH, in wei when the chain is not registered.
+pub fn native_amount(chain_id: u64, value: U256) -> String {
+    with_setting(|setting| setting.evm_network(chain_id))
+        .map(|network| network.format_amount(value))
+        .unwrap_or_else(|_| format!("{} wei", value))
+}
+
+/// Tell the approvers the most the operation can pay in fees.
+/// Fees fixed by the request are exact, otherwise the caps frozen in the request bound them.
+pub fn fee_cap_message(
+    chain_id: u64,
+    frozen: Option<&EvmGasConfig>,
+    gas_limit: Option<u64>,
+    max_fee_per_gas: Option<u64>,
+) -> String {
+    let config = gas_config(chain_id, frozen);
+
+    let gas_limit = gas_limit.or(config.gas_limit_cap);
+    let max_fee_per_gas = max_fee_per_gas.or(config.max_fee_per_gas_cap);
+
+    match (gas_limit, max_fee_per_gas) {
+        (Some(gas_limit), Some(max_fee_per_gas)) => format!(
+            "paying at most {} in fees ({} gas at {} wei)",
+            native_amount(
+                chain_id,
+                U256::from(gas_limit as u128 * max_fee_per_gas as u128)
+            ),
+            gas_limit,
+            max_fee_per_gas
+        ),
+        _ => "paying fees estimated at execution, without a cap".to_string(),
+    }
+}
+
+/// Sign the transaction with the key of the account and broadcast it when asked.
+/// Returns the transaction hash and the signed transaction.
+pub async fn sign_transaction<T: EvmSignTrait + Send>(
+    account_id: &String,
+    chain_id: u64,
+    transaction: &mut T,
+    broadcast: bool,
+) -> Result<(String, Vec<u8>), WalletError> {
+    let ledger = with_ledger(account_id, |ledger| ledger.clone())?;
+
+    let public_key = ledger.public_key()?;
+
+    let signature = ledger
+        .subaccount
+        .sign_with_ecdsa(transaction.unsigned_hash())
+        .await?;
+
+    let signed = transaction.sign(signature, *public_key)?;
+
+    let tx_hash = if broadcast {
+        broadcast_transaction(account_id, chain_id, &signed).await?
+    } else {
+        transaction.tx_id()
+    };
+
+    Ok((tx_hash, signed))
+}
+
+/// Build the EIP-1559 transaction of the request with the fees and nonce picked now,
+/// sign it and broadcast it when asked. The nonce is given back when signing fails.
+/// Returns the transaction hash and the signed transaction.
+pub async fn execute_transaction(
+    account_id: &String,
+    chain_id: u64,
+    nonce: Option<u64>,
+    request: FeeRequest<'_>,
+    broadcast: bool,
+) -> Result<(String, Vec<u8>), WalletError> {
+    let to = request.to.to_string();
+    let value = request.value;
+    let data = request.data.to_string();
+
+    let fees = resolve_fees(account_id, chain_id, request).await?;
+
+    let nonce = reserve_nonce(account_id, chain_id, nonce).await?;
+
+    let mut transaction = EvmTransaction1559 {
+        nonce,
+        chain_id,
+        to,
+        value,
+        data,
+        max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+        max_fee_per_gas: fees.max_fee_per_gas,
+        gas_limit: fees.gas_limit,
+        access_list: vec![],
+        v: "0x00".to_string(),
+        r: "0x00".to_string(),
+        s: "0x00".to_string(),
+    };
+
+    let result = sign_transaction(account_id, chain_id, &mut transaction, broadcast).await;
+
+    if result.is_err() {
+        release_nonce(account_id, chain_id, nonce)?;
+    }
+
+    result
+}
@@ -0,0 +1,449 @@
+use super::{execute_transaction, fee_cap_message, FeeRequest};
+use crate::{
+    error::OperationError,
+    operation::{
+        result::{
+            EvmErc1155Transfered, EvmErc721Transfered, EvmNftApprovalRevoked, EvmNftApproved,
+            OperationResult,
+        },
+        OperationTrait,
+    },
+};
+use async_trait::async_trait;
+use b3wallet_lib::{
+    error::WalletError,
+    ledger::{
+        evm::{
+            address::EvmAddress,
+            gas::EvmGasConfig,
+            nft::{nft_approval_data, nft_transfer_data, EvmNftApproval, EvmNftStandard},
+            u256::U256,
+            utils::vec_u8_to_string,
+        },
+        types::ChainEnum,
+    },
+    store::{with_chain, with_chain_mut, with_setting},
+};
+use candid::{CandidType, Deserialize};
+
+fn account_address(account_id: &String, chain_id: u64) -> Result<EvmAddress, WalletError> {
+    let address = with_chain(account_id, &ChainEnum::EVM(chain_id), |chain| {
+        chain.evm().map(|evm| evm.address)
+    })??;
+
+    Ok(address)
+}
+
+/// Takes the transferred NFT out of the registry of the chain.
+/// Only broadcast transactions change the registry, a transaction that is only signed
+/// may never be sent.
+fn spend_nft(
+    account_id: &String,
+    chain_id: u64,
+    contract_address: &EvmAddress,
+    token_id: U256,
+    amount: U256,
+) -> Result<(), WalletError> {
+    with_chain_mut(account_id, ChainEnum::EVM(chain_id), |chain| {
+        chain
+            .evm_mut()
+            .map(|evm| evm.spend_nft(contract_address, token_id, amount))
+    })??;
+
+    Ok(())
+}
+
+// EVM TRANSFER ERC721
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmTransferErc721 {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub nonce: Option<u64>,
+    pub contract_address: EvmAddress,
+    pub to: EvmAddress,
+    pub token_id: U256,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+#[async_trait]
+impl OperationTrait for EvmTransferErc721 {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let from = account_address(&self.account_id, self.chain_id)?;
+
+        let data = nft_transfer_data(
+            EvmNftStandard::ERC721,
+            &from,
+            &self.to,
+            self.token_id,
+            U256::from(1u64),
+        )?;
+
+        let broadcast = self.broadcast.unwrap_or(false);
+
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: U256::ZERO,
+                data: &format!("0x{}", vec_u8_to_string(&data)),
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            broadcast,
+        )
+        .await?;
+
+        if broadcast {
+            spend_nft(
+                &self.account_id,
+                self.chain_id,
+                &self.contract_address,
+                self.token_id,
+                U256::from(1u64),
+            )?;
+        }
+
+        Ok(EvmErc721Transfered(self, tx_hash, Some(signed)).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
+    fn method_name(&self) -> String {
+        "evm_transfer_erc721".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Transfer NFT {}", self.token_id)
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "Transfer ERC-721 token {} of {} to {}, {}",
+            self.token_id,
+            self.contract_address,
+            self.to,
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
+    }
+}
+
+// EVM TRANSFER ERC1155
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmTransferErc1155 {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub nonce: Option<u64>,
+    pub contract_address: EvmAddress,
+    pub to: EvmAddress,
+    pub token_id: U256,
+    pub amount: U256,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+#[async_trait]
+impl OperationTrait for EvmTransferErc1155 {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let from = account_address(&self.account_id, self.chain_id)?;
+
+        let data = nft_transfer_data(
+            EvmNftStandard::ERC1155,
+            &from,
+            &self.to,
+            self.token_id,
+            self.amount,
+        )?;
+
+        let broadcast = self.broadcast.unwrap_or(false);
+
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: U256::ZERO,
+                data: &format!("0x{}", vec_u8_to_string(&data)),
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            broadcast,
+        )
+        .await?;
+
+        if broadcast {
+            spend_nft(
+                &self.account_id,
+                self.chain_id,
+                &self.contract_address,
+                self.token_id,
+                self.amount,
+            )?;
+        }
+
+        Ok(EvmErc1155Transfered(self, tx_hash, Some(signed)).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        if self.amount.is_zero() {
+            return Err(OperationError::InvalidAmount);
+        }
+
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
+    fn method_name(&self) -> String {
+        "evm_transfer_erc1155".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Transfer {} of NFT {}", self.amount, self.token_id)
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "Transfer {} of ERC-1155 token {} of {} to {}, {}",
+            self.amount,
+            self.token_id,
+            self.contract_address,
+            self.to,
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
+    }
+}
+
+/// What an approval covers, for the consent messages.
+fn approval_scope(contract_address: &EvmAddress, token_id: Option<U256>) -> String {
+    match token_id {
+        Some(token_id) => format!("ERC-721 token {} of {}", token_id, contract_address),
+        None => format!("every NFT of collection {}", contract_address),
+    }
+}
+
+// EVM APPROVE NFT
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmApproveNft {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub nonce: Option<u64>,
+    pub contract_address: EvmAddress,
+    pub operator: EvmAddress,
+    /// The ERC-721 token to approve, None approves the whole collection.
+    pub token_id: Option<U256>,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+#[async_trait]
+impl OperationTrait for EvmApproveNft {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let data = nft_approval_data(&self.operator, self.token_id, true)?;
+
+        let broadcast = self.broadcast.unwrap_or(false);
+
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: U256::ZERO,
+                data: &format!("0x{}", vec_u8_to_string(&data)),
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            broadcast,
+        )
+        .await?;
+
+        if broadcast {
+            let approval = EvmNftApproval::new(
+                self.contract_address,
+                self.operator,
+                self.token_id,
+                tx_hash.clone(),
+            );
+
+            with_chain_mut(&self.account_id, ChainEnum::EVM(self.chain_id), |chain| {
+                chain.evm_mut().map(|evm| evm.add_nft_approval(approval))
+            })??;
+        }
+
+        Ok(EvmNftApproved(self, tx_hash, Some(signed)).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
+    fn method_name(&self) -> String {
+        "evm_approve_nft".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Approve NFT operator {}", self.operator)
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "Approve {} to transfer {} held by this account until revoked, {}",
+            self.operator,
+            approval_scope(&self.contract_address, self.token_id),
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
+    }
+}
+
+// EVM REVOKE NFT APPROVAL
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmRevokeNftApproval {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub nonce: Option<u64>,
+    pub contract_address: EvmAddress,
+    pub operator: EvmAddress,
+    /// The ERC-721 token to revoke, None revokes the approval for the whole collection.
+    pub token_id: Option<U256>,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+#[async_trait]
+impl OperationTrait for EvmRevokeNftApproval {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let data = nft_approval_data(&self.operator, self.token_id, false)?;
+
+        let broadcast = self.broadcast.unwrap_or(false);
+
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: U256::ZERO,
+                data: &format!("0x{}", vec_u8_to_string(&data)),
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            broadcast,
+        )
+        .await?;
+
+        if broadcast {
+            with_chain_mut(&self.account_id, ChainEnum::EVM(self.chain_id), |chain| {
+                chain.evm_mut().map(|evm| {
+                    evm.remove_nft_approval(&self.contract_address, &self.operator, self.token_id)
+                })
+            })??;
+        }
+
+        Ok(EvmNftApprovalRevoked(self, tx_hash, Some(signed)).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
+    fn method_name(&self) -> String {
+        "evm_revoke_nft_approval".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Revoke NFT operator {}", self.operator)
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "Revoke the approval of {} to transfer {}, {}",
+            self.operator,
+            approval_scope(&self.contract_address, self.token_id),
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
+    }
+}
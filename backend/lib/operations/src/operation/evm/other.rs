@@ -1,3 +1,4 @@
+use super::{fee_cap_message, network_name, resolve_fees, sign_transaction, FeeRequest};
 use crate::{
     error::OperationError,
     operation::result::OperationResult,
@@ -6,16 +7,20 @@ use crate::{
 use async_trait::async_trait;
 use b3wallet_lib::{
     error::WalletError,
+    evm_transfer::{release_nonce, reserve_nonce},
     ledger::{
         evm::{
-            evm::EvmSignTrait,
+            address::EvmAddress,
+            create2::EvmCreate2,
+            error::EvmError,
+            gas::EvmGasConfig,
             london::EvmTransaction1559,
+            u256::U256,
             utils::{create_address_from, vec_u8_to_string},
         },
-        subaccount::SubaccountEcdsaTrait,
         types::ChainEnum,
     },
-    store::{with_chain, with_ledger},
+    store::{with_chain, with_ledger, with_setting},
 };
 use candid::{CandidType, Deserialize};
 
@@ -24,11 +29,39 @@ use candid::{CandidType, Deserialize};
 pub struct EvmDeployContract {
     account_id: String,
     chain_id: u64,
-    nonce: u64,
+    nonce: Option<u64>,
     hex_byte_code: Vec<u8>,
     gas_limit: Option<u64>,
     max_fee_per_gas: Option<u64>,
     max_priority_fee_per_gas: Option<u64>,
+    /// Deploy with CREATE2 through a factory instead of CREATE,
+    /// the address then only depends on the factory, the salt and the byte code.
+    #[serde(default)]
+    salt: Option<Vec<u8>>,
+    /// The CREATE2 factory, the one of the chain when the operation is requested if not given.
+    #[serde(default)]
+    factory: Option<EvmAddress>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    gas_config: Option<EvmGasConfig>,
+}
+
+impl EvmDeployContract {
+    /// The CREATE2 deployment of the request, None when it deploys with CREATE.
+    pub fn create2(&self) -> Result<Option<EvmCreate2>, EvmError> {
+        let salt = match &self.salt {
+            Some(salt) => salt,
+            None => return Ok(None),
+        };
+
+        // only operations requested before the factory was frozen miss it
+        let factory = match self.factory {
+            Some(factory) => factory,
+            None => with_setting(|setting| setting.evm_create2_factory(self.chain_id)),
+        };
+
+        EvmCreate2::new(factory, salt, self.hex_byte_code.clone()).map(Some)
+    }
 }
 
 #[async_trait]
@@ -38,23 +71,56 @@ impl OperationTrait for EvmDeployContract {
 
         let public_key = ledger.public_key()?;
 
-        let contract_address = create_address_from(&public_key, self.nonce);
+        let create2 = self.create2()?;
+
+        // a CREATE2 deployment is a call to the factory, which is run first
+        // to make sure the contract lands where the approvers were told
+        let (to, data) = match &create2 {
+            Some(create2) => {
+                let chain =
+                    with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |chain| {
+                        chain.evm()
+                    })??;
+
+                chain.simulate_create2(create2).await?;
+
+                (create2.factory.to_string(), create2.calldata())
+            }
+            None => ("0x".to_string(), self.hex_byte_code.clone()),
+        };
+
+        let data = "0x".to_owned() + &vec_u8_to_string(&data);
+
+        let fees = resolve_fees(
+            &self.account_id,
+            self.chain_id,
+            FeeRequest {
+                to: &to,
+                value: U256::ZERO,
+                data: &data,
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+        )
+        .await?;
 
-        let data = "0x".to_owned() + &vec_u8_to_string(&self.hex_byte_code);
+        let nonce = reserve_nonce(&self.account_id, self.chain_id, self.nonce).await?;
 
-        // TODO: get default gas limit from user settings
-        let gas_limit = self.gas_limit.unwrap_or(0);
-        let max_fee_per_gas = self.max_fee_per_gas.unwrap_or(0);
-        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.unwrap_or(0);
+        let contract_address = match &create2 {
+            Some(create2) => create2.address(),
+            None => create_address_from(&public_key, nonce),
+        };
 
         let mut transaction = EvmTransaction1559 {
-            nonce: self.nonce,
+            nonce,
             chain_id: self.chain_id,
-            gas_limit,
-            max_fee_per_gas,
-            max_priority_fee_per_gas,
-            to: "0x".to_string(),
-            value: 0,
+            gas_limit: fees.gas_limit,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            to,
+            value: U256::ZERO,
             data,
             access_list: vec![],
             v: "0x00".to_string(),
@@ -62,11 +128,14 @@ impl OperationTrait for EvmDeployContract {
             s: "0x00".to_string(),
         };
 
-        let raw_tx = transaction.serialized();
+        let result =
+            sign_transaction(&self.account_id, self.chain_id, &mut transaction, false).await;
 
-        let signature = ledger.subaccount.sign_with_ecdsa(raw_tx).await?;
+        if result.is_err() {
+            release_nonce(&self.account_id, self.chain_id, nonce)?;
+        }
 
-        transaction.sign(signature, *public_key)?;
+        result?;
 
         Ok(EvmContractDeployed {
             transaction,
@@ -76,18 +145,52 @@ impl OperationTrait for EvmDeployContract {
     }
 
     fn validate_request(&self) -> Result<(), OperationError> {
+        self.create2()?;
+
         with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
     }
 
+    fn freeze(&mut self) {
+        if self.salt.is_some() && self.factory.is_none() {
+            self.factory = Some(with_setting(|setting| {
+                setting.evm_create2_factory(self.chain_id)
+            }));
+        }
+
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
     fn method_name(&self) -> String {
         "evm_deploy_contract".to_string()
     }
 
     fn title(&self) -> String {
-        format!("Deploy contract on EVM chain {}", self.chain_id)
+        format!("Deploy contract on {}", network_name(self.chain_id))
     }
 
     fn message(&self) -> String {
-        format!("Deploy contract on EVM chain {}", self.chain_id)
+        let mode = match self.create2() {
+            Ok(Some(create2)) => format!(
+                " at {} through the CREATE2 factory {}",
+                create2.address(),
+                create2.factory
+            ),
+            Ok(None) => String::new(),
+            Err(err) => format!(" with an invalid CREATE2 request ({})", err),
+        };
+
+        format!(
+            "Deploy contract on {}{}, {}",
+            network_name(self.chain_id),
+            mode,
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
     }
 }
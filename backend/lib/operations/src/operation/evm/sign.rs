@@ -1,8 +1,10 @@
+use super::{native_amount, network_name, sign_transaction};
 use crate::{
     error::OperationError,
     operation::{
         result::{
-            EvmMessageSigned, EvmRawTransactionSigned, EvmTransactionSigned, OperationResult,
+            EvmMessageSigned, EvmPersonalSigned, EvmRawTransactionSigned, EvmTransactionSigned,
+            EvmTypedDataSigned, EvmUserOperationSigned, OperationResult,
         },
         OperationTrait,
     },
@@ -10,8 +12,16 @@ use crate::{
 use async_trait::async_trait;
 use b3wallet_lib::{
     error::WalletError,
+    evm_transfer::use_nonce,
     ledger::{
-        evm::evm::{get_evm_transaction, EvmSignTrait, EvmTransaction},
+        evm::{
+            address::EvmAddress,
+            eip712::TypedData,
+            error::EvmError,
+            evm::{get_evm_transaction, EvmSignTrait, EvmTransaction},
+            user_op::{EntryPointVersion, UserOperation},
+            utils::vec_u8_to_string,
+        },
         subaccount::SubaccountEcdsaTrait,
         types::ChainEnum,
     },
@@ -25,26 +35,26 @@ pub struct EvmSignTranscation {
     pub account_id: String,
     pub chain_id: u64,
     pub transaction: EvmTransaction,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
 }
 
 #[async_trait]
 impl OperationTrait for EvmSignTranscation {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
-        let public_key = ledger.public_key()?;
-
         let mut transaction = self.transaction.clone();
 
-        transaction.unsigned_serialized();
-
-        let signature = ledger
-            .subaccount
-            .sign_with_ecdsa(transaction.serialized())
-            .await?;
+        let (tx_hash, signed) = sign_transaction(
+            &self.account_id,
+            self.chain_id,
+            &mut transaction,
+            self.broadcast.unwrap_or(false),
+        )
+        .await?;
 
-        transaction.sign(signature, *public_key)?;
+        use_nonce(&self.account_id, self.chain_id, transaction.nonce())?;
 
-        Ok(EvmTransactionSigned(self, transaction.tx_id()).into())
+        Ok(EvmTransactionSigned(self, tx_hash, Some(signed)).into())
     }
 
     fn validate_request(&self) -> Result<(), OperationError> {
@@ -61,7 +71,7 @@ impl OperationTrait for EvmSignTranscation {
     }
 
     fn message(&self) -> String {
-        format!("Sign EVM Transaction {}", self.chain_id)
+        format!("Sign: {}", self.transaction.summary())
     }
 }
 
@@ -71,6 +81,8 @@ pub struct EvmSignRawTransaction {
     pub account_id: String,
     pub hex_raw_tx: Vec<u8>,
     pub chain_id: u64,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
 }
 
 impl TryFrom<EvmSignRawTransaction> for EvmSignTranscation {
@@ -82,6 +94,7 @@ impl TryFrom<EvmSignRawTransaction> for EvmSignTranscation {
         Ok(EvmSignTranscation {
             account_id: args.account_id,
             chain_id: args.chain_id,
+            broadcast: args.broadcast,
             transaction,
         })
     }
@@ -90,22 +103,19 @@ impl TryFrom<EvmSignRawTransaction> for EvmSignTranscation {
 #[async_trait]
 impl OperationTrait for EvmSignRawTransaction {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
-
-        let public_key = ledger.public_key()?;
-
         let mut transaction = get_evm_transaction(&self.hex_raw_tx, self.chain_id)?;
 
-        transaction.unsigned_serialized();
-
-        let signature = ledger
-            .subaccount
-            .sign_with_ecdsa(transaction.serialized())
-            .await?;
+        let (tx_hash, signed) = sign_transaction(
+            &self.account_id,
+            self.chain_id,
+            &mut transaction,
+            self.broadcast.unwrap_or(false),
+        )
+        .await?;
 
-        transaction.sign(signature, *public_key)?;
+        use_nonce(&self.account_id, self.chain_id, transaction.nonce())?;
 
-        Ok(EvmRawTransactionSigned(self, transaction.tx_id()).into())
+        Ok(EvmRawTransactionSigned(self, tx_hash, Some(signed)).into())
     }
 
     fn validate_request(&self) -> Result<(), OperationError> {
@@ -135,7 +145,10 @@ impl OperationTrait for EvmSignRawTransaction {
     }
 
     fn message(&self) -> String {
-        format!("Sign EVM Transaction {}", self.chain_id)
+        match get_evm_transaction(&self.hex_raw_tx, self.chain_id) {
+            Ok(transaction) => format!("Sign: {}", transaction.summary()),
+            Err(err) => format!("Sign undecodable EVM transaction ({})", err),
+        }
     }
 }
 
@@ -152,6 +165,8 @@ impl OperationTrait for EvmSignMessage {
     async fn execute(self) -> Result<OperationResult, WalletError> {
         let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
 
+        ledger.check_signable()?;
+
         let signed = ledger
             .subaccount
             .sign_with_ecdsa(self.message.clone())
@@ -186,3 +201,198 @@ impl OperationTrait for EvmSignMessage {
         format!("Sign EVM Message {}", self.chain_id)
     }
 }
+
+// EVM PERSONAL SIGN
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmPersonalSign {
+    pub account_id: String,
+    pub chain_id: u64,
+    /// The message as the dApp gave it, before the EIP-191 prefix.
+    pub message: Vec<u8>,
+}
+
+#[async_trait]
+impl OperationTrait for EvmPersonalSign {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
+
+        let signed = ledger.sign_evm_personal_message(&self.message).await?;
+
+        Ok(EvmPersonalSigned(self, signed).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
+    }
+
+    fn method_name(&self) -> String {
+        "evm_personal_sign".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Sign EVM Message {}", self.chain_id)
+    }
+
+    fn message(&self) -> String {
+        match std::str::from_utf8(&self.message) {
+            Ok(text) => format!("Sign message {:?} on {}", text, network_name(self.chain_id)),
+            Err(_) => format!(
+                "Sign binary message 0x{} on {}",
+                vec_u8_to_string(&self.message),
+                network_name(self.chain_id)
+            ),
+        }
+    }
+}
+
+// EVM SIGN TYPED DATA
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmSignTypedData {
+    pub account_id: String,
+    pub chain_id: u64,
+    /// The EIP-712 typed data JSON, as given to `eth_signTypedData_v4`.
+    pub typed_data: String,
+}
+
+#[async_trait]
+impl OperationTrait for EvmSignTypedData {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let hash = TypedData::from_json(&self.typed_data)?.signing_hash()?;
+
+        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
+
+        let signed = ledger.sign_evm_hash(hash).await?;
+
+        Ok(EvmTypedDataSigned(self, signed).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| {})?;
+
+        let typed_data = TypedData::from_json(&self.typed_data)?;
+
+        // a signature for another chain could be replayed there
+        if let Some(chain_id) = typed_data.chain_id()? {
+            if chain_id != self.chain_id {
+                return Err(OperationError::InvalidChainId(chain_id, self.chain_id));
+            }
+        }
+
+        typed_data.signing_hash()?;
+
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "evm_sign_typed_data".to_string()
+    }
+
+    fn title(&self) -> String {
+        match TypedData::from_json(&self.typed_data) {
+            Ok(typed_data) => format!("Sign EVM Typed Data {}", typed_data.primary_type),
+            Err(_) => format!("Sign EVM Typed Data {}", self.chain_id),
+        }
+    }
+
+    fn message(&self) -> String {
+        match TypedData::from_json(&self.typed_data) {
+            Ok(typed_data) => format!(
+                "Sign {} on {}",
+                typed_data.describe(),
+                network_name(self.chain_id)
+            ),
+            Err(err) => format!("Sign undecodable typed data ({})", err),
+        }
+    }
+}
+
+// EVM SIGN USER OPERATION
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub struct EvmSignUserOperation {
+    pub account_id: String,
+    pub chain_id: u64,
+    pub version: EntryPointVersion,
+    /// The canonical EntryPoint of the version when None.
+    pub entry_point: Option<EvmAddress>,
+    /// The operation of the smart account this account owns, its signature is ignored.
+    pub user_operation: UserOperation,
+}
+
+impl EvmSignUserOperation {
+    pub fn entry_point(&self) -> EvmAddress {
+        self.entry_point
+            .unwrap_or_else(|| self.version.entry_point())
+    }
+
+    pub fn user_operation_hash(&self) -> Result<[u8; 32], EvmError> {
+        self.user_operation
+            .hash(self.version, &self.entry_point(), self.chain_id)
+    }
+}
+
+#[async_trait]
+impl OperationTrait for EvmSignUserOperation {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        let hash = self.user_operation_hash()?;
+
+        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
+
+        // the smart accounts check the owner signed the hash the way personal_sign does
+        let signature = ledger.sign_evm_personal_message(&hash).await?;
+
+        let mut user_operation = self.user_operation.clone();
+        user_operation.signature = signature;
+
+        let json = user_operation.to_json(self.version)?.to_string();
+
+        let hash = format!("0x{}", vec_u8_to_string(&hash.to_vec()));
+
+        Ok(EvmUserOperationSigned(self, hash, json).into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        // check if the chain id is initialized
+        with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| {})?;
+
+        self.user_operation_hash()?;
+
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "evm_sign_user_operation".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Sign user operation of smart account {}",
+            self.user_operation.sender
+        )
+    }
+
+    fn message(&self) -> String {
+        let user_operation = &self.user_operation;
+
+        let gas = match (user_operation.paymaster(), user_operation.max_fee()) {
+            (Some(paymaster), _) => format!("gas sponsored by paymaster {}", paymaster),
+            (None, Some(max_fee)) => format!(
+                "paying at most {} in gas",
+                native_amount(self.chain_id, max_fee)
+            ),
+            (None, None) => "paying an unbounded amount of gas".to_string(),
+        };
+
+        format!(
+            "Sign user operation {} of smart account {} on {} for EntryPoint {} {}, {}: {}",
+            user_operation.nonce,
+            user_operation.sender,
+            network_name(self.chain_id),
+            self.version,
+            self.entry_point(),
+            gas,
+            user_operation.describe(self.chain_id)
+        )
+    }
+}
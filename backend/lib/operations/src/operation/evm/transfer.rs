@@ -1,3 +1,4 @@
+use super::{execute_transaction, fee_cap_message, native_amount, network_name, FeeRequest};
 use crate::{
     error::OperationError,
     operation::result::{EvmTransfered, OperationResult},
@@ -8,11 +9,10 @@ use async_trait::async_trait;
 use b3wallet_lib::{
     error::WalletError,
     ledger::{
-        evm::{evm::EvmSignTrait, london::EvmTransaction1559, utils::get_transfer_data},
-        subaccount::SubaccountEcdsaTrait,
+        evm::{address::EvmAddress, gas::EvmGasConfig, u256::U256, utils::get_transfer_data},
         types::ChainEnum,
     },
-    store::{with_chain, with_ledger},
+    store::{with_chain, with_setting},
 };
 use candid::{CandidType, Deserialize};
 
@@ -21,48 +21,40 @@ use candid::{CandidType, Deserialize};
 pub struct EvmTransfer {
     pub account_id: String,
     pub chain_id: u64,
-    pub nonce: u64,
-    pub to: String,
-    pub value: u64,
+    pub nonce: Option<u64>,
+    pub to: EvmAddress,
+    pub value: U256,
     pub gas_limit: Option<u64>,
     pub max_fee_per_gas: Option<u64>,
     pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
 }
 
 #[async_trait]
 impl OperationTrait for EvmTransfer {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
-
-        let public_key = ledger.public_key()?;
-
-        // TODO: get default gas limit from user settings
-        let gas_limit = self.gas_limit.unwrap_or(0);
-        let max_fee_per_gas = self.max_fee_per_gas.unwrap_or(0);
-        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.unwrap_or(0);
-
-        let mut transaction = EvmTransaction1559 {
-            nonce: self.nonce,
-            chain_id: self.chain_id,
-            to: self.to.clone(),
-            value: self.value,
-            data: "0x00".to_string(),
-            max_priority_fee_per_gas,
-            max_fee_per_gas,
-            gas_limit,
-            access_list: vec![],
-            v: "0x00".to_string(),
-            r: "0x00".to_string(),
-            s: "0x00".to_string(),
-        };
-
-        let raw_tx = transaction.unsigned_serialized();
-
-        let _signed = ledger.subaccount.sign_with_ecdsa(raw_tx).await?;
-
-        transaction.sign(_signed, *public_key)?;
-
-        Ok(EvmTransfered(self, transaction.tx_id()).into())
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.to.to_string(),
+                value: self.value,
+                data: "0x",
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            self.broadcast.unwrap_or(false),
+        )
+        .await?;
+
+        Ok(EvmTransfered(self, tx_hash, Some(signed)).into())
     }
 
     fn validate_request(&self) -> Result<(), OperationError> {
@@ -70,16 +62,37 @@ impl OperationTrait for EvmTransfer {
         with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
     }
 
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
     fn method_name(&self) -> String {
         "evm_transfer_eth".to_string()
     }
 
     fn title(&self) -> String {
-        format!("Transfer {} ETH", self.value)
+        format!(
+            "Transfer {} on {}",
+            native_amount(self.chain_id, self.value),
+            network_name(self.chain_id)
+        )
     }
 
     fn message(&self) -> String {
-        format!("Transfer {} ETH", self.value)
+        format!(
+            "Transfer {} on {} to {}, {}",
+            native_amount(self.chain_id, self.value),
+            network_name(self.chain_id),
+            self.to,
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
     }
 }
 
@@ -88,51 +101,43 @@ impl OperationTrait for EvmTransfer {
 pub struct EvmTransferErc20 {
     pub account_id: String,
     pub chain_id: u64,
-    pub nonce: u64,
-    pub to: String,
-    pub value: u64,
-    pub contract_address: String,
+    pub nonce: Option<u64>,
+    pub to: EvmAddress,
+    pub value: U256,
+    pub contract_address: EvmAddress,
     pub gas_limit: Option<u64>,
     pub max_fee_per_gas: Option<u64>,
     pub max_priority_fee_per_gas: Option<u64>,
+    #[serde(default)]
+    pub broadcast: Option<bool>,
+    /// The gas defaults and fee caps of the chain, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
 }
 
 #[async_trait]
 impl OperationTrait for EvmTransferErc20 {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let ledger = with_ledger(&self.account_id, |ledger| ledger.clone())?;
-
-        let public_key = ledger.public_key()?;
-
         let data = "0x".to_owned() + &get_transfer_data(&self.to, self.value)?;
 
-        // TODO: get default gas limit from user settings
-        let gas_limit = self.gas_limit.unwrap_or(0);
-        let max_fee_per_gas = self.max_fee_per_gas.unwrap_or(0);
-        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.unwrap_or(0);
-
-        let mut transaction = EvmTransaction1559 {
-            nonce: self.nonce,
-            chain_id: self.chain_id,
-            max_priority_fee_per_gas,
-            gas_limit,
-            max_fee_per_gas,
-            to: self.contract_address.clone(),
-            value: 0,
-            data,
-            access_list: vec![],
-            v: "0x00".to_string(),
-            r: "0x00".to_string(),
-            s: "0x00".to_string(),
-        };
-
-        let raw_tx = transaction.unsigned_serialized();
-
-        let signature = ledger.subaccount.sign_with_ecdsa(raw_tx).await?;
-
-        transaction.sign(signature, *public_key)?;
-
-        Ok(EvmErc20Transfered(self, transaction.tx_id()).into())
+        let (tx_hash, signed) = execute_transaction(
+            &self.account_id,
+            self.chain_id,
+            self.nonce,
+            FeeRequest {
+                to: &self.contract_address.to_string(),
+                value: U256::ZERO,
+                data: &data,
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_config: self.gas_config.as_ref(),
+            },
+            self.broadcast.unwrap_or(false),
+        )
+        .await?;
+
+        Ok(EvmErc20Transfered(self, tx_hash, Some(signed)).into())
     }
 
     fn validate_request(&self) -> Result<(), OperationError> {
@@ -140,8 +145,14 @@ impl OperationTrait for EvmTransferErc20 {
         with_chain(&self.account_id, &ChainEnum::EVM(self.chain_id), |_| Ok(()))?
     }
 
+    fn freeze(&mut self) {
+        self.gas_config = Some(with_setting(|setting| {
+            setting.evm_gas_config(self.chain_id)
+        }));
+    }
+
     fn method_name(&self) -> String {
-        self.contract_address.clone()
+        self.contract_address.to_string()
     }
 
     fn title(&self) -> String {
@@ -149,6 +160,18 @@ impl OperationTrait for EvmTransferErc20 {
     }
 
     fn message(&self) -> String {
-        format!("Transfer {} ERC20", self.value)
+        format!(
+            "Transfer {} ERC20 of {} on {} to {}, {}",
+            self.value,
+            self.contract_address,
+            network_name(self.chain_id),
+            self.to,
+            fee_cap_message(
+                self.chain_id,
+                self.gas_config.as_ref(),
+                self.gas_limit,
+                self.max_fee_per_gas
+            )
+        )
     }
 }
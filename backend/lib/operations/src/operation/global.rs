@@ -3,14 +3,18 @@ use async_trait::async_trait;
 use b3_utils::ledger::currency::TokenAmount;
 use b3wallet_lib::{
     error::WalletError,
-    ledger::{chain::ChainTrait, types::ChainEnum},
-    store::{with_account, with_chain},
+    ledger::{
+        evm::{api::EVM_TRANSFER_GAS_LIMIT, gas::EvmGasConfig},
+        types::ChainEnum,
+    },
+    send::send,
+    store::{with_account, with_setting},
 };
 use candid::{CandidType, Deserialize};
 
 use crate::error::OperationError;
 
-use super::{result::TokenSent, OperationTrait};
+use super::{evm::fee_cap_message, result::TokenSent, OperationTrait};
 
 #[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
 pub struct SendToken {
@@ -18,14 +22,34 @@ pub struct SendToken {
     pub chain: ChainEnum,
     pub amount: TokenAmount,
     pub account_id: String,
+    /// The gas defaults and fee caps of EVM chains, frozen when the operation is requested.
+    #[serde(default)]
+    pub gas_config: Option<EvmGasConfig>,
+}
+
+impl SendToken {
+    /// The token and the chain it is sent on, e.g. `ETH on Base` for the EVM chains of the registry.
+    fn token_name(&self) -> String {
+        match self.chain {
+            ChainEnum::EVM(chain_id) => with_setting(|setting| setting.evm_network(chain_id))
+                .map(|network| format!("{} on {}", network.symbol, network.name))
+                .unwrap_or_else(|_| self.chain.to_string()),
+            _ => self.chain.to_string(),
+        }
+    }
 }
 
 #[async_trait]
 impl OperationTrait for SendToken {
     async fn execute(self) -> Result<OperationResult, WalletError> {
-        let chain = with_chain(&self.account_id, &self.chain, |chain| chain.clone())?;
-
-        let result = chain.send(self.to.clone(), self.amount).await;
+        let result = send(
+            &self.account_id,
+            &self.chain,
+            self.to.clone(),
+            self.amount,
+            self.gas_config.as_ref(),
+        )
+        .await;
 
         match result {
             Ok(result) => Ok(TokenSent(self, result).into()),
@@ -56,25 +80,45 @@ impl OperationTrait for SendToken {
         .map_err(|err| OperationError::WalletError(err))?
     }
 
+    fn freeze(&mut self) {
+        if let ChainEnum::EVM(chain_id) = self.chain {
+            self.gas_config = Some(with_setting(|setting| setting.evm_gas_config(chain_id)));
+        }
+    }
+
     fn method_name(&self) -> String {
         "send_token".to_string()
     }
 
     fn title(&self) -> String {
-        format!("Send {} {}", self.amount, self.chain)
+        format!("Send {} {}", self.amount, self.token_name())
     }
 
     fn message(&self) -> String {
         // we already checked that the account exists on validate_request
         let account = with_account(&self.account_id, |account| account.clone()).unwrap();
 
-        format!(
+        let message = format!(
             "Send {} {} from {}({}) to {}",
             self.amount,
-            self.chain,
+            self.token_name(),
             account.name(),
             self.account_id,
             self.to
-        )
+        );
+
+        match self.chain {
+            ChainEnum::EVM(chain_id) => format!(
+                "{}, {}",
+                message,
+                fee_cap_message(
+                    chain_id,
+                    self.gas_config.as_ref(),
+                    Some(EVM_TRANSFER_GAS_LIMIT),
+                    None
+                )
+            ),
+            _ => message,
+        }
     }
 }
@@ -4,7 +4,7 @@ use b3_utils::Environment;
 use b3wallet_lib::{
     account::WalletAccount,
     error::WalletError,
-    store::{with_account_mut, with_wallet, with_wallet_mut},
+    store::{with_account, with_account_mut, with_wallet, with_wallet_mut},
 };
 use candid::{CandidType, Deserialize};
 
@@ -54,11 +54,26 @@ impl OperationTrait for CreateAccount {
 #[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
 pub struct RemoveAccount {
     pub account_id: String,
+    // skip the balance and pendings check, the funds left on the account are lost
+    #[serde(default)]
+    pub force: Option<bool>,
+}
+
+impl RemoveAccount {
+    pub fn is_forced(&self) -> bool {
+        self.force.unwrap_or(false)
+    }
 }
 
 #[async_trait]
 impl OperationTrait for RemoveAccount {
     async fn execute(self) -> Result<OperationResult, WalletError> {
+        if !self.is_forced() {
+            let account = with_account(&self.account_id, |account| account.clone())?;
+
+            account.check_removable().await?;
+        }
+
         with_wallet_mut(|s| s.remove_account(&self.account_id))?;
 
         Ok(self.into())
@@ -83,7 +98,14 @@ impl OperationTrait for RemoveAccount {
     }
 
     fn message(&self) -> String {
-        format!("Remove Account {}", self.account_id)
+        if self.is_forced() {
+            format!(
+                "Force Remove Account {}, any remaining balance will be lost",
+                self.account_id
+            )
+        } else {
+            format!("Remove Account {}", self.account_id)
+        }
     }
 }
 
@@ -204,3 +226,81 @@ impl OperationTrait for UnhideAccount {
         format!("Unhide Account {}", self.account_id)
     }
 }
+
+// ARCHIVING ACCOUNT
+#[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
+pub struct ArchiveAccount {
+    pub account_id: String,
+}
+
+#[async_trait]
+impl OperationTrait for ArchiveAccount {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        with_account_mut(&self.account_id, |account| {
+            account.archive();
+        })?;
+
+        Ok(self.into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        with_account_mut(&self.account_id, |account| {
+            if account.is_archived() {
+                Err(OperationError::AccountIsArchived)
+            } else {
+                Ok(())
+            }
+        })?
+    }
+
+    fn method_name(&self) -> String {
+        "archive_account".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Archive Account {}", self.account_id)
+    }
+
+    fn message(&self) -> String {
+        format!("Archive Account {}", self.account_id)
+    }
+}
+
+// UNARCHIVING ACCOUNT
+#[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
+pub struct UnarchiveAccount {
+    pub account_id: String,
+}
+
+#[async_trait]
+impl OperationTrait for UnarchiveAccount {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        with_account_mut(&self.account_id, |account| {
+            account.unarchive();
+        })?;
+
+        Ok(self.into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        with_account_mut(&self.account_id, |account| {
+            if !account.is_archived() {
+                Err(OperationError::AccountIsNotArchived)
+            } else {
+                Ok(())
+            }
+        })?
+    }
+
+    fn method_name(&self) -> String {
+        "unarchive_account".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Unarchive Account {}", self.account_id)
+    }
+
+    fn message(&self) -> String {
+        format!("Unarchive Account {}", self.account_id)
+    }
+}
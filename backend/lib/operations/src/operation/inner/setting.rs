@@ -4,7 +4,14 @@ use b3_utils::{
     wasm::with_wasm_cache,
     wasm::{WasmHashString, WasmVersion},
 };
-use b3wallet_lib::error::WalletError;
+use b3wallet_lib::{
+    error::WalletError,
+    ledger::{
+        evm::{address::EvmAddress, gas::EvmGasConfig, network::EvmNetwork, rpc::RpcService},
+        types::ChainId,
+    },
+    store::{with_setting, with_setting_mut},
+};
 use candid::{CandidType, Deserialize};
 use ic_cdk::api::management_canister::{
     main::{
@@ -144,3 +151,194 @@ impl OperationTrait for UpgradeCanister {
         )
     }
 }
+
+// UPDATE EVM NETWORK
+#[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
+pub struct UpdateEvmNetwork {
+    pub chain_id: ChainId,
+    /// None removes the network from the registry.
+    pub network: Option<EvmNetwork>,
+}
+
+#[async_trait]
+impl OperationTrait for UpdateEvmNetwork {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        with_setting_mut(|setting| setting.set_evm_network(self.chain_id, self.network.clone()));
+
+        Ok(self.into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        if let Some(network) = &self.network {
+            network.validate()?;
+        }
+
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "update_evm_network".to_string()
+    }
+
+    fn title(&self) -> String {
+        match &self.network {
+            Some(network) => format!("Register EVM network {} ({})", network.name, self.chain_id),
+            None => format!("Remove EVM network {}", self.chain_id),
+        }
+    }
+
+    fn message(&self) -> String {
+        let network = match &self.network {
+            Some(network) => network,
+            None => {
+                let name = with_setting(|setting| setting.evm_network(self.chain_id))
+                    .map(|network| network.name)
+                    .unwrap_or_else(|_| "unknown".to_string());
+
+                return format!(
+                    "Remove EVM chain {} ({}) from the network registry, \
+                    the accounts holding it fall back to the providers of the EVM RPC canister",
+                    self.chain_id, name
+                );
+            }
+        };
+
+        // only the url of a custom provider, its headers can hold an API key
+        let rpc = match &network.rpc_service {
+            None => "the providers of the EVM RPC canister".to_string(),
+            Some(RpcService::Custom(api)) => api.url.clone(),
+            Some(service) => format!("{:?}", service),
+        };
+
+        format!(
+            "Register EVM chain {} as {}, native token {} with {} decimals, served by {}, \
+            explorer {}, {}",
+            self.chain_id,
+            network.name,
+            network.symbol,
+            network.decimals,
+            rpc,
+            network.explorer_url.as_deref().unwrap_or("none"),
+            if network.eip1559 {
+                "with EIP-1559 transactions"
+            } else {
+                "without EIP-1559 transactions, which the wallet can not send"
+            }
+        )
+    }
+}
+
+// UPDATE EVM GAS CONFIG
+#[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
+pub struct UpdateEvmGasConfig {
+    pub chain_id: ChainId,
+    /// None removes the gas defaults and fee caps of the chain.
+    pub config: Option<EvmGasConfig>,
+}
+
+fn describe_gas_config(config: &EvmGasConfig) -> String {
+    fn value(value: Option<u64>, unit: &str) -> String {
+        match value {
+            Some(value) => format!("{} {}", value, unit),
+            None => "none".to_string(),
+        }
+    }
+
+    format!(
+        "gas limit cap {}, max fee per gas cap {}, default gas limit {}, \
+        default max fee per gas {}, default priority fee per gas {}",
+        value(config.gas_limit_cap, "gas"),
+        value(config.max_fee_per_gas_cap, "wei"),
+        value(config.gas_limit, "gas"),
+        value(config.max_fee_per_gas, "wei"),
+        value(config.max_priority_fee_per_gas, "wei")
+    )
+}
+
+#[async_trait]
+impl OperationTrait for UpdateEvmGasConfig {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        with_setting_mut(|setting| setting.set_evm_gas_config(self.chain_id, self.config.clone()));
+
+        Ok(self.into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "update_evm_gas_config".to_string()
+    }
+
+    fn title(&self) -> String {
+        match &self.config {
+            Some(_) => format!("Update gas config of EVM chain {}", self.chain_id),
+            None => format!("Remove gas config of EVM chain {}", self.chain_id),
+        }
+    }
+
+    fn message(&self) -> String {
+        let current = with_setting(|setting| setting.evm_gas_config(self.chain_id));
+
+        match &self.config {
+            Some(config) => format!(
+                "Set the gas config of EVM chain {} to {}, it was {}. \
+                Operations already requested keep the caps they were requested with",
+                self.chain_id,
+                describe_gas_config(config),
+                describe_gas_config(&current)
+            ),
+            None => format!(
+                "Remove the gas config of EVM chain {}, which was {}, \
+                its operations then have no fee caps",
+                self.chain_id,
+                describe_gas_config(&current)
+            ),
+        }
+    }
+}
+
+// UPDATE EVM CREATE2 FACTORY
+#[derive(CandidType, Clone, Deserialize, PartialEq, Debug)]
+pub struct UpdateEvmCreate2Factory {
+    pub chain_id: ChainId,
+    /// None goes back to the deterministic deployment proxy.
+    pub factory: Option<EvmAddress>,
+}
+
+#[async_trait]
+impl OperationTrait for UpdateEvmCreate2Factory {
+    async fn execute(self) -> Result<OperationResult, WalletError> {
+        with_setting_mut(|setting| setting.set_evm_create2_factory(self.chain_id, self.factory));
+
+        Ok(self.into())
+    }
+
+    fn validate_request(&self) -> Result<(), OperationError> {
+        Ok(())
+    }
+
+    fn method_name(&self) -> String {
+        "update_evm_create2_factory".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("Update CREATE2 factory of EVM chain {}", self.chain_id)
+    }
+
+    fn message(&self) -> String {
+        let current = with_setting(|setting| setting.evm_create2_factory(self.chain_id));
+
+        let factory = match self.factory {
+            Some(factory) => factory.to_string(),
+            None => "the deterministic deployment proxy".to_string(),
+        };
+
+        format!(
+            "Deploy with CREATE2 through {} on EVM chain {} instead of {}. \
+            Deployments already requested keep the factory they were requested with",
+            factory, self.chain_id, current
+        )
+    }
+}
@@ -1,17 +1,30 @@
 use std::fmt;
 
+use super::btc::batch::BtcBatchTransfer;
+use super::btc::bump::BtcBumpFee;
+use super::btc::cpfp::BtcChildPaysForParent;
 use super::btc::transfer::BtcTransfer;
-use super::evm::sign::{EvmSignMessage, EvmSignRawTransaction, EvmSignTranscation};
+use super::evm::contract::EvmContractCall;
+use super::evm::nft::{EvmApproveNft, EvmRevokeNftApproval, EvmTransferErc1155, EvmTransferErc721};
+use super::evm::sign::{
+    EvmPersonalSign, EvmSignMessage, EvmSignRawTransaction, EvmSignTranscation, EvmSignTypedData,
+    EvmSignUserOperation,
+};
 use super::evm::transfer::{EvmTransfer, EvmTransferErc20};
 use super::global::SendToken;
 use super::icp::transfer::{IcpTransfer, NotifyTopUp, TopUpTransfer};
 use super::inner::account::{
-    CreateAccount, HideAccount, RemoveAccount, RenameAccount, UnhideAccount,
+    ArchiveAccount, CreateAccount, HideAccount, RemoveAccount, RenameAccount, UnarchiveAccount,
+    UnhideAccount,
+};
+use super::inner::setting::{
+    UpdateCanisterSettings, UpdateEvmCreate2Factory, UpdateEvmGasConfig, UpdateEvmNetwork,
+    UpgradeCanister,
 };
-use super::inner::setting::{UpdateCanisterSettings, UpgradeCanister};
 use super::inner::user::{AddUser, RemoveUser};
 
 use b3_utils::ledger::TransferBlockIndex;
+use b3wallet_lib::ledger::evm::address::EvmAddress;
 use b3wallet_lib::ledger::evm::london::EvmTransaction1559;
 use b3wallet_lib::ledger::types::SendResult;
 use candid::{CandidType, Deserialize};
@@ -31,19 +44,35 @@ pub enum OperationResult {
     TopUpTransfered(TopUpTransfered),
     CanisterTopUped(CanisterTopUped),
     BtcTransfered(BtcTransfered),
+    BtcBatchTransfered(BtcBatchTransfered),
+    BtcFeeBumped(BtcFeeBumped),
+    BtcParentAccelerated(BtcParentAccelerated),
     SignerAdded(AddUser),
     SignerRemoved(RemoveUser),
     CanisterUpgraded(UpgradeCanister),
     CanisterSettingsUpdated(UpdateCanisterSettings),
+    EvmNetworkUpdated(UpdateEvmNetwork),
+    EvmGasConfigUpdated(UpdateEvmGasConfig),
+    EvmCreate2FactoryUpdated(UpdateEvmCreate2Factory),
     AccountCreated(CreateAccount),
     AccountRemoved(RemoveAccount),
     AccountRenamed(RenameAccount),
     AccountHidden(HideAccount),
     AccountUnhidden(UnhideAccount),
+    AccountArchived(ArchiveAccount),
+    AccountUnarchived(UnarchiveAccount),
     EvmTransactionSigned(EvmTransactionSigned),
     EvmRawTransactionSigned(EvmRawTransactionSigned),
     EvmContractDeployed(EvmContractDeployed),
     EvmMessageSigned(EvmMessageSigned),
+    EvmContractCalled(EvmContractCalled),
+    EvmTypedDataSigned(EvmTypedDataSigned),
+    EvmPersonalSigned(EvmPersonalSigned),
+    EvmUserOperationSigned(EvmUserOperationSigned),
+    EvmErc721Transfered(EvmErc721Transfered),
+    EvmErc1155Transfered(EvmErc1155Transfered),
+    EvmNftApproved(EvmNftApproved),
+    EvmNftApprovalRevoked(EvmNftApprovalRevoked),
 }
 
 #[rustfmt::skip]
@@ -53,24 +82,40 @@ impl fmt::Display for OperationResult {
             OperationResult::Empty(_) => write!(f, "Empty"),
             OperationResult::TokenSent(TokenSent(ref args, ref tx_id)) => write!(f, "TokenSent: from {} in {} to {} at tx {}", args.account_id, args.chain, args.to, tx_id),
             OperationResult::IcpTransfered(IcpTransfered(args, block_index)) => write!(f, "IcpTransfered: from {} to {} at block {}", args.account_id, args.to, block_index),
-            OperationResult::EvmTransfered(EvmTransfered(args, tx_hash)) => write!(f, "EvmTransfered: from {} to {} at tx {}", args.account_id, args.to, tx_hash),
-            OperationResult::EvmErc20Transfered(EvmErc20Transfered(args, tx_hash)) => write!(f, "EvmErc20Transfered: from {} to {} at tx {}", args.account_id, args.to, tx_hash),
+            OperationResult::EvmTransfered(EvmTransfered(args, tx_hash, _)) => write!(f, "EvmTransfered: from {} to {} at tx {}", args.account_id, args.to, tx_hash),
+            OperationResult::EvmErc20Transfered(EvmErc20Transfered(args, tx_hash, _)) => write!(f, "EvmErc20Transfered: from {} to {} at tx {}", args.account_id, args.to, tx_hash),
             OperationResult::BtcTransfered(BtcTransfered(args, tx_id)) => write!(f, "BtcTransfered: from {} to {} at tx {}", args.account_id, args.to, tx_id),
+            OperationResult::BtcBatchTransfered(BtcBatchTransfered(args, tx_id)) => write!(f, "BtcBatchTransfered: from {} to {} addresses at tx {}", args.account_id, args.outputs.len(), tx_id),
+            OperationResult::BtcFeeBumped(BtcFeeBumped(args, tx_id)) => write!(f, "BtcFeeBumped: from {} replaced tx {} by tx {}", args.account_id, args.txid, tx_id),
+            OperationResult::BtcParentAccelerated(BtcParentAccelerated(args, tx_id)) => write!(f, "BtcParentAccelerated: from {} spent {}:{} at tx {}", args.account_id, args.txid, args.vout, tx_id),
             OperationResult::TopUpTransfered(TopUpTransfered(args, block_index)) => write!(f, "TopUpTransfered: from {} to {} at block {}", args.account_id, args.canister_id, block_index),
             OperationResult::CanisterTopUped(CanisterTopUped(args, cycles)) => write!(f, "CanisterTopUped: from {} top up {} cycles for {}", args.account_id, cycles, args.canister_id),
             OperationResult::SignerAdded(_) => write!(f, "SignerAdded"),
             OperationResult::SignerRemoved(_) => write!(f, "SignerRemoved"),
             OperationResult::CanisterUpgraded(_) => write!(f, "CanisterUpgraded"),
             OperationResult::CanisterSettingsUpdated(_) => write!(f, "CanisterSettingsUpdated"),
+            OperationResult::EvmNetworkUpdated(_) => write!(f, "EvmNetworkUpdated"),
+            OperationResult::EvmGasConfigUpdated(_) => write!(f, "EvmGasConfigUpdated"),
+            OperationResult::EvmCreate2FactoryUpdated(_) => write!(f, "EvmCreate2FactoryUpdated"),
             OperationResult::AccountCreated(_) => write!(f, "AccountCreated"),
             OperationResult::AccountRemoved(_) => write!(f, "AccountRemoved"),
             OperationResult::AccountRenamed(_) => write!(f, "AccountRenamed"),
             OperationResult::AccountHidden(_) => write!(f, "AccountHidden"),
             OperationResult::AccountUnhidden(_) => write!(f, "AccountUnhidden"),
+            OperationResult::AccountArchived(_) => write!(f, "AccountArchived"),
+            OperationResult::AccountUnarchived(_) => write!(f, "AccountUnarchived"),
             OperationResult::EvmRawTransactionSigned(_) => write!(f, "EvmRawTransactionSigned"),
             OperationResult::EvmTransactionSigned(_) => write!(f, "EvmTransactionSigned"),
             OperationResult::EvmContractDeployed(_) => write!(f, "EvmContractDeployed"),
             OperationResult::EvmMessageSigned(_) => write!(f, "EvmMessageSigned"),
+            OperationResult::EvmContractCalled(EvmContractCalled(args, tx_hash, _)) => write!(f, "EvmContractCalled: {} on {} at tx {}", args.function, args.contract_address, tx_hash),
+            OperationResult::EvmTypedDataSigned(_) => write!(f, "EvmTypedDataSigned"),
+            OperationResult::EvmPersonalSigned(_) => write!(f, "EvmPersonalSigned"),
+            OperationResult::EvmUserOperationSigned(EvmUserOperationSigned(args, user_op_hash, _)) => write!(f, "EvmUserOperationSigned: {} of {}", user_op_hash, args.user_operation.sender),
+            OperationResult::EvmErc721Transfered(EvmErc721Transfered(args, tx_hash, _)) => write!(f, "EvmErc721Transfered: token {} of {} to {} at tx {}", args.token_id, args.contract_address, args.to, tx_hash),
+            OperationResult::EvmErc1155Transfered(EvmErc1155Transfered(args, tx_hash, _)) => write!(f, "EvmErc1155Transfered: {} of token {} of {} to {} at tx {}", args.amount, args.token_id, args.contract_address, args.to, tx_hash),
+            OperationResult::EvmNftApproved(EvmNftApproved(args, tx_hash, _)) => write!(f, "EvmNftApproved: {} on {} at tx {}", args.operator, args.contract_address, tx_hash),
+            OperationResult::EvmNftApprovalRevoked(EvmNftApprovalRevoked(args, tx_hash, _)) => write!(f, "EvmNftApprovalRevoked: {} on {} at tx {}", args.operator, args.contract_address, tx_hash),
         }
     }
 }
@@ -82,10 +127,10 @@ pub struct TokenSent(pub SendToken, pub SendResult);
 pub struct IcpTransfered(pub IcpTransfer, pub TransferBlockIndex);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
-pub struct EvmTransfered(pub EvmTransfer, pub String);
+pub struct EvmTransfered(pub EvmTransfer, pub String, pub Option<Vec<u8>>);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
-pub struct EvmErc20Transfered(pub EvmTransferErc20, pub String);
+pub struct EvmErc20Transfered(pub EvmTransferErc20, pub String, pub Option<Vec<u8>>);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
 pub struct TopUpTransfered(pub TopUpTransfer, pub TransferBlockIndex);
@@ -96,9 +141,18 @@ pub struct CanisterTopUped(pub NotifyTopUp, pub u128);
 #[derive(CandidType, Clone, Deserialize, Debug)]
 pub struct BtcTransfered(pub BtcTransfer, pub String);
 
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct BtcBatchTransfered(pub BtcBatchTransfer, pub String);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct BtcFeeBumped(pub BtcBumpFee, pub String);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct BtcParentAccelerated(pub BtcChildPaysForParent, pub String);
+
 #[derive(CandidType, Clone, Deserialize, Debug)]
 pub struct EvmContractDeployed {
-    pub contract_address: String,
+    pub contract_address: EvmAddress,
     pub transaction: EvmTransaction1559,
 }
 
@@ -106,10 +160,35 @@ pub struct EvmContractDeployed {
 pub struct EvmMessageSigned(pub EvmSignMessage, pub Vec<u8>);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
-pub struct EvmTransactionSigned(pub EvmSignTranscation, pub String);
+pub struct EvmTransactionSigned(pub EvmSignTranscation, pub String, pub Option<Vec<u8>>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmRawTransactionSigned(pub EvmSignRawTransaction, pub String, pub Option<Vec<u8>>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmContractCalled(pub EvmContractCall, pub String, pub Option<Vec<u8>>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmTypedDataSigned(pub EvmSignTypedData, pub Vec<u8>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmPersonalSigned(pub EvmPersonalSign, pub Vec<u8>);
+
+/// The userOpHash and the signed operation, as the JSON `eth_sendUserOperation` takes.
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmUserOperationSigned(pub EvmSignUserOperation, pub String, pub String);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmErc721Transfered(pub EvmTransferErc721, pub String, pub Option<Vec<u8>>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmErc1155Transfered(pub EvmTransferErc1155, pub String, pub Option<Vec<u8>>);
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EvmNftApproved(pub EvmApproveNft, pub String, pub Option<Vec<u8>>);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
-pub struct EvmRawTransactionSigned(pub EvmSignRawTransaction, pub String);
+pub struct EvmNftApprovalRevoked(pub EvmRevokeNftApproval, pub String, pub Option<Vec<u8>>);
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
 pub struct Empty;
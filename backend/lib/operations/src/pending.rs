@@ -6,6 +6,7 @@ use crate::{
     types::{ConsentMessage, ResponseMap, UserIds},
 };
 use b3_utils::{api::AppVersion, principal::StoredPrincipal, types::OperationId, NanoTimeStamp};
+use b3wallet_lib::balance::invalidate_balances;
 use candid::{CandidType, Deserialize};
 
 #[derive(CandidType, Clone, Deserialize, Debug)]
@@ -43,14 +44,18 @@ impl PendingOperation {
             NanoTimeStamp::days_from_now(7)
         };
 
-        let consent_message = ConsentMessage::new(&args.request, args.reason);
+        let mut request = args.request;
+
+        request.freeze();
+
+        let consent_message = ConsentMessage::new(&request, args.reason);
 
         PendingOperation {
             id,
             created_by,
             responses: ResponseMap::new(),
             allowed_signers: args.allowed_signers,
-            request: args.request,
+            request,
             status: OperationStatus::Pending,
             deadline,
             created_at: NanoTimeStamp::now(),
@@ -64,6 +69,9 @@ impl PendingOperation {
 
         let match_result = self.request.execute().await;
 
+        // the operation may have moved the funds of any account
+        invalidate_balances();
+
         match match_result {
             Ok(message) => proccess.succeed(message),
             Err(err) => proccess.fail(OperationError::ExecutionError(err.to_string())),